@@ -0,0 +1,554 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::EquationError,
+    expr::Expr,
+    interval::Interval,
+    item::{Constant, FunctionalOperator, LeftAssociativeOperator},
+    numeric::unsupported,
+};
+
+// how many times integration by parts can be nested before giving up
+const MAX_DEPTH: u32 = 8;
+// how many pieces the range of a series is split into to bound its remainder
+const REMAINDER_PIECES: usize = 16;
+
+/// Finds an antiderivative of the expression with respect to the variable, without a constant of integration.
+pub(crate) fn antiderivative(expr: &Expr, variable: &str) -> Option<Expr> {
+    integral(expr, variable, 0)
+}
+
+/// Differentiates the expression with respect to the variable.
+/// The steps (i.e. floor) have a derivative of zero wherever it exists, and gamma has none in terms of the other functions.
+pub(crate) fn derivative(expr: &Expr, x: &str) -> Option<Expr> {
+    if expr.is_free_of(x) {
+        return Some(Expr::Number(0.0));
+    }
+    Some(match expr {
+        Expr::Variable(_) => Expr::Number(1.0),
+        Expr::Negate(u) => -derivative(u, x)?,
+        Expr::Operator(op, lhs, rhs) => {
+            let (u, v) = (&**lhs, &**rhs);
+            match op {
+                LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => {
+                    Expr::operate(op, derivative(u, x)?, derivative(v, x)?)
+                }
+                LeftAssociativeOperator::Multiply => {
+                    derivative(u, x)? * v.clone() + u.clone() * derivative(v, x)?
+                }
+                LeftAssociativeOperator::Divide if v.is_free_of(x) => derivative(u, x)? / v.clone(),
+                LeftAssociativeOperator::Divide => {
+                    derivative(u, x)? / v.clone()
+                        - u.clone() * derivative(v, x)? / v.clone().pow(Expr::Number(2.0))
+                }
+                LeftAssociativeOperator::Power => power_derivative(u, v, x)?,
+                LeftAssociativeOperator::Root => {
+                    power_derivative(v, &(Expr::Number(1.0) / u.clone()), x)?
+                }
+            }
+        }
+        Expr::Function(func, u) => derivative(u, x)? * function_derivative(func, u)?,
+        _ => return None,
+    })
+}
+
+/// The Taylor series of the expression about `about` up to the power `order`, written lowest power first.
+/// With a `radius` it also bounds the error of the series within it of `about`, by the largest value of the next derivative there.
+pub(crate) fn series(
+    expr: &Expr,
+    x: &str,
+    about: f64,
+    order: u32,
+    radius: Option<f64>,
+) -> Result<(Expr, Option<f64>), EquationError> {
+    let differentiate = |expr: &Expr| {
+        derivative(expr, x).map(|d| d.simplify()).ok_or_else(|| {
+            unsupported(format!(
+                "The derivative of {} isn't known in closed form",
+                expr
+            ))
+        })
+    };
+    let shifted = Expr::variable(x) - Expr::Number(about);
+    let mut term = expr.clone();
+    let mut factorial = 1.0;
+    let mut sum = Expr::Number(0.0);
+    for k in 0..=order {
+        if k > 0 {
+            term = differentiate(&term)?;
+            factorial *= k as f64;
+        }
+        let value = term.evaluate_as(&HashMap::from([(x, about)]))?;
+        if !value.is_finite() {
+            return Err(unsupported(format!(
+                "The equation has no series about {}, where a derivative is {}",
+                about, value
+            )));
+        }
+        // whole derivatives give exact coefficients (i.e. `x^3/6`)
+        let coefficient = if value.fract() == 0.0 {
+            Expr::Number(value) / Expr::Number(factorial)
+        } else {
+            Expr::Number(value / factorial)
+        };
+        sum = sum + coefficient * shifted.clone().pow(Expr::Number(k as f64));
+    }
+    let remainder = match radius {
+        Some(radius) => {
+            // the lagrange remainder, with the next derivative bounded by interval arithmetic over pieces of the range (which keeps the bounds tighter)
+            let next = differentiate(&term)?;
+            let radius = radius.abs();
+            let edge = |i: usize| match i {
+                REMAINDER_PIECES => about + radius,
+                i => about - radius + 2.0 * radius * i as f64 / REMAINDER_PIECES as f64,
+            };
+            let mut largest: f64 = 0.0;
+            for i in 0..REMAINDER_PIECES {
+                let range = Interval::new(edge(i), edge(i + 1));
+                let bound = next.evaluate_as(&HashMap::from([(x, range)]))?;
+                largest = largest.max(bound.lo().abs()).max(bound.hi().abs());
+            }
+            Some(largest * radius.powi(order as i32 + 1) / (factorial * (order as f64 + 1.0)))
+        }
+        None => None,
+    };
+    Ok((sum, remainder))
+}
+
+// the derivative of `u^v`
+fn power_derivative(u: &Expr, v: &Expr, x: &str) -> Option<Expr> {
+    let value = u.clone().pow(v.clone());
+    Some(if v.is_free_of(x) {
+        v.clone() * u.clone().pow(v.clone() - Expr::Number(1.0)) * derivative(u, x)?
+    } else if u.is_free_of(x) {
+        match u {
+            Expr::Constant(Constant::E) => value * derivative(v, x)?,
+            _ => value * Expr::apply(FunctionalOperator::Ln, u.clone()) * derivative(v, x)?,
+        }
+    } else {
+        // u^v = e^(v ln(u))
+        value
+            * (derivative(v, x)? * Expr::apply(FunctionalOperator::Ln, u.clone())
+                + v.clone() * derivative(u, x)? / u.clone())
+    })
+}
+
+// the derivative of a function with respect to its own argument
+fn function_derivative(func: &FunctionalOperator, u: &Expr) -> Option<Expr> {
+    let f = |func: FunctionalOperator, x: Expr| Expr::apply(func, x);
+    let u = || u.clone();
+    let square = || u().pow(Expr::Number(2.0));
+    let one = || Expr::Number(1.0);
+    let two = || Expr::Number(2.0);
+    Some(match func {
+        FunctionalOperator::Ln => one() / u(),
+        FunctionalOperator::Log => one() / (u() * f(FunctionalOperator::Ln, 10.0.into())),
+        FunctionalOperator::Log2 => one() / (u() * f(FunctionalOperator::Ln, 2.0.into())),
+        FunctionalOperator::Sin => f(FunctionalOperator::Cos, u()),
+        FunctionalOperator::Cos => -f(FunctionalOperator::Sin, u()),
+        FunctionalOperator::Tan => f(FunctionalOperator::Sec, u()).pow(two()),
+        FunctionalOperator::Cot => -f(FunctionalOperator::Csc, u()).pow(two()),
+        FunctionalOperator::Sec => {
+            f(FunctionalOperator::Sec, u()) * f(FunctionalOperator::Tan, u())
+        }
+        FunctionalOperator::Csc => {
+            -(f(FunctionalOperator::Csc, u()) * f(FunctionalOperator::Cot, u()))
+        }
+        FunctionalOperator::Arcsin => one() / f(FunctionalOperator::Sqrt, one() - square()),
+        FunctionalOperator::Arccos => -(one() / f(FunctionalOperator::Sqrt, one() - square())),
+        FunctionalOperator::Arctan => one() / (one() + square()),
+        FunctionalOperator::Arccot => -(one() / (one() + square())),
+        FunctionalOperator::Arcsec => {
+            one()
+                / (f(FunctionalOperator::Abs, u()) * f(FunctionalOperator::Sqrt, square() - one()))
+        }
+        FunctionalOperator::Arccsc => {
+            -(one()
+                / (f(FunctionalOperator::Abs, u()) * f(FunctionalOperator::Sqrt, square() - one())))
+        }
+        FunctionalOperator::Sqrt => one() / (two() * f(FunctionalOperator::Sqrt, u())),
+        FunctionalOperator::Cbrt => {
+            one() / (Expr::Number(3.0) * f(FunctionalOperator::Cbrt, u()).pow(two()))
+        }
+        FunctionalOperator::Sinh => f(FunctionalOperator::Cosh, u()),
+        FunctionalOperator::Cosh => f(FunctionalOperator::Sinh, u()),
+        FunctionalOperator::Tanh => one() - f(FunctionalOperator::Tanh, u()).pow(two()),
+        FunctionalOperator::Arcsinh => one() / f(FunctionalOperator::Sqrt, square() + one()),
+        FunctionalOperator::Arccosh => one() / f(FunctionalOperator::Sqrt, square() - one()),
+        FunctionalOperator::Arctanh => one() / (one() - square()),
+        FunctionalOperator::Abs => f(FunctionalOperator::Sign, u()),
+        FunctionalOperator::Exp => f(FunctionalOperator::Exp, u()),
+        FunctionalOperator::Erf => {
+            two() * f(FunctionalOperator::Exp, -square())
+                / f(FunctionalOperator::Sqrt, Expr::Constant(Constant::Pi))
+        }
+        FunctionalOperator::Erfc => {
+            -(two() * f(FunctionalOperator::Exp, -square())
+                / f(FunctionalOperator::Sqrt, Expr::Constant(Constant::Pi)))
+        }
+        FunctionalOperator::Frac => one(),
+        FunctionalOperator::Sign
+        | FunctionalOperator::Floor
+        | FunctionalOperator::Ceil
+        | FunctionalOperator::Round
+        | FunctionalOperator::Trunc => Expr::Number(0.0),
+        // these need the digamma function
+        FunctionalOperator::Gamma | FunctionalOperator::Lgamma | FunctionalOperator::Factorial => {
+            return None
+        }
+    })
+}
+
+fn integral(expr: &Expr, x: &str, depth: u32) -> Option<Expr> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    if expr.is_free_of(x) {
+        return Some(expr.clone() * Expr::variable(x));
+    }
+    if let Some(coefficients) = expr.polynomial(x) {
+        return Some(integrate_polynomial(&coefficients, x));
+    }
+    match expr {
+        Expr::Negate(u) => integral(u, x, depth).map(|u| -u),
+        Expr::Operator(op, lhs, rhs) => match op {
+            LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => Some(
+                Expr::operate(op, integral(lhs, x, depth)?, integral(rhs, x, depth)?),
+            ),
+            LeftAssociativeOperator::Multiply => product(lhs, rhs, x, depth),
+            LeftAssociativeOperator::Divide => quotient(lhs, rhs, x, depth),
+            LeftAssociativeOperator::Power => power(lhs, rhs, x),
+            LeftAssociativeOperator::Root if lhs.is_free_of(x) => {
+                power(rhs, &(Expr::Number(1.0) / *lhs.clone()), x)
+            }
+            LeftAssociativeOperator::Root => None,
+        },
+        // f(a x + b) integrates to F(a x + b) / a
+        Expr::Function(func, u) => {
+            let (a, _) = u.linear(x)?;
+            Some(function(func, u)? / a)
+        }
+        _ => None,
+    }
+}
+
+// sums the integrals of the terms of a polynomial, written highest power first (i.e. `x^3/3 + x`)
+fn integrate_polynomial(coefficients: &[Expr], x: &str) -> Expr {
+    coefficients
+        .iter()
+        .enumerate()
+        .rev()
+        .fold(Expr::Number(0.0), |sum, (i, c)| {
+            sum + monomial(c.clone(), x, i as f64)
+        })
+}
+
+// the integral of `c x^k`
+fn monomial(c: Expr, x: &str, k: f64) -> Expr {
+    if k == -1.0 {
+        c * Expr::apply(
+            FunctionalOperator::Ln,
+            Expr::apply(FunctionalOperator::Abs, Expr::variable(x)),
+        )
+    } else {
+        c * Expr::variable(x).pow(Expr::Number(k + 1.0)) / Expr::Number(k + 1.0)
+    }
+}
+
+fn product(lhs: &Expr, rhs: &Expr, x: &str, depth: u32) -> Option<Expr> {
+    if lhs.is_free_of(x) {
+        return Some(lhs.clone() * integral(rhs, x, depth)?);
+    }
+    if rhs.is_free_of(x) {
+        return Some(rhs.clone() * integral(lhs, x, depth)?);
+    }
+    // by parts, differentiating the polynomial until it is gone
+    for (u, dv) in [(lhs, rhs), (rhs, lhs)] {
+        if let Some(p) = u.polynomial(x) {
+            if let Some(result) = parts(u, &p, dv, x, depth) {
+                return Some(result);
+            }
+        }
+    }
+    // (a + b) c is a c + b c
+    for (sum, other) in [(lhs, rhs), (rhs, lhs)] {
+        if let Expr::Operator(
+            op @ (LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract),
+            a,
+            b,
+        ) = sum
+        {
+            return Some(Expr::operate(
+                op,
+                integral(&(*a.clone() * other.clone()), x, depth)?,
+                integral(&(*b.clone() * other.clone()), x, depth)?,
+            ));
+        }
+    }
+    None
+}
+
+// integrates `u dv` as `u v - ∫ v du`, where `p` are the coefficients of the polynomial `u`
+fn parts(u: &Expr, p: &[Expr], dv: &Expr, x: &str, depth: u32) -> Option<Expr> {
+    // logarithms of `a x` are differentiated instead, as `P ln(a x) - ∫ P / x` where P is the integral of u
+    if let Expr::Function(
+        log @ (FunctionalOperator::Ln | FunctionalOperator::Log | FunctionalOperator::Log2),
+        v,
+    ) = dv
+    {
+        let (_, b) = v.linear(x)?;
+        if b != Expr::Number(0.0) {
+            return None;
+        }
+        let rest = p
+            .iter()
+            .enumerate()
+            .rev()
+            .fold(Expr::Number(0.0), |sum, (i, c)| {
+                let n = Expr::Number(i as f64 + 1.0);
+                sum + c.clone() * Expr::variable(x).pow(n.clone()) / n.clone().pow(2.0.into())
+            });
+        let rest = match log {
+            FunctionalOperator::Log => rest / Expr::apply(FunctionalOperator::Ln, 10.0.into()),
+            FunctionalOperator::Log2 => rest / Expr::apply(FunctionalOperator::Ln, 2.0.into()),
+            _ => rest,
+        };
+        return Some(integrate_polynomial(p, x) * dv.clone() - rest);
+    }
+    if dv.polynomial(x).is_some() {
+        return None;
+    }
+    let v = integral(dv, x, depth + 1)?;
+    let du: Vec<Expr> = p
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, c)| Expr::Number(i as f64) * c.clone())
+        .collect();
+    let rest = integral(&(Expr::from_polynomial(&du, x) * v.clone()), x, depth + 1)?;
+    Some(u.clone() * v - rest)
+}
+
+fn quotient(lhs: &Expr, rhs: &Expr, x: &str, depth: u32) -> Option<Expr> {
+    if rhs.is_free_of(x) {
+        return Some(integral(lhs, x, depth)? / rhs.clone());
+    }
+    // a constant factor of the denominator (i.e. `2 e^x`) divides the rest
+    if let Expr::Operator(LeftAssociativeOperator::Multiply, a, b) = rhs {
+        for (k, u) in [(a, b), (b, a)] {
+            if k.is_free_of(x) {
+                return Some(quotient(lhs, u, x, depth + 1)? / (**k).clone());
+            }
+        }
+    }
+    // a polynomial over a power of x is a sum of powers of x
+    let degree = match rhs {
+        Expr::Variable(_) => Some(1.0),
+        Expr::Operator(LeftAssociativeOperator::Power, base, exponent)
+            if **base == Expr::variable(x) =>
+        {
+            match **exponent {
+                Expr::Number(n) => Some(n),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    if let (Some(n), Some(p)) = (degree, lhs.polynomial(x)) {
+        return Some(
+            p.iter()
+                .enumerate()
+                .rev()
+                .fold(Expr::Number(0.0), |sum, (i, c)| {
+                    sum + monomial(c.clone(), x, i as f64 - n)
+                }),
+        );
+    }
+    if !lhs.is_free_of(x) {
+        // `p(x) / c^(a x + b)` is `p(x) c^-(a x + b)`, which the operators write back as a quotient (i.e. `x / e^x` for `x e^-x`)
+        return match rhs {
+            Expr::Operator(LeftAssociativeOperator::Power, base, exponent)
+                if base.is_free_of(x) =>
+            {
+                let reciprocal = Expr::binary(
+                    LeftAssociativeOperator::Power,
+                    (**base).clone(),
+                    -*exponent.clone(),
+                );
+                product(lhs, &reciprocal, x, depth + 1)
+            }
+            _ => None,
+        };
+    }
+    let c = lhs.clone();
+    if let Some((a, _)) = rhs.linear(x) {
+        return Some(
+            c * Expr::apply(
+                FunctionalOperator::Ln,
+                Expr::apply(FunctionalOperator::Abs, rhs.clone()),
+            ) / a,
+        );
+    }
+    match rhs {
+        Expr::Operator(LeftAssociativeOperator::Power, base, exponent) => {
+            // not through `integral`, which would write `c u^-n` back as `c / u^n`
+            Some(c * power(base, &-*exponent.clone(), x)?)
+        }
+        Expr::Function(FunctionalOperator::Sqrt, u) => {
+            integral(&(c * (**u).clone().pow(Expr::Number(-0.5))), x, depth)
+        }
+        Expr::Function(func, u) => {
+            let reciprocal = match func {
+                FunctionalOperator::Sin => FunctionalOperator::Csc,
+                FunctionalOperator::Cos => FunctionalOperator::Sec,
+                FunctionalOperator::Tan => FunctionalOperator::Cot,
+                FunctionalOperator::Csc => FunctionalOperator::Sin,
+                FunctionalOperator::Sec => FunctionalOperator::Cos,
+                FunctionalOperator::Cot => FunctionalOperator::Tan,
+                FunctionalOperator::Exp => {
+                    return integral(
+                        &(c * Expr::apply(FunctionalOperator::Exp, -*u.clone())),
+                        x,
+                        depth,
+                    )
+                }
+                _ => return None,
+            };
+            integral(&(c * Expr::apply(reciprocal, *u.clone())), x, depth)
+        }
+        _ => quadratic(c, rhs, x),
+    }
+}
+
+// the integral of `c / (a x^2 + b x + d)` with numbers for a, b and d
+fn quadratic(c: Expr, denominator: &Expr, x: &str) -> Option<Expr> {
+    let coefficients = denominator.polynomial(x)?;
+    let [Expr::Number(d), Expr::Number(b), Expr::Number(a)] = coefficients.as_slice() else {
+        return None;
+    };
+    let discriminant = b * b - 4.0 * a * d;
+    let linear = Expr::Number(2.0 * a) * Expr::variable(x) + Expr::Number(*b);
+    Some(if discriminant < 0.0 {
+        let root = Expr::apply(FunctionalOperator::Sqrt, Expr::Number(-discriminant));
+        Expr::Number(2.0) * c * Expr::apply(FunctionalOperator::Arctan, linear / root.clone())
+            / root
+    } else if discriminant > 0.0 {
+        // partial fractions over the two real roots
+        let root = Expr::apply(FunctionalOperator::Sqrt, Expr::Number(discriminant));
+        c * Expr::apply(
+            FunctionalOperator::Ln,
+            Expr::apply(
+                FunctionalOperator::Abs,
+                (linear.clone() - root.clone()) / (linear + root.clone()),
+            ),
+        ) / root
+    } else {
+        -(Expr::Number(2.0) * c / linear)
+    })
+}
+
+fn power(base: &Expr, exponent: &Expr, x: &str) -> Option<Expr> {
+    if exponent.is_free_of(x) {
+        // (a x + b)^n integrates to (a x + b)^(n + 1) / (a (n + 1))
+        let (a, _) = base.linear(x)?;
+        if *exponent == Expr::Number(-1.0) {
+            return Some(
+                Expr::apply(
+                    FunctionalOperator::Ln,
+                    Expr::apply(FunctionalOperator::Abs, base.clone()),
+                ) / a,
+            );
+        }
+        let n = exponent.clone() + Expr::Number(1.0);
+        return Some(match n {
+            // a whole reciprocal is written as a coefficient (i.e. `2 * x^0.5` rather than `x^0.5/0.5`)
+            Expr::Number(m) if m.recip().fract() == 0.0 => {
+                Expr::Number(m.recip()) * base.clone().pow(n) / a
+            }
+            _ => base.clone().pow(n.clone()) / (n * a),
+        });
+    }
+    if base.is_free_of(x) {
+        // c^(a x + b) integrates to c^(a x + b) / (a ln(c))
+        let (a, _) = exponent.linear(x)?;
+        let value = base.clone().pow(exponent.clone());
+        return Some(match base {
+            Expr::Constant(Constant::E) => value / a,
+            _ => value / (a * Expr::apply(FunctionalOperator::Ln, base.clone())),
+        });
+    }
+    None
+}
+
+// the integral of a function with respect to its own argument
+fn function(func: &FunctionalOperator, u: &Expr) -> Option<Expr> {
+    let f = |func: FunctionalOperator, x: Expr| Expr::apply(func, x);
+    let u = || u.clone();
+    let abs_ln = |x: Expr| f(FunctionalOperator::Ln, f(FunctionalOperator::Abs, x));
+    let square = || u().pow(Expr::Number(2.0));
+    let one = || Expr::Number(1.0);
+    let two = || Expr::Number(2.0);
+    // u f(u) - ∫ u f'(u) du, for the inverse functions
+    let by_parts = |rest: Expr| u() * f(func.clone(), u()) + rest;
+    let gaussian = || {
+        f(FunctionalOperator::Exp, -square())
+            / f(FunctionalOperator::Sqrt, Expr::Constant(Constant::Pi))
+    };
+    Some(match func {
+        FunctionalOperator::Sin => -f(FunctionalOperator::Cos, u()),
+        FunctionalOperator::Cos => f(FunctionalOperator::Sin, u()),
+        FunctionalOperator::Tan => -abs_ln(f(FunctionalOperator::Cos, u())),
+        FunctionalOperator::Cot => abs_ln(f(FunctionalOperator::Sin, u())),
+        FunctionalOperator::Sec => {
+            abs_ln(f(FunctionalOperator::Sec, u()) + f(FunctionalOperator::Tan, u()))
+        }
+        FunctionalOperator::Csc => {
+            -abs_ln(f(FunctionalOperator::Csc, u()) + f(FunctionalOperator::Cot, u()))
+        }
+        FunctionalOperator::Exp => f(FunctionalOperator::Exp, u()),
+        FunctionalOperator::Sinh => f(FunctionalOperator::Cosh, u()),
+        FunctionalOperator::Cosh => f(FunctionalOperator::Sinh, u()),
+        FunctionalOperator::Tanh => f(FunctionalOperator::Ln, f(FunctionalOperator::Cosh, u())),
+        FunctionalOperator::Sqrt => {
+            two() * u() * f(FunctionalOperator::Sqrt, u()) / Expr::Number(3.0)
+        }
+        FunctionalOperator::Cbrt => {
+            Expr::Number(3.0) * u() * f(FunctionalOperator::Cbrt, u()) / Expr::Number(4.0)
+        }
+        FunctionalOperator::Ln => by_parts(-u()),
+        FunctionalOperator::Log => by_parts(-u() / f(FunctionalOperator::Ln, 10.0.into())),
+        FunctionalOperator::Log2 => by_parts(-u() / f(FunctionalOperator::Ln, 2.0.into())),
+        FunctionalOperator::Arcsin => by_parts(f(FunctionalOperator::Sqrt, one() - square())),
+        FunctionalOperator::Arccos => by_parts(-f(FunctionalOperator::Sqrt, one() - square())),
+        FunctionalOperator::Arctan => {
+            by_parts(-f(FunctionalOperator::Ln, one() + square()) / two())
+        }
+        FunctionalOperator::Arccot => by_parts(f(FunctionalOperator::Ln, one() + square()) / two()),
+        FunctionalOperator::Arcsec => by_parts(-f(
+            FunctionalOperator::Arccosh,
+            f(FunctionalOperator::Abs, u()),
+        )),
+        FunctionalOperator::Arccsc => by_parts(f(
+            FunctionalOperator::Arccosh,
+            f(FunctionalOperator::Abs, u()),
+        )),
+        FunctionalOperator::Arcsinh => by_parts(-f(FunctionalOperator::Sqrt, square() + one())),
+        FunctionalOperator::Arccosh => by_parts(-f(FunctionalOperator::Sqrt, square() - one())),
+        FunctionalOperator::Arctanh => {
+            by_parts(f(FunctionalOperator::Ln, one() - square()) / two())
+        }
+        FunctionalOperator::Abs => u() * f(FunctionalOperator::Abs, u()) / two(),
+        FunctionalOperator::Sign => f(FunctionalOperator::Abs, u()),
+        FunctionalOperator::Erf => by_parts(gaussian()),
+        FunctionalOperator::Erfc => by_parts(-gaussian()),
+        // the steps and the gamma function don't have elementary integrals
+        FunctionalOperator::Floor
+        | FunctionalOperator::Ceil
+        | FunctionalOperator::Round
+        | FunctionalOperator::Trunc
+        | FunctionalOperator::Frac
+        | FunctionalOperator::Gamma
+        | FunctionalOperator::Lgamma
+        | FunctionalOperator::Factorial => return None,
+    })
+}
@@ -0,0 +1,235 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    equation::Equation,
+    error::{EquationError, EquationErrorType},
+    item::{Constant, FunctionalOperator, LeftAssociativeOperator},
+    numeric::{unsupported, Evaluator, Numeric, NumericEvaluator},
+};
+
+/// A step of a [`Compiled`] equation, whose operands are the indices of earlier steps.
+#[derive(Debug, Clone)]
+pub enum Node {
+    /// A number written in the equation or set as the value of a variable.
+    Value(f64),
+    /// A named constant.
+    Constant(Constant),
+    /// The variable at this index of [`Compiled::variables`].
+    Variable(usize),
+    /// The negative of a step (unary minus).
+    Negate(usize),
+    /// A function of a step.
+    Function(FunctionalOperator, usize),
+    /// An operator between two steps.
+    Operator(LeftAssociativeOperator, usize, usize),
+}
+
+impl Node {
+    /// Gives the indices of the steps this one is worked out from.
+    pub fn operands(&self) -> Vec<usize> {
+        match self {
+            Node::Value(_) | Node::Constant(_) | Node::Variable(_) => Vec::new(),
+            Node::Negate(x) | Node::Function(_, x) => vec![*x],
+            Node::Operator(_, lhs, rhs) => vec![*lhs, *rhs],
+        }
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Node::Value(a), Node::Value(b)) => a.to_bits() == b.to_bits(),
+            (Node::Constant(a), Node::Constant(b)) => a == b,
+            (Node::Variable(a), Node::Variable(b)) => a == b,
+            (Node::Negate(a), Node::Negate(b)) => a == b,
+            (Node::Function(f, a), Node::Function(g, b)) => f == g && a == b,
+            (Node::Operator(op, a, b), Node::Operator(other, c, d)) => {
+                op == other && a == c && b == d
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Node {}
+
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            // -0 isn't merged with 0, since they divide differently
+            Node::Value(value) => value.to_bits().hash(state),
+            Node::Constant(constant) => constant.hash(state),
+            Node::Variable(i) | Node::Negate(i) => i.hash(state),
+            Node::Function(func, x) => {
+                func.hash(state);
+                x.hash(state);
+            }
+            Node::Operator(op, lhs, rhs) => {
+                op.hash(state);
+                lhs.hash(state);
+                rhs.hash(state);
+            }
+        }
+    }
+}
+
+/// An equation compiled into a graph of steps, where every subtree that appears more than once (i.e. an equation set into several variables with [`Equation::set_equation`]) is a single step.
+/// Each step is worked out once per evaluation, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Compiled {
+    nodes: Vec<Node>,
+    root: usize,
+    variables: Vec<String>,
+}
+
+impl Compiled {
+    /// Gives the steps in the order they are worked out, where every step only uses earlier ones.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+    /// Gives the index of the step that is the value of the equation.
+    pub fn root(&self) -> usize {
+        self.root
+    }
+    /// Gives the variables in the order their values are given to [`evaluate`](Self::evaluate).
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+    /// Gives how many times every step is used, by later steps or as the value of the equation, where a step used more than once is shared.
+    pub fn uses(&self) -> Vec<usize> {
+        let mut uses = vec![0; self.nodes.len()];
+        for node in &self.nodes {
+            for operand in node.operands() {
+                uses[operand] += 1;
+            }
+        }
+        uses[self.root()] += 1;
+        uses
+    }
+    /// Evaluates the equation with the values of its variables, in the order of [`variables`](Self::variables).
+    pub fn evaluate(&self, values: &[f64]) -> Result<f64, EquationError> {
+        self.evaluate_as(values)
+    }
+    /// Evaluates the equation in any number type, with the values of its variables in the order of [`variables`](Self::variables).
+    pub fn evaluate_as<N: Numeric>(&self, values: &[N]) -> Result<N, EquationError> {
+        if values.len() != self.variables.len() {
+            return Err(unsupported(format!(
+                "{} values for the {} variables {:?}",
+                values.len(),
+                self.variables.len(),
+                self.variables
+            )));
+        }
+        let evaluator = NumericEvaluator::<N>::new();
+        let mut steps: Vec<N> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let step = match node {
+                Node::Value(value) => evaluator.value(*value)?,
+                Node::Constant(constant) => evaluator.constant(constant)?,
+                Node::Variable(i) => values[*i].clone(),
+                Node::Negate(x) => evaluator.negate(steps[*x].clone())?,
+                Node::Function(func, x) => evaluator.function(func, steps[*x].clone())?,
+                Node::Operator(op, lhs, rhs) => {
+                    evaluator.operator(op, steps[*lhs].clone(), steps[*rhs].clone())?
+                }
+            };
+            steps.push(step);
+        }
+        Ok(steps.swap_remove(self.root))
+    }
+    /// Evaluates the equation with the values of its variables by name.
+    pub fn evaluate_with_bindings(
+        &self,
+        bindings: &HashMap<&str, f64>,
+    ) -> Result<f64, EquationError> {
+        let values = self
+            .variables
+            .iter()
+            .map(|variable| {
+                bindings.get(variable.as_str()).copied().ok_or_else(|| {
+                    EquationError::new(
+                        format!("Variable {} not set", variable),
+                        EquationErrorType::UnsetVariable,
+                    )
+                })
+            })
+            .collect::<Result<Vec<f64>, EquationError>>()?;
+        self.evaluate(&values)
+    }
+}
+
+/// Compiles an equation whose variables are given in order, sharing the steps of identical subtrees.
+pub(crate) fn compile(
+    equation: &Equation,
+    variables: Vec<String>,
+) -> Result<Compiled, EquationError> {
+    let builder = Builder {
+        variables: &variables,
+        nodes: RefCell::new(Vec::new()),
+        indices: RefCell::new(HashMap::new()),
+    };
+    let root = equation.evaluate_with(&builder)?;
+    Ok(Compiled {
+        nodes: builder.nodes.into_inner(),
+        root,
+        variables,
+    })
+}
+
+// builds the steps of a group, where the value of a subtree is the index of its step
+#[derive(Debug)]
+struct Builder<'a> {
+    variables: &'a [String],
+    nodes: RefCell<Vec<Node>>,
+    indices: RefCell<HashMap<Node, usize>>,
+}
+
+impl Builder<'_> {
+    // the step of a node, which is only added the first time it is seen
+    fn intern(&self, node: Node) -> usize {
+        let mut indices = self.indices.borrow_mut();
+        if let Some(index) = indices.get(&node) {
+            return *index;
+        }
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(node.clone());
+        indices.insert(node, nodes.len() - 1);
+        nodes.len() - 1
+    }
+}
+
+impl Evaluator for Builder<'_> {
+    type Value = usize;
+    fn value(&self, value: f64) -> Result<usize, EquationError> {
+        Ok(self.intern(Node::Value(value)))
+    }
+    fn constant(&self, constant: &Constant) -> Result<usize, EquationError> {
+        Ok(self.intern(Node::Constant(constant.clone())))
+    }
+    fn variable(&self, name: &str) -> Option<usize> {
+        let i = self
+            .variables
+            .iter()
+            .position(|variable| variable == name)?;
+        Some(self.intern(Node::Variable(i)))
+    }
+    fn negate(&self, x: usize) -> Result<usize, EquationError> {
+        Ok(self.intern(Node::Negate(x)))
+    }
+    fn function(&self, func: &FunctionalOperator, x: usize) -> Result<usize, EquationError> {
+        Ok(self.intern(Node::Function(func.clone(), x)))
+    }
+    fn operator(
+        &self,
+        op: &LeftAssociativeOperator,
+        lhs: usize,
+        rhs: usize,
+    ) -> Result<usize, EquationError> {
+        Ok(self.intern(Node::Operator(op.clone(), lhs, rhs)))
+    }
+}
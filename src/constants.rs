@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::item::Constant;
+
+// the physics pack, in SI units (CODATA 2018), the first seven are exact by definition
+const PHYSICS: [(&str, f64); 14] = [
+    ("c", 299_792_458.0),
+    ("h", 6.626_070_15e-34),
+    ("k_B", 1.380_649e-23),
+    ("N_A", 6.022_140_76e23),
+    ("q_e", 1.602_176_634e-19),
+    ("g", 9.806_65),
+    ("R", 8.314_462_618_153_24),
+    ("hbar", 1.054_571_817e-34),
+    ("G", 6.674_30e-11),
+    ("m_e", 9.109_383_701_5e-31),
+    ("m_p", 1.672_621_923_69e-27),
+    ("epsilon_0", 8.854_187_812_8e-12),
+    ("mu_0", 1.256_637_062_12e-6),
+    ("sigma", 5.670_374_419e-8),
+];
+
+/// The named constants that are recognised while parsing an equation, see [`Equation::with_constants`](crate::Equation::with_constants).
+/// Names in the table are parsed as constants instead of variables, so they can't be set and aren't listed as variables.
+/// The default table has `pi`, `e`, `tau` and `deg`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constants {
+    values: HashMap<String, Constant>,
+}
+
+impl Constants {
+    /// Creates an empty table, where every name is a variable.
+    pub fn new() -> Constants {
+        Constants {
+            values: HashMap::new(),
+        }
+    }
+    /// Creates the default table along with the physics pack: `c`, `h`, `hbar`, `k_B`, `N_A`, `q_e` (the elementary charge), `g` (standard gravity), `R`, `G`, `m_e`, `m_p`, `epsilon_0`, `mu_0` and `sigma` (Stefan-Boltzmann), all in SI units.
+    pub fn physics() -> Constants {
+        let mut constants = Constants::default();
+        for (name, value) in PHYSICS {
+            constants.insert(name, value);
+        }
+        constants
+    }
+    /// Adds a constant with the given value, replacing any constant with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, value: f64) -> &mut Self {
+        self.values.insert(name.into(), Constant::Custom(value));
+        self
+    }
+    /// Removes a constant, so that its name is parsed as a variable (i.e. to use `e` as a variable).
+    pub fn remove(&mut self, name: &str) -> &mut Self {
+        self.values.remove(name);
+        self
+    }
+    /// Gives the constant with the given name, if there is one.
+    pub fn get(&self, name: &str) -> Option<Constant> {
+        self.values.get(name).cloned()
+    }
+    /// Gives the names of every constant in the table.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
+
+impl Default for Constants {
+    fn default() -> Self {
+        Constants {
+            values: HashMap::from([
+                ("pi".to_string(), Constant::Pi),
+                ("e".to_string(), Constant::E),
+                ("tau".to_string(), Constant::Tau),
+                ("deg".to_string(), Constant::Deg),
+            ]),
+        }
+    }
+}
@@ -1,23 +1,105 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter},
+    ops::RangeInclusive,
+};
+
+#[cfg(feature = "complex")]
+use num_complex::Complex64;
+
+#[cfg(feature = "jit")]
+use crate::jit::{self, CompiledFn};
+#[cfg(feature = "precision")]
+use crate::precision::{BigFloat, PrecisionEvaluator};
 
 use crate::{
+    calculus,
+    compile::{self, Compiled},
+    constants::Constants,
+    equivalence::{self, Equivalence},
     error::{EquationError, EquationErrorType},
-    item::{Item, Operator},
+    expr::ExprEvaluator,
+    integration::{self, Integral, IntegrationOptions},
+    interval::Interval,
+    item::{Constant, FunctionalOperator, Item, LeftAssociativeOperator, Operator},
+    limit::{self, Direction},
+    numeric::{AngleEvaluator, Evaluator, Numeric, NumericEvaluator},
+    optimize::{self, OptimizeMethod, Optimum},
     parse::ParseStream,
+    polynomial::{Polynomial, PolynomialEvaluator},
+    sample,
+    units::{Quantity, Unit},
 };
 
 /// The equation Struct is used to solve an equation.
-#[derive(Debug)]
+/// Equations are equal when they are written the same way in the same angle mode, see [`canonicalize`](Self::canonicalize) to compare them up to rearrangement.
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Equation {
     inner: Group,
+    angle_mode: AngleMode,
+}
+
+/// The unit that the trigonometric functions take their angles in, and that the inverse trigonometric functions give them in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AngleMode {
+    /// Angles in radians, a full turn is 2 pi.
+    #[default]
+    Radians,
+    /// Angles in degrees, a full turn is 360.
+    Degrees,
+    /// Angles in gradians, a full turn is 400.
+    Gradians,
 }
 
 /// The Group struct is used to represent a group of items (like those inside of bracets).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Group {
     items: Vec<Item>,
 }
 
+// a partially evaluated item
+#[derive(Debug)]
+enum Term<N> {
+    Value(N),
+    Operator(Operator),
+}
+
+// a subtraction with nothing on its left is a unary minus
+fn is_unary_minus<N>(items: &[Term<N>], i: usize) -> bool {
+    matches!(
+        items.get(i),
+        Some(Term::Operator(Operator::LeftAssociative(
+            LeftAssociativeOperator::Subtract
+        )))
+    ) && (i == 0 || matches!(items.get(i - 1), Some(Term::Operator(_))))
+}
+
+// removes the value at `i`, along with a unary minus in front of it
+fn take_operand<E: Evaluator>(
+    evaluator: &E,
+    items: &mut Vec<Term<E::Value>>,
+    i: usize,
+) -> Result<E::Value, EquationError> {
+    let negate = is_unary_minus(items, i);
+    if negate && i + 1 < items.len() {
+        items.remove(i);
+    }
+    if i >= items.len() {
+        return Err(EquationError::new(
+            "Missing value".to_string(),
+            EquationErrorType::MissingItems,
+        ));
+    }
+    match items.remove(i) {
+        Term::Value(val) if negate => evaluator.negate(val),
+        Term::Value(val) => Ok(val),
+        Term::Operator(op) => Err(EquationError::new(
+            format!("Unexpected token {:?}", op),
+            EquationErrorType::UnexpectedToken,
+        )),
+    }
+}
+
 // private little thing for equation validation
 enum Previous {
     Function,
@@ -33,21 +115,27 @@ impl Group {
     /// Checks if the group is a valid equation.
     pub fn validate(&self) -> Result<(), EquationError> {
         let mut previous = Previous::None;
-        let mut x = self.items.iter();
-        while let Some(next) = x.next() {
+        for next in self.items.iter() {
             match (&previous, next) {
-                (Previous::None, Item::Value(_) | Item::Variable(_) | Item::Group(_)) => {
-                    previous = Previous::Value
-                }
+                (
+                    Previous::None,
+                    Item::Value(_) | Item::Constant(_) | Item::Variable(_) | Item::Group(_),
+                ) => previous = Previous::Value,
                 (Previous::None, Item::Operator(Operator::Functional(_))) => {
                     previous = Previous::Function
                 }
                 (Previous::Value, Item::Operator(Operator::LeftAssociative(_))) => {
                     previous = Previous::Function
                 }
-                (Previous::Function, Item::Group(_) | Item::Variable(_) | Item::Value(_)) => {
-                    previous = Previous::Value
-                }
+                (
+                    Previous::Function,
+                    Item::Group(_) | Item::Constant(_) | Item::Variable(_) | Item::Value(_),
+                ) => previous = Previous::Value,
+                // unary minus
+                (
+                    Previous::None | Previous::Function,
+                    Item::Operator(Operator::LeftAssociative(LeftAssociativeOperator::Subtract)),
+                ) => previous = Previous::Function,
 
                 (_, _) => {
                     return Err(EquationError::new(
@@ -61,8 +149,7 @@ impl Group {
     }
     /// Sets the value of a variable in the group.
     pub fn set_value(&mut self, variable: &str, item: Item) {
-        let mut x = self.items.iter_mut();
-        while let Some(next) = x.next() {
+        for next in self.items.iter_mut() {
             match next {
                 Item::Variable(val) if val == variable => {
                     *next = item.clone();
@@ -76,74 +163,90 @@ impl Group {
     }
     /// Evaluates the value of the group
     pub fn evaluate(&self) -> Result<f64, EquationError> {
-        // first lets iter over all groups and evaluate them into values
-        let mut items = self.items.clone();
-        let mut i = 0;
-        while i < items.len() {
-            if let Some(Item::Group(val)) = items.get(i) {
+        self.evaluate_as()
+    }
+    /// Evaluates the value of the group in any number type.
+    pub fn evaluate_as<N: Numeric>(&self) -> Result<N, EquationError> {
+        self.evaluate_with(&NumericEvaluator::<N>::new())
+    }
+    /// Evaluates the value of the group with an evaluator.
+    pub(crate) fn evaluate_with<E: Evaluator>(
+        &self,
+        evaluator: &E,
+    ) -> Result<E::Value, EquationError> {
+        // first lets turn all groups, constants and variables into values
+        let mut items = Vec::with_capacity(self.items.len());
+        for item in self.items.iter() {
+            items.push(match item {
+                Item::Value(val) => Term::Value(evaluator.value(*val)?),
+                Item::Constant(constant) => Term::Value(evaluator.constant(constant)?),
                 // this is recursive but should never exceed the limit as long as someone doesnt do something stupid...
-                let val = val.evaluate()?;
-                items[i] = Item::Value(val);
-            }
-            i += 1;
-        }
-        // now we evaluate all functions
-        let mut i = 0;
-        while i < items.len() - 1 {
-            let next = items.get(i).unwrap().clone();
-            if let Item::Operator(Operator::Functional(func)) = next {
-                let to_eval = items.remove(i + 1);
-                if let Item::Value(to_eval) = to_eval {
-                    let val = func.evaluate(to_eval);
-                    items[i] = Item::Value(val);
-                } else if let Item::Variable(var) = to_eval {
-                    return Err(EquationError::new(
+                Item::Group(val) => Term::Value(val.evaluate_with(evaluator)?),
+                Item::Variable(var) => Term::Value(evaluator.variable(var).ok_or_else(|| {
+                    EquationError::new(
                         format!("Variable {} not set", var),
                         EquationErrorType::UnsetVariable,
-                    ));
-                } else {
-                    return Err(EquationError::new(
-                        format!("Unexpected token {:?}", to_eval),
-                        EquationErrorType::UnexpectedToken,
-                    ));
-                }
+                    )
+                })?),
+                Item::Operator(op) => Term::Operator(op.clone()),
+            });
+        }
+        if items.is_empty() {
+            return Err(EquationError::new(
+                "Empty group".to_string(),
+                EquationErrorType::MissingItems,
+            ));
+        }
+        // now we evaluate all functions, right to left so that `sin cos x` works
+        for i in (0..items.len()).rev() {
+            if let Term::Operator(Operator::Functional(func)) = &items[i] {
+                let func = func.clone();
+                let val = take_operand(evaluator, &mut items, i + 1)?;
+                items[i] = Term::Value(evaluator.function(&func, val)?);
             }
-            i += 1;
         }
-        // functions
-        let mut i = 1;
+        // left-associative operators, in pemdas order
         for order in 0..3 {
-            while i < items.len() - 1 {
-                let next = items.get(i).unwrap().clone();
-                if let Item::Operator(Operator::LeftAssociative(op)) = next {
-                    if op.is_of_order(&order) {
-                        let right = items.remove(i + 1);
-                        let left = items.remove(i - 1);
-                        let x = (left, right);
-                        // println!("{:?}", x);
-                        if let (Item::Value(left), Item::Value(right)) = x.clone() {
-                            let val = op.eval(left, right);
-                            items[i - 1] = Item::Value(val);
-                        } else {
-                            return Err(EquationError::new(
-                                "Incorrect Tokens".to_string(),
-                                EquationErrorType::UnexpectedToken,
-                            ));
-                        }
+            if order == 1 {
+                // unary minus binds looser than powers, so `-2^2` is -4
+                let mut i = 0;
+                while i < items.len() {
+                    if is_unary_minus(&items, i) {
+                        let val = take_operand(evaluator, &mut items, i + 1)?;
+                        items[i] = Term::Value(evaluator.negate(val)?);
+                    }
+                    i += 1;
+                }
+            }
+            let mut i = 1;
+            while i < items.len() {
+                if let Term::Operator(Operator::LeftAssociative(op)) = &items[i] {
+                    if op.is_of_order(&order) && !is_unary_minus(&items, i) {
+                        let op = op.clone();
+                        // only powers can have a unary minus on their right (i.e. `2^-1`)
+                        let right = take_operand(evaluator, &mut items, i + 1)?;
+                        let left = match items.remove(i - 1) {
+                            Term::Value(val) => val,
+                            Term::Operator(_) => {
+                                return Err(EquationError::new(
+                                    "Incorrect Tokens".to_string(),
+                                    EquationErrorType::UnexpectedToken,
+                                ))
+                            }
+                        };
+                        items[i - 1] = Term::Value(evaluator.operator(&op, left, right)?);
                         continue;
                     }
                 }
                 i += 1;
             }
-            i = 1;
         }
-        if let Some(Item::Value(val)) = items.get(0) {
-            Ok(*val)
-        } else {
-            Err(EquationError::new(
+        match (items.len(), items.pop()) {
+            (1, Some(Term::Value(val))) => Ok(val),
+            _ => Err(EquationError::new(
                 "Unexpected Tokens".to_string(),
                 EquationErrorType::UnexpectedToken,
-            ))
+            )),
         }
     }
     /// Lists the variables that are not set in an equation
@@ -162,6 +265,30 @@ impl Group {
         }
         vars
     }
+    // checks if the group applies a function that matches, in it or in its groups
+    pub(crate) fn has_function(&self, matches: &dyn Fn(&FunctionalOperator) -> bool) -> bool {
+        self.items.iter().any(|item| match item {
+            Item::Operator(Operator::Functional(func)) => matches(func),
+            Item::Group(group) => group.has_function(matches),
+            _ => false,
+        })
+    }
+    /// Rewrites the group with the operands of sums and products in a set order (i.e. `1 + y*x` is `x*y + 1`), subtracted terms after added ones and divisors after factors.
+    /// Factors of 1 are dropped, so division is written one way (i.e. `a*(1/b)` is `a/b`), and `-0` is `0`.
+    /// Groups that are the same up to such rearrangement are then equal and hash the same.
+    /// Their values are unchanged in the real and complex numbers, but not in types whose division rounds (i.e. `3/2*2` is `2*3/2`, which is 3 rather than 2 in `i64`).
+    pub fn canonicalize(&self) -> Group {
+        match self.evaluate_with(&ExprEvaluator) {
+            Ok(expr) => expr.canonical().to_group(),
+            Err(_) => self.clone(),
+        }
+    }
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Group::new()
+    }
 }
 
 impl From<Vec<Item>> for Group {
@@ -170,24 +297,117 @@ impl From<Vec<Item>> for Group {
     }
 }
 
+// written as the parser reads it, or item by item when the items don't make an equation
+impl Display for Group {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Ok(expr) = self.evaluate_with(&ExprEvaluator) {
+            return write!(f, "{}", expr);
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match item {
+                Item::Value(value) => write!(f, "{}", value)?,
+                Item::Constant(constant) => write!(f, "{}", constant)?,
+                Item::Variable(name) => write!(f, "{}", name)?,
+                Item::Operator(Operator::LeftAssociative(op)) => write!(f, "{}", op.symbol())?,
+                Item::Operator(Operator::Functional(func)) => write!(f, "{}", func.name())?,
+                Item::Group(group) => write!(f, "({})", group)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Group> for Equation {
+    fn from(group: Group) -> Self {
+        Equation {
+            inner: group,
+            angle_mode: AngleMode::default(),
+        }
+    }
+}
+
 impl Equation {
-    /// Creates a new equation from a string.
+    /// Creates a new equation from a string, with the default [`Constants`] (`pi`, `e`, `tau` and `deg`).
     pub fn new(s: impl Into<String>) -> Result<Equation, EquationError> {
-        let parse_stream = ParseStream::new(s.into());
-        let mut group: Group = parse_stream.parse_items()?.into();
+        Equation::with_constants(s, &Constants::default())
+    }
+    /// Creates a new equation from a string, where the names in `constants` are constants and every other name is a variable.
+    pub fn with_constants(
+        s: impl Into<String>,
+        constants: &Constants,
+    ) -> Result<Equation, EquationError> {
+        let parse_stream = ParseStream::with_constants(s.into(), constants.clone());
+        let group: Group = parse_stream.parse_items()?.into();
         // group.validate()?;
-        // set consts
-        group.set_value("pi", std::f64::consts::PI.into());
-        group.set_value("e", std::f64::consts::E.into());
-        group.set_value("tau", std::f64::consts::TAU.into());
-        group.set_value("deg", (std::f64::consts::PI / 180.0).into());
-        Ok(Equation { inner: group })
+        Ok(Equation {
+            inner: group,
+            angle_mode: AngleMode::default(),
+        })
+    }
+    /// Creates an equation from a string of the form `lhs = rhs`, which evaluates to `lhs - rhs` so that it is zero where the sides are equal.
+    /// A string without an `=` is the same as one ending in `= 0`.
+    pub fn from_equality(s: impl Into<String>) -> Result<Equation, EquationError> {
+        Equation::from_equality_with_constants(s, &Constants::default())
+    }
+    /// Creates an equation from a string of the form `lhs = rhs` like [`from_equality`](Self::from_equality), where the names in `constants` are constants and every other name is a variable.
+    pub fn from_equality_with_constants(
+        s: impl Into<String>,
+        constants: &Constants,
+    ) -> Result<Equation, EquationError> {
+        let s = s.into();
+        let Some((lhs, rhs)) = s.split_once('=') else {
+            return Equation::with_constants(s, constants);
+        };
+        let side = |side: &str| -> Result<Item, EquationError> {
+            Ok(
+                ParseStream::with_constants(side.to_string(), constants.clone())
+                    .parse_items()?
+                    .into(),
+            )
+        };
+        let group: Group = vec![
+            side(lhs)?,
+            LeftAssociativeOperator::Subtract.into(),
+            side(rhs)?,
+        ]
+        .into();
+        Ok(group.into())
+    }
+    /// Sets the unit of the angles of the trigonometric functions, which are radians by default.
+    /// Angles are converted to and from radians, so `sin(180)` in degrees is about `1e-16` rather than exactly zero in an f64.
+    pub fn set_angle_mode(&mut self, mode: AngleMode) -> &mut Self {
+        self.angle_mode = mode;
+        self
+    }
+    /// Gives the unit of the angles of the trigonometric functions.
+    pub fn angle_mode(&self) -> AngleMode {
+        self.angle_mode
     }
     /// Sets the value of a variable in the equation.
     pub fn set_value(&mut self, variable: &str, value: f64) -> &mut Self {
         self.inner.set_value(variable, Item::Value(value));
         self
     }
+    /// Sets the value of a variable along with its unit (i.e. `10.0` in `km/h`), for evaluating with [`evaluate_with_units`](Self::evaluate_with_units).
+    /// The other ways to evaluate see the value in SI base units.
+    pub fn set_value_with_unit(
+        &mut self,
+        variable: &str,
+        value: f64,
+        unit: &str,
+    ) -> Result<&mut Self, EquationError> {
+        let unit = Unit::parse(unit)?;
+        let item = vec![
+            Item::Value(value),
+            LeftAssociativeOperator::Multiply.into(),
+            Constant::Unit(unit).into(),
+        ];
+        self.inner.set_value(variable, item.into());
+        Ok(self)
+    }
     /// Sets the value of a variable to an equation. Note: The equation's variables used in the original equation will be used, and no already set values will be overwriten.
     pub fn set_equation(&mut self, variable: &str, equation: Self) -> &mut Self {
         self.inner.set_value(variable, Item::Group(equation.inner));
@@ -195,10 +415,220 @@ impl Equation {
     }
     /// Evaluates the equation.
     pub fn evaluate(&self) -> Result<f64, EquationError> {
-        self.inner.evaluate()
+        self.evaluate_as()
+    }
+    /// Evaluates the equation in any [`Numeric`] type (i.e. `f32`, `i64` or [`Rational`](crate::Rational)). Values set with `set_value` are converted into it.
+    pub fn evaluate_as<N: Numeric>(&self) -> Result<N, EquationError> {
+        self.evaluate_with(&NumericEvaluator::<N>::new())
+    }
+    /// Evaluates the equation, with `bindings` giving the values of the variables that aren't set.
+    pub fn evaluate_with_bindings(
+        &self,
+        bindings: &HashMap<&str, f64>,
+    ) -> Result<f64, EquationError> {
+        self.evaluate_with(&NumericEvaluator::with_bindings(bindings))
+    }
+    /// Integrates the equation over `variable` from `a` to `b`, which can be infinite, with adaptive Gauss-Kronrod quadrature.
+    /// Every other variable has to be set.
+    pub fn integrate(
+        &self,
+        variable: &str,
+        a: f64,
+        b: f64,
+        options: &IntegrationOptions,
+    ) -> Result<Integral, EquationError> {
+        integration::integrate(
+            |x| self.evaluate_with_bindings(&HashMap::from([(variable, x)])),
+            a,
+            b,
+            options,
+        )
+    }
+    /// Finds an antiderivative of the equation with respect to `variable` (i.e. `x^3/3 + sin(x)` for `x^2 + cos(x)`), without a constant of integration.
+    /// It knows polynomials, the functions of a linear argument (i.e. `cos(2*x + 1)`) and integration by parts of a polynomial times a function (i.e. `x * e^x`).
+    /// Gives `None` when it can't find a closed form, where [`integrate`](Self::integrate) can still find the value numerically.
+    pub fn antiderivative(&self, variable: &str) -> Option<Equation> {
+        let expr = self.evaluate_with(&ExprEvaluator).ok()?;
+        calculus::antiderivative(&expr.simplify(), variable).map(|expr| expr.to_group().into())
+    }
+    /// Differentiates the equation with respect to `variable` (i.e. `2*x*cos(x^2)` for `sin(x^2)`).
+    /// The steps (i.e. `floor`) have a derivative of zero wherever it exists, and it gives `None` for gamma and the factorial, whose derivatives aren't elementary.
+    pub fn derivative(&self, variable: &str) -> Option<Equation> {
+        let expr = self.evaluate_with(&ExprEvaluator).ok()?;
+        calculus::derivative(&expr.simplify(), variable)
+            .map(|expr| expr.simplify().to_group().into())
+    }
+    /// Gives the Taylor series of the equation in `variable` about `about`, up to the power `order` (i.e. `x - x^3/6` for `sin(x)` about 0 with order 3).
+    /// Every other variable has to be set, and the equation must be differentiable that many times at `about`.
+    pub fn series(
+        &self,
+        variable: &str,
+        about: f64,
+        order: u32,
+    ) -> Result<Equation, EquationError> {
+        let expr = self.evaluate_with(&ExprEvaluator)?.simplify();
+        let (series, _) = calculus::series(&expr, variable, about, order, None)?;
+        Ok(series.to_group().into())
+    }
+    /// Gives the Taylor series like [`series`](Self::series), along with a bound on its error for every value of `variable` within `radius` of `about`.
+    /// The bound comes from the largest the next derivative can be in that range, so it is guaranteed but can be loose (or infinite where the derivative blows up).
+    pub fn series_with_remainder(
+        &self,
+        variable: &str,
+        about: f64,
+        order: u32,
+        radius: f64,
+    ) -> Result<(Equation, f64), EquationError> {
+        let expr = self.evaluate_with(&ExprEvaluator)?.simplify();
+        let (series, remainder) = calculus::series(&expr, variable, about, order, Some(radius))?;
+        Ok((series.to_group().into(), remainder.unwrap_or(f64::INFINITY)))
+    }
+    /// Checks if two equations are mathematically the same (i.e. `(x + 1)^2` and `x^2 + 2*x + 1`), giving values of the variables where they differ if they aren't.
+    /// It first simplifies their difference, multiplying out polynomials, and otherwise compares them at many random points, which makes it likely but not certain.
+    pub fn is_equivalent(&self, other: &Equation) -> Equivalence {
+        equivalence::equivalence(self, other)
+    }
+    /// Rewrites the equation with the operands of sums and products in a set order, see [`Group::canonicalize`], keeping its angle mode.
+    /// Unlike [`is_equivalent`](Self::is_equivalent) it doesn't simplify, so `x + x` and `2*x` stay different.
+    pub fn canonicalize(&self) -> Equation {
+        Equation {
+            inner: self.inner.canonicalize(),
+            angle_mode: self.angle_mode,
+        }
+    }
+    /// Compiles the equation into a graph of steps for evaluating it many times, where every subtree that appears more than once is worked out once per evaluation.
+    /// The variables that aren't set are given to [`Compiled::evaluate`] in alphabetical order (see [`Compiled::variables`]).
+    pub fn compile(&self) -> Result<Compiled, EquationError> {
+        let mut variables: Vec<String> = self.list_vars().into_iter().collect();
+        variables.sort();
+        compile::compile(self, variables)
+    }
+    /// Compiles the equation like [`compile`](Self::compile), with the variables that aren't set given to [`Compiled::evaluate`] in the order of `variables`.
+    /// Variables that aren't in the equation are ignored, and every one that is has to be given.
+    pub fn compile_with_variables(&self, variables: &[&str]) -> Result<Compiled, EquationError> {
+        compile::compile(
+            self,
+            variables
+                .iter()
+                .map(|variable| variable.to_string())
+                .collect(),
+        )
+    }
+    /// Compiles the equation to machine code, whose variables are given to [`CompiledFn::call`] in alphabetical order like [`compile`](Self::compile).
+    /// Functions without an instruction (i.e. `sin`) are called the way the interpreter calls them, so it gives the same values as [`evaluate_with_bindings`](Self::evaluate_with_bindings).
+    #[cfg(feature = "jit")]
+    pub fn jit(&self) -> Result<CompiledFn, EquationError> {
+        Ok(jit::jit(self.compile()?))
+    }
+    /// Compiles the equation to machine code like [`jit`](Self::jit), with the variables given to [`CompiledFn::call`] in the order of `variables`.
+    #[cfg(feature = "jit")]
+    pub fn jit_with_variables(&self, variables: &[&str]) -> Result<CompiledFn, EquationError> {
+        Ok(jit::jit(self.compile_with_variables(variables)?))
+    }
+    /// Evaluates the equation at `n` evenly spaced values of `variable` over `range`, for plotting or tabulating it.
+    /// Points are added between them where the equation bends sharply, jumps or stops being defined (i.e. around 0 for `1/x`), so there can be more than `n`.
+    /// The points are in order from the start of the range to its end (which can be lower), and each has its own result, which isn't a finite number outside the domain of the equation.
+    /// There are none when an end of the range isn't finite.
+    pub fn sample(
+        &self,
+        variable: &str,
+        range: RangeInclusive<f64>,
+        n: usize,
+    ) -> Vec<(f64, Result<f64, EquationError>)> {
+        sample::sample(
+            |x| self.evaluate_with_bindings(&HashMap::from([(variable, x)])),
+            *range.start(),
+            *range.end(),
+            n,
+        )
+    }
+    /// Finds the limit of the equation as `variable` approaches `point` from `direction`, where `point` can be infinite (i.e. 1 for `sin(x)/x` at 0).
+    /// Forms like `0/0` use l'Hôpital's rule with the derivatives, and anything else is extrapolated from values ever closer to the point.
+    /// The limit can be infinite, and a [`NoLimit`](EquationErrorType::NoLimit) error is given when the sides differ or the values don't settle (i.e. `sin(1/x)` at 0).
+    pub fn limit(
+        &self,
+        variable: &str,
+        point: f64,
+        direction: Direction,
+    ) -> Result<f64, EquationError> {
+        let expr = self.evaluate_with(&ExprEvaluator)?.simplify();
+        limit::limit(&expr, variable, point, direction)
+    }
+    /// Finds the smallest value of the equation over `variables`, each within its bounds in `bounds` (which can be infinite).
+    /// Other variables have to be set. The methods find a local minimum, which is the smallest one when the equation has only one within the bounds.
+    pub fn minimize(
+        &self,
+        variables: &[&str],
+        bounds: &[(f64, f64)],
+        method: OptimizeMethod,
+    ) -> Result<Optimum, EquationError> {
+        optimize::optimize(self, variables, bounds, method, false)
+    }
+    /// Finds the largest value of the equation like [`minimize`](Self::minimize) finds the smallest.
+    pub fn maximize(
+        &self,
+        variables: &[&str],
+        bounds: &[(f64, f64)],
+        method: OptimizeMethod,
+    ) -> Result<Optimum, EquationError> {
+        optimize::optimize(self, variables, bounds, method, true)
+    }
+    /// Gives the equation as a polynomial in `variable`, if it is one and has no other variables.
+    /// Any other function or power of the variable (i.e. `sin(x)` or `x^0.5`) isn't a polynomial.
+    pub fn as_polynomial(&self, variable: &str) -> Option<Polynomial> {
+        self.evaluate_with(&PolynomialEvaluator::new(variable))
+            .ok()
+            .map(Polynomial::new)
+    }
+    /// Evaluates the equation with the units of its literals and variables (i.e. `3m / 2s`), checking that the dimensions are consistent.
+    /// A unit is written in brackets after its literal (i.e. `3 [m] / 2 [s]` or `2 [kg*m^2/s^2]`), straight after it (i.e. `2h` or `10m/s`), or after a space when every name in it has more than one letter (i.e. `1 kW`).
+    /// Without brackets a unit can't contain a `*`, and units that could be read as names (i.e. `2 h`, `2s*t`, or `2h` with the physics constants) fail to parse.
+    pub fn evaluate_with_units(&self) -> Result<Quantity, EquationError> {
+        self.evaluate_as()
+    }
+    /// Evaluates the equation with units and converts the result to `unit` (i.e. `km/h`), which must have the same dimension.
+    pub fn evaluate_in(&self, unit: &str) -> Result<f64, EquationError> {
+        self.evaluate_with_units()?.convert_to(unit)
+    }
+    /// Evaluates the equation over intervals, giving bounds that are guaranteed to hold the result for every value of the variables within their bounds.
+    pub fn evaluate_interval(
+        &self,
+        bindings: &HashMap<&str, Interval>,
+    ) -> Result<Interval, EquationError> {
+        self.evaluate_with(&NumericEvaluator::with_bindings(bindings))
+    }
+    /// Evaluates the equation over the complex numbers. `i` and `j` are the imaginary unit unless set to a value.
+    #[cfg(feature = "complex")]
+    pub fn evaluate_complex(&self) -> Result<Complex64, EquationError> {
+        self.evaluate_as()
+    }
+    /// Evaluates the equation with `bits` bits of precision. Every step (including functions and constants) is correctly rounded, though literals and set values are limited to the digits an f64 keeps.
+    #[cfg(feature = "precision")]
+    pub fn evaluate_with_precision(&self, bits: u64) -> Result<BigFloat, EquationError> {
+        self.evaluate_with(&PrecisionEvaluator::new(bits)?)
+    }
+    // evaluates with the angles in the angle mode of the equation
+    pub(crate) fn evaluate_with<E: Evaluator>(
+        &self,
+        evaluator: &E,
+    ) -> Result<E::Value, EquationError> {
+        self.inner
+            .evaluate_with(&AngleEvaluator::new(evaluator, self.angle_mode))
     }
     /// Gives a HashSet of all variables (that are not set) in the equation.
     pub fn list_vars(&self) -> HashSet<String> {
         self.inner.list_vars()
     }
+    // checks if the equation applies a function that matches
+    pub(crate) fn has_function(&self, matches: &dyn Fn(&FunctionalOperator) -> bool) -> bool {
+        self.inner.has_function(matches)
+    }
+}
+
+/// Writes the equation as a formula that parses back into the same equation (i.e. `x^2 + 2*x`), with the values that were set in place of their variables.
+/// The angle mode isn't part of the formula, literals with a unit are written with it in brackets (i.e. `3[m]/2[s]`), and named constants other than `pi`, `e`, `tau` and `deg` are written as their values.
+impl Display for Equation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inner)
+    }
 }
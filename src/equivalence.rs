@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::{
+    equation::Equation,
+    expr::{Expr, ExprEvaluator},
+};
+
+// how many random points the equations are compared at when their forms don't settle it
+const POINTS: usize = 200;
+// the relative difference between two values that still counts as the same
+const TOLERANCE: f64 = 1e-8;
+
+/// Whether two equations are the same, as found by [`Equation::is_equivalent`](crate::Equation::is_equivalent).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Equivalence {
+    /// Their difference simplifies to zero (i.e. `(x + 1)^2` and `x^2 + 2*x + 1`), so they are the same wherever both are defined.
+    Proven,
+    /// They couldn't be simplified into each other, but gave the same values at this many random points where they are defined.
+    Likely(usize),
+    /// They differ at these values of the variables, where their values aren't the same or only one of them is defined.
+    Different(HashMap<String, f64>),
+    /// Neither was defined at any of the random points, so they couldn't be compared (i.e. `sqrt(-1 - x^2)` and `ln(-1 - x^2)`).
+    Undefined,
+}
+
+impl Equivalence {
+    /// Checks if the equations were found to be the same, proven or likely.
+    pub fn holds(&self) -> bool {
+        matches!(self, Equivalence::Proven | Equivalence::Likely(_))
+    }
+}
+
+/// Compares two equations, first by simplifying their difference and otherwise at random points.
+pub(crate) fn equivalence(a: &Equation, b: &Equation) -> Equivalence {
+    let mut variables: Vec<String> = a.list_vars().union(&b.list_vars()).cloned().collect();
+    variables.sort();
+    if let (Ok(lhs), Ok(rhs)) = (
+        a.evaluate_with(&ExprEvaluator),
+        b.evaluate_with(&ExprEvaluator),
+    ) {
+        if is_zero(&(lhs - rhs), &variables) {
+            return Equivalence::Proven;
+        }
+    }
+    let mut random = SplitMix(0x5eed);
+    let mut compared = 0;
+    for i in 0..POINTS {
+        // mostly small values, with some near zero and some large ones
+        let scale = match i % 4 {
+            0 => 1.0,
+            3 => 100.0,
+            _ => 10.0,
+        };
+        let bindings: HashMap<&str, f64> = variables
+            .iter()
+            .map(|variable| (variable.as_str(), scale * (2.0 * random.next() - 1.0)))
+            .collect();
+        let value = |equation: &Equation| {
+            equation
+                .evaluate_with_bindings(&bindings)
+                .ok()
+                .filter(|value| !value.is_nan())
+        };
+        let same = match (value(a), value(b)) {
+            (None, None) => continue,
+            // infinities are only the same as themselves
+            (Some(x), Some(y)) if !x.is_finite() || !y.is_finite() => x == y,
+            (Some(x), Some(y)) => {
+                x == y || (x - y).abs() <= TOLERANCE * x.abs().max(y.abs()).max(1.0)
+            }
+            _ => false,
+        };
+        if !same {
+            return Equivalence::Different(
+                bindings
+                    .into_iter()
+                    .map(|(variable, value)| (variable.to_string(), value))
+                    .collect(),
+            );
+        }
+        compared += 1;
+    }
+    if compared == 0 {
+        return Equivalence::Undefined;
+    }
+    Equivalence::Likely(compared)
+}
+
+// checks if the expression simplifies to zero, multiplying out the polynomials in each variable in turn
+fn is_zero(expr: &Expr, variables: &[String]) -> bool {
+    let expr = expr.simplify();
+    if expr == Expr::Number(0.0) {
+        return true;
+    }
+    let Some((variable, rest)) = variables.split_first() else {
+        return false;
+    };
+    match expr.polynomial(variable) {
+        Some(coefficients) if !expr.is_free_of(variable) => coefficients
+            .iter()
+            .all(|coefficient| is_zero(coefficient, rest)),
+        // the expression doesn't depend on this variable, or isn't a polynomial in it
+        _ => is_zero(&expr, rest),
+    }
+}
+
+// splitmix64, so that the same equations are always compared at the same points
+struct SplitMix(u64);
+
+impl SplitMix {
+    // a number from 0 up to 1
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
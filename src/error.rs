@@ -25,6 +25,18 @@ pub enum EquationErrorType {
     UnexpectedToken,
     /// An unset variable was found.
     UnsetVariable,
+    /// A value was too large for the number type the equation is evaluated in.
+    Overflow,
+    /// A division by zero in a number type that has no infinity.
+    DivisionByZero,
+    /// An operation the number type the equation is evaluated in can't represent (i.e. `sin` of an integer).
+    Unsupported,
+    /// Values of different dimensions were combined (i.e. metres added to seconds).
+    DimensionMismatch,
+    /// Equations depend on each other's values in a loop.
+    CircularDependency,
+    /// The values of an equation don't approach a single limit (i.e. `1/x` at 0, which approaches different infinities from either side).
+    NoLimit,
 }
 
 impl Display for EquationError {
@@ -0,0 +1,806 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use crate::{
+    equation::Group,
+    error::EquationError,
+    item::{Constant, FunctionalOperator, Item, LeftAssociativeOperator},
+    numeric::{Evaluator, Numeric, NumericEvaluator},
+    units::Unit,
+};
+
+// the precedences the tree is written with, where a looser operand than its operator needs brackets
+const SUM: u8 = 1;
+const PRODUCT: u8 = 2;
+const POWER: u8 = 3;
+const ATOM: u8 = 4;
+
+// the highest power a polynomial is expanded to
+const MAX_POWER: f64 = 64.0;
+
+/// An equation as a tree, which the symbolic methods (i.e. [`Equation::antiderivative`](crate::Equation::antiderivative)) work on.
+/// The operators on it simplify as they go, collecting like terms and powers of the same base (i.e. `x * x + x^2` is `2*x^2`).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Number(f64),
+    Constant(Constant),
+    Variable(String),
+    Negate(Box<Expr>),
+    Function(FunctionalOperator, Box<Expr>),
+    Operator(LeftAssociativeOperator, Box<Expr>, Box<Expr>),
+}
+
+/// Builds the tree of a group, where every variable that isn't set is a symbol.
+#[derive(Debug)]
+pub(crate) struct ExprEvaluator;
+
+impl Evaluator for ExprEvaluator {
+    type Value = Expr;
+    fn value(&self, value: f64) -> Result<Expr, EquationError> {
+        Ok(Expr::Number(value))
+    }
+    fn constant(&self, constant: &Constant) -> Result<Expr, EquationError> {
+        Ok(Expr::Constant(constant.clone()))
+    }
+    fn variable(&self, name: &str) -> Option<Expr> {
+        Some(Expr::Variable(name.to_string()))
+    }
+    // the tree is kept as written, so that it prints the way it was parsed
+    fn negate(&self, x: Expr) -> Result<Expr, EquationError> {
+        Ok(Expr::Negate(Box::new(x)))
+    }
+    fn function(&self, func: &FunctionalOperator, x: Expr) -> Result<Expr, EquationError> {
+        Ok(Expr::Function(func.clone(), Box::new(x)))
+    }
+    fn operator(
+        &self,
+        op: &LeftAssociativeOperator,
+        lhs: Expr,
+        rhs: Expr,
+    ) -> Result<Expr, EquationError> {
+        Ok(Expr::binary(op.clone(), lhs, rhs))
+    }
+}
+
+impl Expr {
+    pub(crate) fn variable(name: &str) -> Expr {
+        Expr::Variable(name.to_string())
+    }
+    /// An operator without any simplification.
+    pub(crate) fn binary(op: LeftAssociativeOperator, lhs: Expr, rhs: Expr) -> Expr {
+        Expr::Operator(op, Box::new(lhs), Box::new(rhs))
+    }
+    /// Raises the expression to a power.
+    pub(crate) fn pow(self, exponent: Expr) -> Expr {
+        match (self, exponent) {
+            (_, Expr::Number(0.0)) => Expr::Number(1.0),
+            (base, Expr::Number(1.0)) => base,
+            (Expr::Number(1.0), _) => Expr::Number(1.0),
+            (Expr::Number(b), Expr::Number(n))
+                if b.fract() == 0.0
+                    && n.fract() == 0.0
+                    && n > 0.0
+                    && b.powf(n).abs() < 2f64.powi(53) =>
+            {
+                Expr::Number(b.powf(n))
+            }
+            // (x^m)^n is x^(m n) when n is an integer
+            (Expr::Operator(LeftAssociativeOperator::Power, base, m), Expr::Number(n))
+                if n.fract() == 0.0 && matches!(*m, Expr::Number(_)) =>
+            {
+                base.pow(*m * Expr::Number(n))
+            }
+            (base, exponent) => Expr::binary(LeftAssociativeOperator::Power, base, exponent),
+        }
+    }
+    /// Applies a function, which is worked out when the value is a whole number (i.e. `cos(0)` is `1`).
+    pub(crate) fn apply(func: FunctionalOperator, x: Expr) -> Expr {
+        if let Expr::Number(x) = x {
+            let value = func.evaluate(x);
+            if value.is_finite() && value.fract() == 0.0 {
+                return Expr::Number(value);
+            }
+        }
+        Expr::Function(func, Box::new(x))
+    }
+    /// Applies a left-associative operator, simplifying like the arithmetic operators do.
+    pub(crate) fn operate(op: &LeftAssociativeOperator, lhs: Expr, rhs: Expr) -> Expr {
+        match op {
+            LeftAssociativeOperator::Add => lhs + rhs,
+            LeftAssociativeOperator::Subtract => lhs - rhs,
+            LeftAssociativeOperator::Multiply => lhs * rhs,
+            LeftAssociativeOperator::Divide => lhs / rhs,
+            LeftAssociativeOperator::Power => lhs.pow(rhs),
+            LeftAssociativeOperator::Root => match lhs {
+                Expr::Number(2.0) => Expr::apply(FunctionalOperator::Sqrt, rhs),
+                Expr::Number(3.0) => Expr::apply(FunctionalOperator::Cbrt, rhs),
+                lhs => Expr::binary(LeftAssociativeOperator::Root, lhs, rhs),
+            },
+        }
+    }
+    /// Rebuilds the tree with the simplifying operators (i.e. `3√x` is `cbrt(x)` and `x^-2` has the number -2 as its exponent).
+    pub(crate) fn simplify(&self) -> Expr {
+        match self {
+            Expr::Negate(x) => -x.simplify(),
+            Expr::Function(func, x) => Expr::apply(func.clone(), x.simplify()),
+            Expr::Operator(op, lhs, rhs) => Expr::operate(op, lhs.simplify(), rhs.simplify()),
+            x => x.clone(),
+        }
+    }
+    /// Rebuilds the tree with the operands of sums and products sorted, without simplifying it (i.e. `b - c + a` is `a + b - c`).
+    /// Negated terms come after the others and divisors after the factors, and factors of 1 are dropped (i.e. `a*(1/b)` is `a/b`).
+    pub(crate) fn canonical(&self) -> Expr {
+        match self {
+            Expr::Operator(
+                LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract,
+                _,
+                _,
+            )
+            | Expr::Negate(_) => {
+                let mut terms = Vec::new();
+                self.terms(false, &mut terms);
+                let (mut negative, mut positive): (Vec<_>, Vec<_>) =
+                    terms.into_iter().partition(|(_, negative)| *negative);
+                sort(&mut positive);
+                sort(&mut negative);
+                let mut negative = negative.into_iter().map(|(term, _)| term);
+                let first = match positive.len() {
+                    0 => Expr::Negate(Box::new(negative.next().unwrap_or(Expr::Number(0.0)))),
+                    _ => positive
+                        .into_iter()
+                        .map(|(term, _)| term)
+                        .reduce(|sum, term| Expr::binary(LeftAssociativeOperator::Add, sum, term))
+                        .unwrap_or(Expr::Number(0.0)),
+                };
+                negative.fold(first, |sum, term| {
+                    Expr::binary(LeftAssociativeOperator::Subtract, sum, term)
+                })
+            }
+            Expr::Operator(
+                LeftAssociativeOperator::Multiply | LeftAssociativeOperator::Divide,
+                _,
+                _,
+            ) => {
+                let mut factors = Vec::new();
+                let negative = self.factors(false, &mut factors);
+                let (mut divisors, mut factors): (Vec<_>, Vec<_>) =
+                    factors.into_iter().partition(|(_, divisor)| *divisor);
+                sort(&mut factors);
+                sort(&mut divisors);
+                let product = factors
+                    .into_iter()
+                    .map(|(factor, _)| factor)
+                    .reduce(|product, factor| {
+                        Expr::binary(LeftAssociativeOperator::Multiply, product, factor)
+                    })
+                    .unwrap_or(Expr::Number(1.0));
+                let product = divisors.into_iter().fold(product, |product, (divisor, _)| {
+                    Expr::binary(LeftAssociativeOperator::Divide, product, divisor)
+                });
+                if negative {
+                    Expr::Negate(Box::new(product))
+                } else {
+                    product
+                }
+            }
+            Expr::Operator(op, lhs, rhs) => {
+                Expr::binary(op.clone(), lhs.canonical(), rhs.canonical())
+            }
+            Expr::Function(func, x) => Expr::Function(func.clone(), Box::new(x.canonical())),
+            x => x.clone(),
+        }
+    }
+    // the terms of a sum, which are subtracted when they are negative
+    fn terms(&self, negative: bool, terms: &mut Vec<(Expr, bool)>) {
+        match self {
+            Expr::Operator(LeftAssociativeOperator::Add, lhs, rhs) => {
+                lhs.terms(negative, terms);
+                rhs.terms(negative, terms);
+            }
+            Expr::Operator(LeftAssociativeOperator::Subtract, lhs, rhs) => {
+                lhs.terms(negative, terms);
+                rhs.terms(!negative, terms);
+            }
+            Expr::Negate(x) => x.terms(!negative, terms),
+            term => match term.canonical() {
+                // -0 is 0
+                Expr::Number(0.0) => terms.push((Expr::Number(0.0), false)),
+                // a product with a negated factor is a negated term
+                Expr::Negate(x) => terms.push((*x, !negative)),
+                term => terms.push((term, negative)),
+            },
+        }
+    }
+    // the factors of a product, which divide when they are divisors, giving whether the product is negated
+    fn factors(&self, divisor: bool, factors: &mut Vec<(Expr, bool)>) -> bool {
+        match self {
+            Expr::Operator(LeftAssociativeOperator::Multiply, lhs, rhs) => {
+                lhs.factors(divisor, factors) != rhs.factors(divisor, factors)
+            }
+            Expr::Operator(LeftAssociativeOperator::Divide, lhs, rhs) => {
+                lhs.factors(divisor, factors) != rhs.factors(!divisor, factors)
+            }
+            Expr::Negate(x) => !x.factors(divisor, factors),
+            factor => match factor.canonical() {
+                Expr::Number(1.0) => false,
+                Expr::Negate(x) => !x.factors(divisor, factors),
+                factor => {
+                    factors.push((factor, divisor));
+                    false
+                }
+            },
+        }
+    }
+    // the numerator and denominator of a whole number or of a fraction of them (i.e. `1/3`)
+    fn fraction(&self) -> Option<(f64, f64)> {
+        let whole = |x: f64| x.fract() == 0.0 && x.abs() < 2f64.powi(53);
+        match self {
+            Expr::Number(n) if whole(*n) => Some((*n, 1.0)),
+            Expr::Negate(x) => x.fraction().map(|(n, d)| (-n, d)),
+            Expr::Operator(LeftAssociativeOperator::Divide, n, d) => match (&**n, &**d) {
+                (Expr::Number(n), Expr::Number(d)) if whole(*n) && whole(*d) && *d != 0.0 => {
+                    Some((*n, *d))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    // a fraction in its lowest terms, which is a number when it is whole
+    fn from_fraction(n: f64, d: f64) -> Option<Expr> {
+        if d == 0.0 || n.abs().max(d.abs()) >= 2f64.powi(53) {
+            return None;
+        }
+        let divisor = gcd(n.abs(), d.abs());
+        let (n, d) = (n / divisor * d.signum(), d.abs() / divisor);
+        Some(if d == 1.0 {
+            Expr::Number(n)
+        } else {
+            let fraction = Expr::binary(
+                LeftAssociativeOperator::Divide,
+                Expr::Number(n.abs()),
+                Expr::Number(d),
+            );
+            if n < 0.0 {
+                Expr::Negate(Box::new(fraction))
+            } else {
+                fraction
+            }
+        })
+    }
+    /// Checks if the expression doesn't depend on the variable.
+    pub(crate) fn is_free_of(&self, variable: &str) -> bool {
+        match self {
+            Expr::Number(_) | Expr::Constant(_) => true,
+            Expr::Variable(name) => name != variable,
+            Expr::Negate(x) | Expr::Function(_, x) => x.is_free_of(variable),
+            Expr::Operator(_, lhs, rhs) => lhs.is_free_of(variable) && rhs.is_free_of(variable),
+        }
+    }
+    /// Gives `(a, b)` when the expression is `a * variable + b`, where neither depend on the variable.
+    pub(crate) fn linear(&self, variable: &str) -> Option<(Expr, Expr)> {
+        if self.is_free_of(variable) {
+            return Some((Expr::Number(0.0), self.clone()));
+        }
+        match self {
+            Expr::Variable(_) => Some((Expr::Number(1.0), Expr::Number(0.0))),
+            Expr::Negate(x) => {
+                let (a, b) = x.linear(variable)?;
+                Some((-a, -b))
+            }
+            Expr::Operator(op, lhs, rhs) => match op {
+                LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => {
+                    let (a, b) = lhs.linear(variable)?;
+                    let (c, d) = rhs.linear(variable)?;
+                    Some((Expr::operate(op, a, c), Expr::operate(op, b, d)))
+                }
+                LeftAssociativeOperator::Multiply if lhs.is_free_of(variable) => {
+                    let (a, b) = rhs.linear(variable)?;
+                    Some((*lhs.clone() * a, *lhs.clone() * b))
+                }
+                LeftAssociativeOperator::Multiply if rhs.is_free_of(variable) => {
+                    let (a, b) = lhs.linear(variable)?;
+                    Some((a * *rhs.clone(), b * *rhs.clone()))
+                }
+                LeftAssociativeOperator::Divide if rhs.is_free_of(variable) => {
+                    let (a, b) = lhs.linear(variable)?;
+                    Some((a / *rhs.clone(), b / *rhs.clone()))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// Gives the coefficients of the expression as a polynomial in the variable, lowest power first.
+    /// Unlike [`Polynomial`](crate::Polynomial) the coefficients can depend on other variables.
+    pub(crate) fn polynomial(&self, variable: &str) -> Option<Vec<Expr>> {
+        if self.is_free_of(variable) {
+            return Some(vec![self.clone()]);
+        }
+        let mut coefficients = match self {
+            Expr::Variable(_) => vec![Expr::Number(0.0), Expr::Number(1.0)],
+            Expr::Negate(x) => x.polynomial(variable)?.into_iter().map(Neg::neg).collect(),
+            Expr::Operator(op, lhs, rhs) => match op {
+                LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => {
+                    let mut lhs = lhs.polynomial(variable)?;
+                    let mut rhs = rhs.polynomial(variable)?;
+                    let len = lhs.len().max(rhs.len());
+                    lhs.resize(len, Expr::Number(0.0));
+                    rhs.resize(len, Expr::Number(0.0));
+                    lhs.into_iter()
+                        .zip(rhs)
+                        .map(|(a, b)| Expr::operate(op, a, b))
+                        .collect()
+                }
+                LeftAssociativeOperator::Multiply => {
+                    multiply_polynomials(&lhs.polynomial(variable)?, &rhs.polynomial(variable)?)
+                }
+                LeftAssociativeOperator::Divide if rhs.is_free_of(variable) => lhs
+                    .polynomial(variable)?
+                    .into_iter()
+                    .map(|c| c / *rhs.clone())
+                    .collect(),
+                LeftAssociativeOperator::Power => match **rhs {
+                    Expr::Number(n) if n.fract() == 0.0 && (0.0..=MAX_POWER).contains(&n) => {
+                        let base = lhs.polynomial(variable)?;
+                        let mut power = vec![Expr::Number(1.0)];
+                        for _ in 0..n as usize {
+                            power = multiply_polynomials(&power, &base);
+                        }
+                        power
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            },
+            _ => return None,
+        };
+        while coefficients.len() > 1 && coefficients.last() == Some(&Expr::Number(0.0)) {
+            coefficients.pop();
+        }
+        Some(coefficients)
+    }
+    /// Builds a polynomial in the variable from its coefficients, lowest power first, which is written highest power first.
+    pub(crate) fn from_polynomial(coefficients: &[Expr], variable: &str) -> Expr {
+        coefficients
+            .iter()
+            .enumerate()
+            .rev()
+            .fold(Expr::Number(0.0), |sum, (i, c)| {
+                sum + c.clone() * Expr::variable(variable).pow(Expr::Number(i as f64))
+            })
+    }
+    /// Converts the tree back into the items of a group.
+    pub(crate) fn to_group(&self) -> Group {
+        match self.to_item() {
+            Item::Group(group) => group,
+            item => vec![item].into(),
+        }
+    }
+    /// Evaluates the tree in any [`Numeric`] type, with `bindings` giving the values of the variables.
+    pub(crate) fn evaluate_as<N: Numeric>(
+        &self,
+        bindings: &HashMap<&str, N>,
+    ) -> Result<N, EquationError> {
+        self.to_group()
+            .evaluate_with(&NumericEvaluator::with_bindings(bindings))
+    }
+    fn to_item(&self) -> Item {
+        match self {
+            Expr::Number(value) => Item::Value(*value),
+            Expr::Constant(constant) => Item::Constant(constant.clone()),
+            Expr::Variable(name) => Item::Variable(name.clone()),
+            Expr::Negate(x) => vec![LeftAssociativeOperator::Subtract.into(), x.to_item()].into(),
+            Expr::Function(func, x) => vec![func.clone().into(), x.to_item()].into(),
+            Expr::Operator(op, lhs, rhs) => {
+                vec![lhs.to_item(), op.clone().into(), rhs.to_item()].into()
+            }
+        }
+    }
+    // how tightly the expression holds together when it is written
+    fn precedence(&self) -> u8 {
+        match self {
+            _ if self.quantity().is_some() => ATOM,
+            _ if self.is_negative() => PRODUCT,
+            Expr::Negate(_) => PRODUCT,
+            Expr::Operator(op, _, _) => match op {
+                LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => SUM,
+                LeftAssociativeOperator::Multiply | LeftAssociativeOperator::Divide => PRODUCT,
+                LeftAssociativeOperator::Power => POWER,
+                LeftAssociativeOperator::Root => ATOM,
+            },
+            _ => ATOM,
+        }
+    }
+    // a literal with a unit (i.e. `3[m]`), which is written as one item
+    fn quantity(&self) -> Option<(f64, &Unit)> {
+        match self {
+            Expr::Operator(LeftAssociativeOperator::Multiply, lhs, rhs) => match (&**lhs, &**rhs) {
+                (Expr::Number(value), Expr::Constant(Constant::Unit(unit)))
+                    if !value.is_sign_negative() =>
+                {
+                    Some((*value, unit))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    // if the expression is written starting with a minus
+    fn is_negative(&self) -> bool {
+        match self {
+            Expr::Number(value) => value.is_sign_negative(),
+            Expr::Constant(constant) => constant.value().is_sign_negative(),
+            Expr::Negate(_) => true,
+            _ => false,
+        }
+    }
+}
+
+// sorts the operands of a sum or product the same way whatever order they were written in
+fn sort(operands: &mut [(Expr, bool)]) {
+    operands.sort_by_cached_key(|(operand, _)| format!("{:?}", operand));
+}
+
+// the greatest common divisor of two whole numbers
+fn gcd(a: f64, b: f64) -> f64 {
+    if b == 0.0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// works out the operator when both sides are whole numbers or fractions of them, keeping the result exact
+fn exact(op: LeftAssociativeOperator, lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+    let ((a, b), (c, d)) = (lhs.fraction()?, rhs.fraction()?);
+    match op {
+        LeftAssociativeOperator::Add => Expr::from_fraction(a * d + c * b, b * d),
+        LeftAssociativeOperator::Subtract => Expr::from_fraction(a * d - c * b, b * d),
+        LeftAssociativeOperator::Multiply => Expr::from_fraction(a * c, b * d),
+        LeftAssociativeOperator::Divide => Expr::from_fraction(a * d, b * c),
+        _ => None,
+    }
+}
+
+// multiplies two polynomials given by their coefficients
+fn multiply_polynomials(lhs: &[Expr], rhs: &[Expr]) -> Vec<Expr> {
+    let mut product = vec![Expr::Number(0.0); lhs.len() + rhs.len() - 1];
+    for (i, a) in lhs.iter().enumerate() {
+        for (j, b) in rhs.iter().enumerate() {
+            product[i + j] = product[i + j].clone() + a.clone() * b.clone();
+        }
+    }
+    product
+}
+
+impl From<f64> for Expr {
+    fn from(value: f64) -> Self {
+        Expr::Number(value)
+    }
+}
+
+// a number that stays an exact fraction while it is one
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Coefficient {
+    numer: f64,
+    denom: f64,
+}
+
+impl Coefficient {
+    const ONE: Coefficient = Coefficient {
+        numer: 1.0,
+        denom: 1.0,
+    };
+    fn of(expr: &Expr) -> Option<Coefficient> {
+        match expr {
+            Expr::Number(value) => Some(Coefficient::reduce(*value, 1.0)),
+            _ => expr
+                .fraction()
+                .map(|(numer, denom)| Coefficient::reduce(numer, denom)),
+        }
+    }
+    // the fraction in its lowest terms, or its value when either part isn't whole
+    fn reduce(numer: f64, denom: f64) -> Coefficient {
+        let whole = |x: f64| x.fract() == 0.0 && x.abs() < 2f64.powi(53);
+        if whole(numer) && whole(denom) && denom != 0.0 {
+            let divisor = gcd(numer.abs(), denom.abs());
+            Coefficient {
+                numer: numer / divisor * denom.signum(),
+                denom: denom.abs() / divisor,
+            }
+        } else {
+            Coefficient {
+                numer: numer / denom,
+                denom: 1.0,
+            }
+        }
+    }
+    fn mul(self, other: Coefficient) -> Coefficient {
+        Coefficient::reduce(self.numer * other.numer, self.denom * other.denom)
+    }
+    fn div(self, other: Coefficient) -> Coefficient {
+        Coefficient::reduce(self.numer * other.denom, self.denom * other.numer)
+    }
+    fn add(self, other: Coefficient) -> Coefficient {
+        Coefficient::reduce(
+            self.numer * other.denom + other.numer * self.denom,
+            self.denom * other.denom,
+        )
+    }
+    fn negate(self) -> Coefficient {
+        Coefficient {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+// a product as a coefficient times powers of its factors, where the powers of the same base are collected (i.e. `x * x^2` is `x^3`)
+#[derive(Debug)]
+struct Product {
+    coefficient: Coefficient,
+    factors: Vec<(Expr, Expr)>,
+}
+
+impl Product {
+    fn of(x: Expr) -> Product {
+        let mut product = Product {
+            coefficient: Coefficient::ONE,
+            factors: Vec::new(),
+        };
+        product.collect(x, false);
+        product
+    }
+    fn collect(&mut self, x: Expr, inverse: bool) {
+        if let Some(c) = Coefficient::of(&x) {
+            self.coefficient = if inverse {
+                self.coefficient.div(c)
+            } else {
+                self.coefficient.mul(c)
+            };
+            return;
+        }
+        match x {
+            Expr::Operator(LeftAssociativeOperator::Multiply, a, b) => {
+                self.collect(*a, inverse);
+                self.collect(*b, inverse);
+            }
+            Expr::Operator(LeftAssociativeOperator::Divide, a, b) => {
+                self.collect(*a, inverse);
+                self.collect(*b, !inverse);
+            }
+            Expr::Negate(a) => {
+                self.coefficient = self.coefficient.negate();
+                self.collect(*a, inverse);
+            }
+            Expr::Operator(LeftAssociativeOperator::Power, base, exponent) => {
+                self.factor(*base, if inverse { -*exponent } else { *exponent })
+            }
+            x => self.factor(x, Expr::Number(if inverse { -1.0 } else { 1.0 })),
+        }
+    }
+    fn factor(&mut self, base: Expr, exponent: Expr) {
+        match self.factors.iter_mut().find(|(b, _)| *b == base) {
+            Some((_, e)) => *e = e.clone() + exponent,
+            None => self.factors.push((base, exponent)),
+        }
+    }
+    // written as the coefficient and the positive powers over the negative ones (i.e. `-2 * x / y^2`)
+    fn build(self) -> Expr {
+        let Coefficient { numer, denom } = self.coefficient;
+        if numer == 0.0 {
+            return Expr::Number(0.0);
+        }
+        let times = |product: Option<Expr>, x: Expr| {
+            Some(match product {
+                Some(product) => Expr::binary(LeftAssociativeOperator::Multiply, product, x),
+                None => x,
+            })
+        };
+        let mut numerator = (numer.abs() != 1.0).then_some(Expr::Number(numer.abs()));
+        let mut denominator = (denom != 1.0).then_some(Expr::Number(denom));
+        for (base, exponent) in self.factors {
+            match exponent {
+                Expr::Number(0.0) => {}
+                exponent if exponent.is_negative() => {
+                    denominator = times(denominator, base.pow(-exponent))
+                }
+                exponent => numerator = times(numerator, base.pow(exponent)),
+            }
+        }
+        let numerator = numerator.unwrap_or(Expr::Number(1.0));
+        let value = match denominator {
+            Some(denominator) => {
+                Expr::binary(LeftAssociativeOperator::Divide, numerator, denominator)
+            }
+            None => numerator,
+        };
+        if numer < 0.0 {
+            Expr::Negate(Box::new(value))
+        } else {
+            value
+        }
+    }
+}
+
+// a sum as coefficients times its terms, where the multiples of the same term are collected (i.e. `2*x + x` is `3*x`)
+#[derive(Debug, Default)]
+struct Sum {
+    terms: Vec<(Coefficient, Vec<(Expr, Expr)>)>,
+}
+
+impl Sum {
+    fn collect(&mut self, x: Expr, negative: bool) {
+        match x {
+            Expr::Operator(LeftAssociativeOperator::Add, a, b) => {
+                self.collect(*a, negative);
+                self.collect(*b, negative);
+            }
+            Expr::Operator(LeftAssociativeOperator::Subtract, a, b) => {
+                self.collect(*a, negative);
+                self.collect(*b, !negative);
+            }
+            Expr::Negate(a) => self.collect(*a, !negative),
+            x => {
+                let Product {
+                    coefficient,
+                    factors,
+                } = Product::of(x);
+                let coefficient = if negative {
+                    coefficient.negate()
+                } else {
+                    coefficient
+                };
+                match self.terms.iter_mut().find(|(_, term)| *term == factors) {
+                    Some((c, _)) => *c = c.add(coefficient),
+                    None => self.terms.push((coefficient, factors)),
+                }
+            }
+        }
+    }
+    fn build(mut self) -> Expr {
+        self.terms
+            .retain(|(coefficient, _)| coefficient.numer != 0.0);
+        // it starts with a positive term when there is one (i.e. `x - 1` rather than `-1 + x`)
+        if let Some(first) = self.terms.iter().position(|(c, _)| c.numer > 0.0) {
+            let term = self.terms.remove(first);
+            self.terms.insert(0, term);
+        }
+        let mut sum = None;
+        for (coefficient, factors) in self.terms {
+            let negative = coefficient.numer < 0.0;
+            let term = Product {
+                coefficient: if negative {
+                    coefficient.negate()
+                } else {
+                    coefficient
+                },
+                factors,
+            }
+            .build();
+            sum = Some(match sum {
+                None if negative => Expr::Negate(Box::new(term)),
+                None => term,
+                Some(sum) if negative => Expr::binary(LeftAssociativeOperator::Subtract, sum, term),
+                Some(sum) => Expr::binary(LeftAssociativeOperator::Add, sum, term),
+            });
+        }
+        sum.unwrap_or(Expr::Number(0.0))
+    }
+}
+
+impl Add for Expr {
+    type Output = Expr;
+    fn add(self, rhs: Expr) -> Expr {
+        if let Some(sum) = exact(LeftAssociativeOperator::Add, &self, &rhs) {
+            return sum;
+        }
+        let mut sum = Sum::default();
+        sum.collect(self, false);
+        sum.collect(rhs, false);
+        sum.build()
+    }
+}
+
+impl Sub for Expr {
+    type Output = Expr;
+    fn sub(self, rhs: Expr) -> Expr {
+        if let Some(difference) = exact(LeftAssociativeOperator::Subtract, &self, &rhs) {
+            return difference;
+        }
+        let mut sum = Sum::default();
+        sum.collect(self, false);
+        sum.collect(rhs, true);
+        sum.build()
+    }
+}
+
+impl Mul for Expr {
+    type Output = Expr;
+    fn mul(self, rhs: Expr) -> Expr {
+        if let Some(product) = exact(LeftAssociativeOperator::Multiply, &self, &rhs) {
+            return product;
+        }
+        let mut product = Product::of(self);
+        product.collect(rhs, false);
+        product.build()
+    }
+}
+
+impl Div for Expr {
+    type Output = Expr;
+    fn div(self, rhs: Expr) -> Expr {
+        // fractions stay exact, so that `1/3` isn't rounded
+        if let Some(quotient) = exact(LeftAssociativeOperator::Divide, &self, &rhs) {
+            return quotient;
+        }
+        if rhs == Expr::Number(0.0) {
+            return Expr::binary(LeftAssociativeOperator::Divide, self, rhs);
+        }
+        let mut product = Product::of(self);
+        product.collect(rhs, true);
+        product.build()
+    }
+}
+
+impl Neg for Expr {
+    type Output = Expr;
+    fn neg(self) -> Expr {
+        match self {
+            Expr::Number(0.0) => Expr::Number(0.0),
+            Expr::Number(a) => Expr::Number(-a),
+            Expr::Negate(x) => *x,
+            Expr::Operator(LeftAssociativeOperator::Subtract, lhs, rhs) => *rhs - *lhs,
+            x => Expr::Negate(Box::new(x)),
+        }
+    }
+}
+
+// written so that the parser reads it back as the same tree, with as few brackets as that needs
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let bracketed = |f: &mut Formatter<'_>, x: &Expr, brackets: bool| {
+            if brackets {
+                write!(f, "({})", x)
+            } else {
+                write!(f, "{}", x)
+            }
+        };
+        if let Some((value, unit)) = self.quantity() {
+            return write!(f, "{}[{}]", value, unit.name());
+        }
+        match self {
+            Expr::Number(value) => write!(f, "{}", value),
+            Expr::Constant(constant) => write!(f, "{}", constant),
+            Expr::Variable(name) => write!(f, "{}", name),
+            Expr::Negate(x) => {
+                write!(f, "-")?;
+                bracketed(f, x, x.precedence() < PRODUCT || x.is_negative())
+            }
+            // the factorial takes the single item before it
+            Expr::Function(FunctionalOperator::Factorial, x) => {
+                let item = matches!(**x, Expr::Number(_) | Expr::Constant(_) | Expr::Variable(_));
+                bracketed(f, x, !item || x.is_negative())?;
+                write!(f, "!")
+            }
+            Expr::Function(func, x) => write!(f, "{}({})", func.name(), x),
+            Expr::Operator(LeftAssociativeOperator::Root, degree, x) => {
+                write!(f, "root({}, {})", degree, x)
+            }
+            Expr::Operator(op, lhs, rhs) => {
+                let precedence = self.precedence();
+                bracketed(f, lhs, lhs.precedence() < precedence)?;
+                if precedence == SUM {
+                    write!(f, " {} ", op.symbol())?;
+                } else {
+                    write!(f, "{}", op.symbol())?;
+                }
+                bracketed(f, rhs, rhs.precedence() <= precedence || rhs.is_negative())
+            }
+        }
+    }
+}
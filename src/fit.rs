@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use crate::{equation::Equation, error::EquationError, numeric::unsupported, system::solve_f64};
+
+// the relative change in the sum of squares or the parameters that counts as settled
+const TOLERANCE: f64 = 1e-12;
+// the cosine between the residuals and the derivative of every parameter that counts as a minimum
+const GRADIENT_TOLERANCE: f64 = 1e-8;
+const MAX_ITERATIONS: usize = 200;
+
+/// The parameters of a model fitted to data by [`fit`], along with how well they fit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    parameters: Vec<String>,
+    values: HashMap<String, f64>,
+    residuals: Vec<f64>,
+    covariance: Option<Vec<Vec<f64>>>,
+    iterations: usize,
+    converged: bool,
+}
+
+impl Fit {
+    /// Gives the parameters in the order they were given, which is the order of the rows and columns of the covariance.
+    pub fn parameters(&self) -> &[String] {
+        &self.parameters
+    }
+    /// Gives the estimate of every parameter.
+    pub fn values(&self) -> &HashMap<String, f64> {
+        &self.values
+    }
+    /// Gives the estimate of a parameter.
+    pub fn get(&self, parameter: &str) -> Option<f64> {
+        self.values.get(parameter).copied()
+    }
+    /// Gives the data minus the model at every point, in the order of the data.
+    pub fn residuals(&self) -> &[f64] {
+        &self.residuals
+    }
+    /// Gives the sum of the squares of the residuals, which the fit minimizes.
+    pub fn sum_of_squares(&self) -> f64 {
+        self.residuals.iter().map(|r| r * r).sum()
+    }
+    /// Gives the estimated covariance of the parameters, in the order they were given.
+    /// It is `None` when there are no more points than parameters, or the parameters can't be told apart by the data (i.e. `a*b*t`).
+    pub fn covariance(&self) -> Option<&[Vec<f64>]> {
+        self.covariance.as_deref()
+    }
+    /// Gives the standard error of a parameter, which is the square root of its variance.
+    pub fn standard_error(&self, parameter: &str) -> Option<f64> {
+        let i = self.parameters.iter().position(|name| name == parameter)?;
+        Some(self.covariance.as_ref()?[i][i].sqrt())
+    }
+    /// Gives the number of steps taken.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+    /// Checks if the parameters settled at a minimum of the sum of squares. When they didn't, the values are the best that were found.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+}
+
+/// Fits `parameters` of `equation` to points of `independent` and the value of the equation there, by least squares with Levenberg-Marquardt.
+/// Every parameter starts at 1 (see [`fit_from`]), and other variables have to be set.
+pub fn fit(
+    equation: &Equation,
+    parameters: &[&str],
+    independent: &str,
+    data: &[(f64, f64)],
+) -> Result<Fit, EquationError> {
+    fit_from(equation, parameters, independent, data, &HashMap::new())
+}
+
+/// Fits like [`fit`], starting from `initial_guess`, where parameters that aren't given start at 1.
+/// A guess near the answer helps models with several local minima (i.e. `sin(w*t)`).
+pub fn fit_from(
+    equation: &Equation,
+    parameters: &[&str],
+    independent: &str,
+    data: &[(f64, f64)],
+    initial_guess: &HashMap<&str, f64>,
+) -> Result<Fit, EquationError> {
+    if parameters.is_empty() || data.is_empty() {
+        return Err(unsupported(format!(
+            "A fit of {} parameters to {} points",
+            parameters.len(),
+            data.len()
+        )));
+    }
+    if let Some(parameter) = parameters
+        .iter()
+        .enumerate()
+        .find_map(|(i, parameter)| parameters[..i].contains(parameter).then_some(parameter))
+    {
+        return Err(unsupported(format!(
+            "The parameter {} given twice",
+            parameter
+        )));
+    }
+    if parameters.contains(&independent) {
+        return Err(unsupported(format!(
+            "A fit of {} as both a parameter and the independent variable",
+            independent
+        )));
+    }
+    let model = Model {
+        equation,
+        parameters,
+        independent,
+        derivatives: parameters
+            .iter()
+            .map(|parameter| equation.derivative(parameter))
+            .collect(),
+    };
+    let n = parameters.len();
+    let mut p: Vec<f64> = parameters
+        .iter()
+        .map(|parameter| initial_guess.get(parameter).copied().unwrap_or(1.0))
+        .collect();
+    let mut r = model.residuals(&p, data)?;
+    let mut s = sum_squares(&r);
+    if !s.is_finite() {
+        return Err(unsupported(format!(
+            "The model can't be evaluated at the initial guess {:?}",
+            p
+        )));
+    }
+    let mut lambda = 1e-3;
+    let mut iterations = 0;
+    let mut converged = false;
+    // at a minimum the residuals are orthogonal to the derivative of every parameter, or they are as small as rounding leaves them
+    let exact = TOLERANCE * TOLERANCE * data.iter().map(|(_, y)| y * y).sum::<f64>();
+    let flat = |jtj: &[Vec<f64>], jtr: &[f64], s: f64| {
+        s <= exact
+            || jtr
+                .iter()
+                .enumerate()
+                .all(|(i, g)| g.abs() <= GRADIENT_TOLERANCE * (jtj[i][i] * s).sqrt())
+    };
+    while iterations < MAX_ITERATIONS {
+        iterations += 1;
+        let jacobian = model.jacobian(&p, data)?;
+        let (jtj, jtr) = normal_equations(&jacobian, &r);
+        if flat(&jtj, &jtr, s) {
+            converged = true;
+            break;
+        }
+        // raise the damping until a step lowers the sum of squares, where a parameter that changes nothing still gets some
+        let largest = (0..n).map(|i| jtj[i][i]).fold(f64::MIN_POSITIVE, f64::max);
+        let mut next = None;
+        while lambda < 1e16 {
+            let mut matrix: Vec<Vec<f64>> = jtj
+                .iter()
+                .zip(&jtr)
+                .map(|(row, g)| row.iter().copied().chain([*g]).collect())
+                .collect();
+            for (i, row) in matrix.iter_mut().enumerate() {
+                row[i] += lambda * jtj[i][i].max(1e-12 * largest);
+            }
+            if let Some(step) = solve_f64(&matrix, n) {
+                let candidate: Vec<f64> = p.iter().zip(&step).map(|(p, d)| p + d).collect();
+                let candidate_r = model.residuals(&candidate, data)?;
+                let candidate_s = sum_squares(&candidate_r);
+                if candidate_s < s {
+                    next = Some((candidate, candidate_r, candidate_s, step));
+                    break;
+                }
+            }
+            lambda *= 10.0;
+        }
+        // no step makes it any better, so this is as close as it gets, though it isn't a minimum
+        let Some((candidate, candidate_r, candidate_s, step)) = next else {
+            break;
+        };
+        lambda = (lambda / 10.0).max(1e-12);
+        let settled = s - candidate_s <= TOLERANCE * s
+            || step
+                .iter()
+                .zip(&candidate)
+                .all(|(d, p)| d.abs() <= TOLERANCE * p.abs().max(1.0));
+        (p, r, s) = (candidate, candidate_r, candidate_s);
+        if settled {
+            let (jtj, jtr) = normal_equations(&model.jacobian(&p, data)?, &r);
+            converged = flat(&jtj, &jtr, s);
+            break;
+        }
+    }
+    // the covariance is s^2 (JᵀJ)⁻¹, where s^2 is the variance of the residuals left
+    let covariance = if data.len() > n {
+        let (jtj, _) = normal_equations(&model.jacobian(&p, data)?, &r);
+        let variance = s / (data.len() - n) as f64;
+        (0..n)
+            .map(|j| {
+                let matrix: Vec<Vec<f64>> = jtj
+                    .iter()
+                    .enumerate()
+                    .map(|(i, row)| row.iter().copied().chain([(i == j) as u8 as f64]).collect())
+                    .collect();
+                solve_f64(&matrix, n)
+            })
+            .collect::<Option<Vec<Vec<f64>>>>()
+            .map(|columns| {
+                (0..n)
+                    .map(|i| (0..n).map(|j| variance * columns[j][i]).collect())
+                    .collect()
+            })
+    } else {
+        None
+    };
+    Ok(Fit {
+        parameters: parameters.iter().map(|name| name.to_string()).collect(),
+        values: parameters
+            .iter()
+            .map(|name| name.to_string())
+            .zip(p)
+            .collect(),
+        residuals: r,
+        covariance,
+        iterations,
+        converged,
+    })
+}
+
+// the equation as a function of its parameters at every point
+struct Model<'a> {
+    equation: &'a Equation,
+    parameters: &'a [&'a str],
+    independent: &'a str,
+    // the derivative with respect to every parameter, where it is known in closed form
+    derivatives: Vec<Option<Equation>>,
+}
+
+impl Model<'_> {
+    fn bindings(&self, p: &[f64], t: f64) -> HashMap<&str, f64> {
+        let mut bindings: HashMap<&str, f64> = self
+            .parameters
+            .iter()
+            .copied()
+            .zip(p.iter().copied())
+            .collect();
+        bindings.insert(self.independent, t);
+        bindings
+    }
+    fn residuals(&self, p: &[f64], data: &[(f64, f64)]) -> Result<Vec<f64>, EquationError> {
+        data.iter()
+            .map(|(t, y)| {
+                Ok(y - self
+                    .equation
+                    .evaluate_with_bindings(&self.bindings(p, *t))?)
+            })
+            .collect()
+    }
+    // the derivative of the model at every point (a row) with respect to every parameter (a column)
+    fn jacobian(&self, p: &[f64], data: &[(f64, f64)]) -> Result<Vec<Vec<f64>>, EquationError> {
+        let mut jacobian = vec![vec![0.0; p.len()]; data.len()];
+        for (j, derivative) in self.derivatives.iter().enumerate() {
+            match derivative {
+                Some(derivative) => {
+                    for (row, (t, _)) in jacobian.iter_mut().zip(data) {
+                        row[j] = derivative.evaluate_with_bindings(&self.bindings(p, *t))?;
+                    }
+                }
+                // central differences, like a system's jacobian
+                None => {
+                    let h = f64::EPSILON.cbrt() * p[j].abs().max(1.0);
+                    let mut above = p.to_vec();
+                    above[j] += h;
+                    let mut below = p.to_vec();
+                    below[j] -= h;
+                    let (above, below) =
+                        (self.residuals(&above, data)?, self.residuals(&below, data)?);
+                    for (i, row) in jacobian.iter_mut().enumerate() {
+                        row[j] = (below[i] - above[i]) / (2.0 * h);
+                    }
+                }
+            }
+        }
+        Ok(jacobian)
+    }
+}
+
+fn sum_squares(values: &[f64]) -> f64 {
+    // NaN is never a better fit
+    let sum: f64 = values.iter().map(|value| value * value).sum();
+    if sum.is_nan() {
+        f64::INFINITY
+    } else {
+        sum
+    }
+}
+
+// JᵀJ and Jᵀr, where the step δ solves JᵀJ δ = Jᵀr
+fn normal_equations(jacobian: &[Vec<f64>], r: &[f64]) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let n = jacobian.first().map_or(0, Vec::len);
+    let jtj = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| jacobian.iter().map(|row| row[i] * row[j]).sum())
+                .collect()
+        })
+        .collect();
+    let jtr = (0..n)
+        .map(|i| jacobian.iter().zip(r).map(|(row, r)| row[i] * r).sum())
+        .collect();
+    (jtj, jtr)
+}
@@ -0,0 +1,310 @@
+use crate::error::EquationError;
+
+// the 15 point kronrod rule and the 7 point gauss rule inside it, on [-1, 1] (the nodes are symmetric, the last is the middle)
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.0,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_22,
+    0.063_092_092_629_978_55,
+    0.104_790_010_322_250_18,
+    0.140_653_259_715_525_92,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_83,
+];
+// the weights of the gauss rule, which uses every other kronrod node
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+// how many times an interval can be halved before the refinement around it counts as a singularity
+const SINGULAR_DEPTH: u32 = 24;
+
+/// The options of [`Equation::integrate`](crate::Equation::integrate).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntegrationOptions {
+    /// The error that is always small enough, `1e-12` by default.
+    pub absolute_tolerance: f64,
+    /// The error relative to the value that is small enough, `1e-10` by default.
+    pub relative_tolerance: f64,
+    /// The most intervals the range is split into, 1000 by default.
+    pub max_subdivisions: usize,
+}
+
+impl Default for IntegrationOptions {
+    fn default() -> Self {
+        IntegrationOptions {
+            absolute_tolerance: 1e-12,
+            relative_tolerance: 1e-10,
+            max_subdivisions: 1000,
+        }
+    }
+}
+
+/// The result of [`Equation::integrate`](crate::Equation::integrate).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Integral {
+    value: f64,
+    error: f64,
+    evaluations: usize,
+    converged: bool,
+    singularities: Vec<f64>,
+}
+
+impl Integral {
+    /// Gives the value of the integral.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+    /// Gives an estimate of the absolute error of the value.
+    pub fn error(&self) -> f64 {
+        self.error
+    }
+    /// Gives the number of times the equation was evaluated.
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+    /// Checks if the error got within the tolerances. When it didn't, the integral might diverge (i.e. `1/x` from 0).
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+    /// Gives the points where the integrand seems to blow up (i.e. 0 for `1/sqrt(x)`), which needed many more evaluations around them.
+    /// The integral can still converge when they are integrable.
+    pub fn singularities(&self) -> &[f64] {
+        &self.singularities
+    }
+}
+
+// an interval and its estimate
+#[derive(Debug)]
+struct Piece {
+    lo: f64,
+    hi: f64,
+    value: f64,
+    error: f64,
+    depth: u32,
+    // the end the integrand was largest towards
+    peak: f64,
+}
+
+/// Integrates `f` from `a` to `b` with adaptive gauss-kronrod quadrature, where infinite bounds are mapped onto finite ones.
+pub(crate) fn integrate(
+    f: impl Fn(f64) -> Result<f64, EquationError>,
+    a: f64,
+    b: f64,
+    options: &IntegrationOptions,
+) -> Result<Integral, EquationError> {
+    if a == b {
+        return Ok(Integral {
+            value: 0.0,
+            error: 0.0,
+            evaluations: 0,
+            converged: true,
+            singularities: Vec::new(),
+        });
+    }
+    if a > b {
+        let mut integral = integrate(f, b, a, options)?;
+        integral.value = -integral.value;
+        return Ok(integral);
+    }
+    match (a.is_finite(), b.is_finite()) {
+        (true, true) => adaptive(&f, a, b, options, |t| t),
+        // x = a + (1 - t) / t for t in (0, 1], where dx = dt / t^2
+        (true, false) => adaptive(
+            &|t| Ok(f(a + (1.0 - t) / t)? / (t * t)),
+            0.0,
+            1.0,
+            options,
+            |t| a + (1.0 - t) / t,
+        ),
+        (false, true) => adaptive(
+            &|t| Ok(f(b - (1.0 - t) / t)? / (t * t)),
+            0.0,
+            1.0,
+            options,
+            |t| b - (1.0 - t) / t,
+        ),
+        // x = t / (1 - t^2) for t in (-1, 1), where dx = (1 + t^2) / (1 - t^2)^2 dt
+        (false, false) => adaptive(
+            &|t| {
+                let s = 1.0 - t * t;
+                Ok(f(t / s)? * (1.0 + t * t) / (s * s))
+            },
+            -1.0,
+            1.0,
+            options,
+            |t| t / (1.0 - t * t),
+        ),
+    }
+}
+
+// integrates over a finite interval, where `x` maps the points of the interval back to the original variable for the singularities
+fn adaptive(
+    f: &dyn Fn(f64) -> Result<f64, EquationError>,
+    a: f64,
+    b: f64,
+    options: &IntegrationOptions,
+    x: impl Fn(f64) -> f64,
+) -> Result<Integral, EquationError> {
+    let mut evaluations = 0;
+    let mut pieces = vec![kronrod(f, a, b, 0, &mut evaluations)?];
+    // pieces that are too narrow to be split, which stay in the total as they are
+    let mut settled: Vec<Piece> = Vec::new();
+    let total = |pieces: &[Piece], settled: &[Piece]| {
+        let value: f64 = pieces.iter().chain(settled).map(|p| p.value).sum();
+        let error: f64 = pieces.iter().chain(settled).map(|p| p.error).sum();
+        (value, error)
+    };
+    let mut converged = false;
+    while pieces.len() + settled.len() < options.max_subdivisions.max(1) {
+        let (value, error) = total(&pieces, &settled);
+        if error
+            <= options
+                .absolute_tolerance
+                .max(options.relative_tolerance * value.abs())
+        {
+            converged = true;
+            break;
+        }
+        let Some(worst) =
+            (0..pieces.len()).max_by(|i, j| pieces[*i].error.total_cmp(&pieces[*j].error))
+        else {
+            break;
+        };
+        let piece = pieces.swap_remove(worst);
+        let middle = piece.lo / 2.0 + piece.hi / 2.0;
+        if middle <= piece.lo || middle >= piece.hi {
+            settled.push(piece);
+            continue;
+        }
+        pieces.push(kronrod(
+            f,
+            piece.lo,
+            middle,
+            piece.depth + 1,
+            &mut evaluations,
+        )?);
+        pieces.push(kronrod(
+            f,
+            middle,
+            piece.hi,
+            piece.depth + 1,
+            &mut evaluations,
+        )?);
+    }
+    let (value, error) = total(&pieces, &settled);
+    converged |= error
+        <= options
+            .absolute_tolerance
+            .max(options.relative_tolerance * value.abs());
+    // the deepest piece of every cluster of refinement
+    let mut deep: Vec<&Piece> = pieces
+        .iter()
+        .chain(&settled)
+        .filter(|p| p.depth >= SINGULAR_DEPTH)
+        .collect();
+    deep.sort_by_key(|p| std::cmp::Reverse(p.depth));
+    let mut singular_points: Vec<f64> = Vec::new();
+    let near = (b - a) / 2f64.powi(SINGULAR_DEPTH as i32 / 2);
+    for piece in deep {
+        if singular_points
+            .iter()
+            .all(|t| (t - piece.peak).abs() > near)
+        {
+            singular_points.push(piece.peak);
+        }
+    }
+    let mut singularities: Vec<f64> = singular_points
+        .into_iter()
+        .map(x)
+        .filter(|x| x.is_finite())
+        .collect();
+    singularities.sort_by(f64::total_cmp);
+    Ok(Integral {
+        value,
+        error,
+        evaluations,
+        converged,
+        singularities,
+    })
+}
+
+fn kronrod(
+    f: &dyn Fn(f64) -> Result<f64, EquationError>,
+    lo: f64,
+    hi: f64,
+    depth: u32,
+    evaluations: &mut usize,
+) -> Result<Piece, EquationError> {
+    let center = lo / 2.0 + hi / 2.0;
+    let half = hi / 2.0 - lo / 2.0;
+    let mut values = [(0.0, 0.0); 8];
+    for (i, node) in KRONROD_NODES.iter().enumerate() {
+        values[i] = if *node == 0.0 {
+            let value = f(center)?;
+            (value, value)
+        } else {
+            (f(center - half * node)?, f(center + half * node)?)
+        };
+    }
+    *evaluations += 15;
+    let middle = values[7].0;
+    let mut kronrod = KRONROD_WEIGHTS[7] * middle;
+    let mut gauss = GAUSS_WEIGHTS[3] * middle;
+    let mut absolute = KRONROD_WEIGHTS[7] * middle.abs();
+    for i in 0..7 {
+        let (left, right) = values[i];
+        kronrod += KRONROD_WEIGHTS[i] * (left + right);
+        absolute += KRONROD_WEIGHTS[i] * (left.abs() + right.abs());
+        if i % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[i / 2] * (left + right);
+        }
+    }
+    // the spread of the integrand around its mean, which scales the error estimate like quadpack does
+    let mean = kronrod / 2.0;
+    let spread = KRONROD_WEIGHTS[7] * (middle - mean).abs()
+        + (0..7)
+            .map(|i| KRONROD_WEIGHTS[i] * ((values[i].0 - mean).abs() + (values[i].1 - mean).abs()))
+            .sum::<f64>();
+    let (kronrod, gauss, absolute, spread) = (
+        kronrod * half,
+        gauss * half,
+        absolute * half.abs(),
+        spread * half.abs(),
+    );
+    let mut error = (kronrod - gauss).abs();
+    if spread != 0.0 && error != 0.0 {
+        error = spread * (200.0 * error / spread).powf(1.5).min(1.0);
+    }
+    if absolute > f64::MIN_POSITIVE / (50.0 * f64::EPSILON) {
+        error = error.max(50.0 * f64::EPSILON * absolute);
+    }
+    // something that isn't a number needs splitting as much as possible
+    let (value, error) = if kronrod.is_finite() && error.is_finite() {
+        (kronrod, error)
+    } else {
+        (kronrod, f64::INFINITY)
+    };
+    let (first, last) = values[0];
+    Ok(Piece {
+        lo,
+        hi,
+        value,
+        error,
+        depth,
+        peak: if first.abs() >= last.abs() { lo } else { hi },
+    })
+}
@@ -0,0 +1,464 @@
+use std::{
+    f64::consts::{FRAC_PI_2, PI, TAU},
+    fmt::{Display, Formatter},
+};
+
+use crate::{
+    error::EquationError,
+    item::{Constant, FunctionalOperator, LeftAssociativeOperator},
+    numeric::{division_by_zero, unsupported, Numeric},
+    special,
+};
+
+/// A closed range of real numbers, the result of [`Equation::evaluate_interval`](crate::Equation::evaluate_interval).
+/// Every operation rounds its bounds outwards, so the true result always lies within the interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    /// Creates an interval between two bounds, which can be given in either order.
+    pub fn new(a: f64, b: f64) -> Interval {
+        if a.is_nan() || b.is_nan() {
+            return Interval::entire();
+        }
+        Interval {
+            lo: a.min(b),
+            hi: a.max(b),
+        }
+    }
+    /// Creates an interval holding a single number.
+    pub fn point(x: f64) -> Interval {
+        Interval::new(x, x)
+    }
+    /// Creates the interval of every real number.
+    pub fn entire() -> Interval {
+        Interval {
+            lo: f64::NEG_INFINITY,
+            hi: f64::INFINITY,
+        }
+    }
+    /// Gives the lower bound.
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+    /// Gives the upper bound.
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+    /// Gives the distance between the bounds.
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+    /// Gives the number halfway between the bounds.
+    pub fn midpoint(&self) -> f64 {
+        self.lo / 2.0 + self.hi / 2.0
+    }
+    /// Checks if a number lies within the interval (i.e. whether the interval could hold a root when checking 0).
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+    // checks if the interval could hold `offset + k * period` for any integer k
+    fn hits(&self, offset: f64, period: f64) -> bool {
+        if self.width() >= period || !self.width().is_finite() {
+            return true;
+        }
+        // f64 multiples of pi drift away from the real ones, so this errs towards saying yes
+        let tolerance = 4.0 * f64::EPSILON * self.lo.abs().max(self.hi.abs()).max(1.0);
+        let k = ((self.lo - offset) / period).ceil();
+        [k - 1.0, k].iter().any(|k| {
+            let x = offset + k * period;
+            self.lo - tolerance <= x && x <= self.hi + tolerance
+        })
+    }
+    fn recip(self) -> Result<Interval, EquationError> {
+        divide(Interval::point(1.0), self)
+    }
+}
+
+// widens the bounds by `ulps` units in the last place, to cover the rounding of whatever computed them
+fn outward(lo: f64, hi: f64, ulps: u32) -> Interval {
+    let (mut lo, mut hi) = (
+        if lo.is_nan() { f64::NEG_INFINITY } else { lo },
+        if hi.is_nan() { f64::INFINITY } else { hi },
+    );
+    for _ in 0..ulps {
+        lo = lo.next_down();
+        hi = hi.next_up();
+    }
+    Interval::new(lo, hi)
+}
+
+// the smallest interval holding every value, widened for rounding
+fn hull(values: &[f64], ulps: u32) -> Interval {
+    if values.iter().any(|x| x.is_nan()) {
+        return Interval::entire();
+    }
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    outward(lo, hi, ulps)
+}
+
+// the libm functions are within a couple of units in the last place, this leaves a margin on top of that
+const FUNCTION_ULPS: u32 = 4;
+// the gamma and error functions are approximations good to about 15 digits
+const SPECIAL_ULPS: u32 = 64;
+// where gamma has its minimum on the positive numbers, and the minimum values of gamma and ln gamma
+const GAMMA_MIN_AT: f64 = 1.461_632_144_968_362_3;
+const GAMMA_MIN: f64 = 0.885_603_194_410_888_7;
+const LN_GAMMA_MIN: f64 = -0.121_486_290_535_849_6;
+
+fn multiply(a: Interval, b: Interval) -> Interval {
+    // zero times infinity is zero for the bounds of an interval
+    let mul = |x: f64, y: f64| if x == 0.0 || y == 0.0 { 0.0 } else { x * y };
+    hull(
+        &[
+            mul(a.lo, b.lo),
+            mul(a.lo, b.hi),
+            mul(a.hi, b.lo),
+            mul(a.hi, b.hi),
+        ],
+        1,
+    )
+}
+
+// dividing by an interval holding zero splits the result in two, this gives the hull of both pieces
+fn divide(a: Interval, b: Interval) -> Result<Interval, EquationError> {
+    if b.lo > 0.0 || b.hi < 0.0 {
+        return Ok(hull(
+            &[a.lo / b.lo, a.lo / b.hi, a.hi / b.lo, a.hi / b.hi],
+            1,
+        ));
+    }
+    if b.lo == 0.0 && b.hi == 0.0 {
+        return Err(division_by_zero());
+    }
+    if a.lo == 0.0 && a.hi == 0.0 {
+        return Ok(a);
+    }
+    let (inf, neg_inf) = (f64::INFINITY, f64::NEG_INFINITY);
+    Ok(if b.lo == 0.0 && a.lo >= 0.0 {
+        outward(a.lo / b.hi, inf, 1)
+    } else if b.lo == 0.0 && a.hi <= 0.0 {
+        outward(neg_inf, a.hi / b.hi, 1)
+    } else if b.hi == 0.0 && a.lo >= 0.0 {
+        outward(neg_inf, a.lo / b.lo, 1)
+    } else if b.hi == 0.0 && a.hi <= 0.0 {
+        outward(a.hi / b.lo, inf, 1)
+    } else {
+        Interval::entire()
+    })
+}
+
+fn power(base: Interval, exp: Interval) -> Result<Interval, EquationError> {
+    if exp.width() == 0.0 && exp.lo.fract() == 0.0 {
+        let n = exp.lo;
+        if n == 0.0 {
+            return Ok(Interval::point(1.0));
+        }
+        if n < 0.0 {
+            return power(base, Interval::point(-n))?.recip();
+        }
+        // powf of the magnitude is more accurate than powi's repeated multiplication
+        let pow = |x: f64| {
+            let val = x.abs().powf(n);
+            if x < 0.0 && n % 2.0 != 0.0 {
+                -val
+            } else {
+                val
+            }
+        };
+        if n % 2.0 == 0.0 && base.contains(0.0) {
+            let far = base.lo.abs().max(base.hi.abs());
+            return Ok(outward(0.0, pow(far), FUNCTION_ULPS).clamp_below(0.0));
+        }
+        return Ok(hull(&[pow(base.lo), pow(base.hi)], FUNCTION_ULPS));
+    }
+    if base.lo < 0.0 {
+        // negative bases only have real powers at integers
+        if exp.hits(0.0, 1.0) {
+            return Ok(Interval::entire());
+        }
+        if base.hi < 0.0 {
+            return Err(unsupported(
+                "A fractional power of a negative number is not real".to_string(),
+            ));
+        }
+    }
+    let base = Interval::new(base.lo.max(0.0), base.hi);
+    // x^y is monotonic in both x and y, so the extremes are on the corners
+    Ok(hull(
+        &[
+            base.lo.powf(exp.lo),
+            base.lo.powf(exp.hi),
+            base.hi.powf(exp.lo),
+            base.hi.powf(exp.hi),
+        ],
+        FUNCTION_ULPS,
+    )
+    .clamp_below(0.0))
+}
+
+// odd roots are defined for negative numbers too, any other degree is a power
+fn root(degree: Interval, x: Interval) -> Result<Interval, EquationError> {
+    let n = degree.lo;
+    if degree.width() == 0.0 && n.fract() == 0.0 && n % 2.0 != 0.0 && x.lo < 0.0 {
+        if n < 0.0 {
+            return root(Interval::point(-n), x)?.recip();
+        }
+        // odd roots are increasing
+        return Ok(x.increasing(|x| x.signum() * x.abs().powf(n.recip())));
+    }
+    power(x, degree.recip()?)
+}
+
+impl Interval {
+    // raises the lower bound to `min`, when rounding took it past a bound that is known
+    fn clamp_below(self, min: f64) -> Interval {
+        Interval::new(self.lo.max(min), self.hi.max(min))
+    }
+    fn clamp(self, min: f64, max: f64) -> Interval {
+        Interval::new(self.lo.clamp(min, max), self.hi.clamp(min, max))
+    }
+    fn increasing(self, f: impl Fn(f64) -> f64) -> Interval {
+        outward(f(self.lo), f(self.hi), FUNCTION_ULPS)
+    }
+    fn decreasing(self, f: impl Fn(f64) -> f64) -> Interval {
+        outward(f(self.hi), f(self.lo), FUNCTION_ULPS)
+    }
+    fn sin(self) -> Interval {
+        let mut val = hull(&[self.lo.sin(), self.hi.sin()], FUNCTION_ULPS);
+        if self.hits(FRAC_PI_2, TAU) {
+            val.hi = 1.0;
+        }
+        if self.hits(-FRAC_PI_2, TAU) {
+            val.lo = -1.0;
+        }
+        val.clamp(-1.0, 1.0)
+    }
+    fn cos(self) -> Interval {
+        let mut val = hull(&[self.lo.cos(), self.hi.cos()], FUNCTION_ULPS);
+        if self.hits(0.0, TAU) {
+            val.hi = 1.0;
+        }
+        if self.hits(PI, TAU) {
+            val.lo = -1.0;
+        }
+        val.clamp(-1.0, 1.0)
+    }
+    fn tan(self) -> Interval {
+        if self.hits(FRAC_PI_2, PI) {
+            return Interval::entire();
+        }
+        self.increasing(f64::tan)
+    }
+    fn cot(self) -> Interval {
+        if self.hits(0.0, PI) {
+            return Interval::entire();
+        }
+        self.decreasing(|x| x.cos() / x.sin())
+    }
+    fn cosh(self) -> Interval {
+        let far = hull(&[self.lo.cosh(), self.hi.cosh()], FUNCTION_ULPS);
+        if self.contains(0.0) {
+            return Interval::new(1.0, far.hi);
+        }
+        far.clamp_below(1.0)
+    }
+    fn abs(self) -> Interval {
+        let (lo, hi) = (self.lo.abs(), self.hi.abs());
+        if self.contains(0.0) {
+            return Interval::new(0.0, lo.max(hi));
+        }
+        Interval::new(lo, hi)
+    }
+    // for functions that never decrease and are exact in f64 (i.e. floor)
+    fn stepped(self, f: impl Fn(f64) -> f64) -> Interval {
+        Interval::new(f(self.lo), f(self.hi))
+    }
+    fn frac(self) -> Interval {
+        if self.lo.trunc() == self.hi.trunc() && (self.lo >= 0.0 || self.hi <= 0.0) {
+            return self.stepped(f64::fract);
+        }
+        // the interval holds a jump, so it could be anywhere on the side of its sign
+        Interval::new(
+            if self.lo < 0.0 { -1.0 } else { 0.0 },
+            if self.hi > 0.0 { 1.0 } else { 0.0 },
+        )
+    }
+    // decreasing then increasing on the positive numbers, with a minimum at `GAMMA_MIN_AT`
+    fn valley(self, f: fn(f64) -> f64, min: f64) -> Interval {
+        if self.contains(GAMMA_MIN_AT) {
+            let far = hull(&[f(self.lo), f(self.hi)], SPECIAL_ULPS);
+            return outward(min, far.hi, SPECIAL_ULPS);
+        }
+        if self.hi < GAMMA_MIN_AT {
+            outward(f(self.hi), f(self.lo), SPECIAL_ULPS)
+        } else {
+            outward(f(self.lo), f(self.hi), SPECIAL_ULPS)
+        }
+    }
+    fn gamma(self) -> Interval {
+        if self.lo > 0.0 {
+            return self.valley(special::gamma, GAMMA_MIN).clamp_below(0.0);
+        }
+        let pole = self.lo.floor();
+        if pole + 1.0 <= self.hi || self.lo == pole {
+            return Interval::entire();
+        }
+        // between the poles only the sign of gamma is known, which alternates
+        if pole % 2.0 == 0.0 {
+            Interval::new(0.0, f64::INFINITY)
+        } else {
+            Interval::new(f64::NEG_INFINITY, 0.0)
+        }
+    }
+    fn ln_gamma(self) -> Interval {
+        if self.lo > 0.0 {
+            return self.valley(special::ln_gamma, LN_GAMMA_MIN);
+        }
+        Interval::entire()
+    }
+    // atan(1/x) is decreasing on either side of zero, and jumps from -pi/2 up to pi/2 at zero
+    fn arccot(self) -> Interval {
+        if self.lo < 0.0 && self.hi >= 0.0 {
+            return outward(-FRAC_PI_2, FRAC_PI_2, FUNCTION_ULPS);
+        }
+        self.decreasing(|x| FunctionalOperator::Arccot.evaluate(x))
+    }
+    // applies a function defined outside of (-1, 1) to the parts of the interval in there
+    fn outside_unit(
+        self,
+        name: &str,
+        f: impl Fn(Interval) -> Interval,
+    ) -> Result<Interval, EquationError> {
+        let below = (self.lo <= -1.0).then(|| f(Interval::new(self.lo, self.hi.min(-1.0))));
+        let above = (self.hi >= 1.0).then(|| f(Interval::new(self.lo.max(1.0), self.hi)));
+        match (below, above) {
+            (Some(a), Some(b)) => Ok(hull(&[a.lo, a.hi, b.lo, b.hi], 0)),
+            (Some(piece), None) | (None, Some(piece)) => Ok(piece),
+            (None, None) => Err(unsupported(format!(
+                "The {} of {} is not a real number",
+                name, self
+            ))),
+        }
+    }
+    // clips the interval to the domain of a function, as long as some of it is in there
+    fn within(self, lo: f64, hi: f64, name: &str) -> Result<Interval, EquationError> {
+        if self.hi < lo || self.lo > hi {
+            return Err(unsupported(format!(
+                "The {} of {} is not a real number",
+                name, self
+            )));
+        }
+        Ok(self.clamp(lo, hi))
+    }
+}
+
+impl Numeric for Interval {
+    // integers are exact, anything else could have been rounded when parsed
+    fn from_f64(value: f64) -> Result<Self, EquationError> {
+        if value.fract() == 0.0 && value.abs() <= 2f64.powi(53) {
+            Ok(Interval::point(value))
+        } else {
+            Ok(outward(value, value, 1))
+        }
+    }
+    fn constant(constant: &Constant) -> Result<Self, EquationError> {
+        let value = constant.value();
+        Ok(outward(value, value, 2))
+    }
+    fn negate(self) -> Result<Self, EquationError> {
+        Ok(Interval::new(-self.hi, -self.lo))
+    }
+    fn function(func: &FunctionalOperator, x: Self) -> Result<Self, EquationError> {
+        match func {
+            FunctionalOperator::Log => Ok(x
+                .within(0.0, f64::INFINITY, "logarithm")?
+                .increasing(f64::log10)),
+            FunctionalOperator::Ln => Ok(x
+                .within(0.0, f64::INFINITY, "logarithm")?
+                .increasing(f64::ln)),
+            FunctionalOperator::Sin => Ok(x.sin()),
+            FunctionalOperator::Cos => Ok(x.cos()),
+            FunctionalOperator::Tan => Ok(x.tan()),
+            FunctionalOperator::Cot => Ok(x.cot()),
+            FunctionalOperator::Sec => x.cos().recip(),
+            FunctionalOperator::Csc => x.sin().recip(),
+            FunctionalOperator::Arcsin => {
+                Ok(x.within(-1.0, 1.0, "inverse sine")?.increasing(f64::asin))
+            }
+            FunctionalOperator::Arccos => {
+                Ok(x.within(-1.0, 1.0, "inverse cosine")?.decreasing(f64::acos))
+            }
+            FunctionalOperator::Arctan => Ok(x.increasing(f64::atan)),
+            FunctionalOperator::Arccot => Ok(x.arccot()),
+            // both are increasing (arcsec) or decreasing (arccsc) on either side of the gap
+            FunctionalOperator::Arcsec => x.outside_unit("inverse secant", |piece| {
+                piece.increasing(|x| func.evaluate(x)).clamp_below(0.0)
+            }),
+            FunctionalOperator::Arccsc => x.outside_unit("inverse cosecant", |piece| {
+                piece.decreasing(|x| func.evaluate(x))
+            }),
+            FunctionalOperator::Sqrt => Ok(x
+                .within(0.0, f64::INFINITY, "square root")?
+                .increasing(f64::sqrt)
+                .clamp_below(0.0)),
+            FunctionalOperator::Cbrt => Ok(x.increasing(f64::cbrt)),
+            FunctionalOperator::Sinh => Ok(x.increasing(f64::sinh)),
+            FunctionalOperator::Cosh => Ok(x.cosh()),
+            FunctionalOperator::Tanh => Ok(x.increasing(f64::tanh).clamp(-1.0, 1.0)),
+            FunctionalOperator::Arcsinh => Ok(x.increasing(f64::asinh)),
+            FunctionalOperator::Arccosh => Ok(x
+                .within(1.0, f64::INFINITY, "inverse hyperbolic cosine")?
+                .increasing(f64::acosh)
+                .clamp_below(0.0)),
+            FunctionalOperator::Arctanh => Ok(x
+                .within(-1.0, 1.0, "inverse hyperbolic tangent")?
+                .increasing(f64::atanh)),
+            FunctionalOperator::Abs => Ok(x.abs()),
+            FunctionalOperator::Sign
+            | FunctionalOperator::Floor
+            | FunctionalOperator::Ceil
+            | FunctionalOperator::Round
+            | FunctionalOperator::Trunc => Ok(x.stepped(|x| func.evaluate(x))),
+            FunctionalOperator::Frac => Ok(x.frac()),
+            FunctionalOperator::Exp => Ok(x.increasing(f64::exp).clamp_below(0.0)),
+            FunctionalOperator::Log2 => Ok(x
+                .within(0.0, f64::INFINITY, "logarithm")?
+                .increasing(f64::log2)),
+            FunctionalOperator::Gamma => Ok(x.gamma()),
+            FunctionalOperator::Lgamma => Ok(x.ln_gamma()),
+            FunctionalOperator::Erf => {
+                Ok(outward(special::erf(x.lo), special::erf(x.hi), SPECIAL_ULPS).clamp(-1.0, 1.0))
+            }
+            FunctionalOperator::Erfc => {
+                Ok(outward(special::erfc(x.hi), special::erfc(x.lo), SPECIAL_ULPS).clamp(0.0, 2.0))
+            }
+            FunctionalOperator::Factorial => {
+                Ok(
+                    Interval::operator(&LeftAssociativeOperator::Add, x, Interval::point(1.0))?
+                        .gamma(),
+                )
+            }
+        }
+    }
+    fn operator(op: &LeftAssociativeOperator, lhs: Self, rhs: Self) -> Result<Self, EquationError> {
+        match op {
+            LeftAssociativeOperator::Add => Ok(outward(lhs.lo + rhs.lo, lhs.hi + rhs.hi, 1)),
+            LeftAssociativeOperator::Subtract => Ok(outward(lhs.lo - rhs.hi, lhs.hi - rhs.lo, 1)),
+            LeftAssociativeOperator::Multiply => Ok(multiply(lhs, rhs)),
+            LeftAssociativeOperator::Divide => divide(lhs, rhs),
+            LeftAssociativeOperator::Power => power(lhs, rhs),
+            LeftAssociativeOperator::Root => root(lhs, rhs),
+        }
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
@@ -1,10 +1,20 @@
-use crate::equation::Group;
+#[cfg(feature = "complex")]
+use num_complex::Complex64;
+
+use std::{
+    fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
+};
+
+use crate::{equation::Group, special, units::Unit};
 
 /// An item represents a single item in an equation.
 #[derive(Debug, Clone)]
 pub enum Item {
     /// A value is a number.
     Value(f64),
+    /// A constant is a mathematical constant (i.e. pi).
+    Constant(Constant),
     /// A variable is a variable that can be replaced later.
     Variable(String),
     /// An operator is an operator that can be used in an equation (i.e. +).
@@ -13,8 +23,25 @@ pub enum Item {
     Group(Group),
 }
 
+/// A mathematical constant. These are kept apart from values so that they can be computed in the precision an equation is evaluated in.
+#[derive(Debug, Clone)]
+pub enum Constant {
+    /// The ratio of a circle's circumference to its diameter.
+    Pi,
+    /// Euler's number.
+    E,
+    /// The ratio of a circle's circumference to its radius (2 pi).
+    Tau,
+    /// One degree in radians (pi / 180).
+    Deg,
+    /// A constant from a [`Constants`](crate::Constants) table, with the value it was given.
+    Custom(f64),
+    /// A unit of measurement following a literal (i.e. the `m` of `3m` or `3 [m]`), which is its size in SI base units outside of [`Equation::evaluate_with_units`](crate::Equation::evaluate_with_units).
+    Unit(Unit),
+}
+
 /// An operator is an operator that can be used in an equation (i.e. +).
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Operator {
     /// A left-associative operator is an operator that requires a left and right of the equation (i.e. +).
     LeftAssociative(LeftAssociativeOperator),
@@ -23,7 +50,7 @@ pub enum Operator {
 }
 
 /// A left-associative operator is an operator that requires a left and right of the equation (i.e. +).
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum LeftAssociativeOperator {
     /// The addition operator.
     Add,
@@ -35,12 +62,12 @@ pub enum LeftAssociativeOperator {
     Divide,
     /// The exponentiation operator.
     Power,
-    /// The root operator. (not used)
+    /// The root operator, the left is the degree and the right is the value (i.e. `3√8` is 2).
     Root,
 }
 
 /// A functional operator is an operator that requires only a right of the equation (i.e. sin).
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FunctionalOperator {
     /// The Log (base 10)
     Log,
@@ -64,12 +91,121 @@ pub enum FunctionalOperator {
     Arccos,
     /// The inverse tangent function.
     Arctan,
-    /// The inverse cotangent function.
+    /// The inverse cotangent function, `arctan(1/x)`.
+    /// Its values are in (-pi/2, pi/2] with `arccot(0) = pi/2`, so it jumps at zero and is negative for negative numbers
+    /// (rather than the continuous (0, pi) branch).
     Arccot,
-    /// The inverse secant function.
+    /// The inverse secant function, `arccos(1/x)`, with values in [0, pi].
     Arcsec,
-    /// The inverse cosecant function.
+    /// The inverse cosecant function, `arcsin(1/x)`, with values in [-pi/2, pi/2].
     Arccsc,
+    /// The square root.
+    Sqrt,
+    /// The cube root, which is negative for negative numbers, also with complex numbers. Other complex numbers take the principal root.
+    Cbrt,
+    /// The hyperbolic sine function.
+    Sinh,
+    /// The hyperbolic cosine function.
+    Cosh,
+    /// The hyperbolic tangent function.
+    Tanh,
+    /// The inverse hyperbolic sine function.
+    Arcsinh,
+    /// The inverse hyperbolic cosine function.
+    Arccosh,
+    /// The inverse hyperbolic tangent function.
+    Arctanh,
+    /// The absolute value.
+    Abs,
+    /// The sign, which is -1, 0 or 1.
+    Sign,
+    /// Rounds down.
+    Floor,
+    /// Rounds up.
+    Ceil,
+    /// Rounds to the nearest integer, halfway cases away from zero.
+    Round,
+    /// Rounds towards zero.
+    Trunc,
+    /// The fractional part, `x - trunc(x)`.
+    Frac,
+    /// The exponential function (e^x).
+    Exp,
+    /// The log (base 2)
+    Log2,
+    /// The gamma function.
+    Gamma,
+    /// The natural log of the absolute value of the gamma function.
+    Lgamma,
+    /// The error function.
+    Erf,
+    /// The complementary error function.
+    Erfc,
+    /// The factorial, written after the value (i.e. `5!`).
+    Factorial,
+}
+
+// the bits of a float, where 0 and -0 are the same and so are all NaNs, so that items holding them can be Eq and Hash
+pub(crate) fn float_bits(value: f64) -> u64 {
+    if value.is_nan() {
+        f64::NAN.to_bits()
+    } else if value == 0.0 {
+        0
+    } else {
+        value.to_bits()
+    }
+}
+
+// values are the same when their bits are (see `float_bits`)
+impl PartialEq for Item {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Item::Value(a), Item::Value(b)) => float_bits(*a) == float_bits(*b),
+            (Item::Constant(a), Item::Constant(b)) => a == b,
+            (Item::Variable(a), Item::Variable(b)) => a == b,
+            (Item::Operator(a), Item::Operator(b)) => a == b,
+            (Item::Group(a), Item::Group(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Item {}
+
+impl Hash for Item {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Item::Value(value) => float_bits(*value).hash(state),
+            Item::Constant(constant) => constant.hash(state),
+            Item::Variable(name) => name.hash(state),
+            Item::Operator(op) => op.hash(state),
+            Item::Group(group) => group.hash(state),
+        }
+    }
+}
+
+impl PartialEq for Constant {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Constant::Custom(a), Constant::Custom(b)) => float_bits(*a) == float_bits(*b),
+            (Constant::Unit(a), Constant::Unit(b)) => a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for Constant {}
+
+impl Hash for Constant {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Constant::Custom(value) => float_bits(*value).hash(state),
+            Constant::Unit(unit) => unit.hash(state),
+            _ => {}
+        }
+    }
 }
 
 impl From<f64> for Item {
@@ -78,6 +214,12 @@ impl From<f64> for Item {
     }
 }
 
+impl From<Constant> for Item {
+    fn from(val: Constant) -> Self {
+        Item::Constant(val)
+    }
+}
+
 impl From<String> for Item {
     fn from(val: String) -> Self {
         Item::Variable(val)
@@ -126,6 +268,33 @@ impl From<Vec<Item>> for Item {
     }
 }
 
+impl Constant {
+    /// Returns the value of the constant as an f64.
+    pub fn value(&self) -> f64 {
+        match self {
+            Constant::Pi => std::f64::consts::PI,
+            Constant::E => std::f64::consts::E,
+            Constant::Tau => std::f64::consts::TAU,
+            Constant::Deg => std::f64::consts::PI / 180.0,
+            Constant::Custom(value) => *value,
+            Constant::Unit(unit) => unit.factor(),
+        }
+    }
+}
+
+// constants without a name the parser knows (i.e. units) are written as their value
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constant::Pi => write!(f, "pi"),
+            Constant::E => write!(f, "e"),
+            Constant::Tau => write!(f, "tau"),
+            Constant::Deg => write!(f, "deg"),
+            Constant::Custom(_) | Constant::Unit(_) => write!(f, "{}", self.value()),
+        }
+    }
+}
+
 impl FunctionalOperator {
     /// Returns the value of the operator given the right value.
     pub fn evaluate(&self, x: f64) -> f64 {
@@ -141,9 +310,160 @@ impl FunctionalOperator {
             FunctionalOperator::Arcsin => x.asin(),
             FunctionalOperator::Arccos => x.acos(),
             FunctionalOperator::Arctan => x.atan(),
-            FunctionalOperator::Arccot => x.atan().recip(),
-            FunctionalOperator::Arcsec => x.acos().recip(),
-            FunctionalOperator::Arccsc => x.asin().recip(),
+            FunctionalOperator::Arccot if x == 0.0 => std::f64::consts::FRAC_PI_2,
+            FunctionalOperator::Arccot => x.recip().atan(),
+            // taking the reciprocal first loses most of the digits near 1, this form doesn't
+            FunctionalOperator::Arcsec if x < 0.0 => {
+                std::f64::consts::PI - ((x - 1.0) * (x + 1.0)).sqrt().atan()
+            }
+            FunctionalOperator::Arcsec => ((x - 1.0) * (x + 1.0)).sqrt().atan(),
+            FunctionalOperator::Arccsc => ((x - 1.0) * (x + 1.0)).sqrt().recip().atan().copysign(x),
+            FunctionalOperator::Sqrt => x.sqrt(),
+            FunctionalOperator::Cbrt => x.cbrt(),
+            FunctionalOperator::Sinh => x.sinh(),
+            FunctionalOperator::Cosh => x.cosh(),
+            FunctionalOperator::Tanh => x.tanh(),
+            FunctionalOperator::Arcsinh => x.asinh(),
+            FunctionalOperator::Arccosh => x.acosh(),
+            FunctionalOperator::Arctanh => x.atanh(),
+            FunctionalOperator::Abs => x.abs(),
+            FunctionalOperator::Sign if x == 0.0 => 0.0,
+            FunctionalOperator::Sign => x.signum(),
+            FunctionalOperator::Floor => x.floor(),
+            FunctionalOperator::Ceil => x.ceil(),
+            FunctionalOperator::Round => x.round(),
+            FunctionalOperator::Trunc => x.trunc(),
+            FunctionalOperator::Frac => x.fract(),
+            FunctionalOperator::Exp => x.exp(),
+            FunctionalOperator::Log2 => x.log2(),
+            FunctionalOperator::Gamma => special::gamma(x),
+            FunctionalOperator::Lgamma => special::ln_gamma(x),
+            FunctionalOperator::Erf => special::erf(x),
+            FunctionalOperator::Erfc => special::erfc(x),
+            FunctionalOperator::Factorial => special::factorial(x),
+        }
+    }
+    /// Checks if the function takes an angle (i.e. sin), which is converted from the [`AngleMode`](crate::equation::AngleMode) of the equation.
+    pub fn takes_angle(&self) -> bool {
+        matches!(
+            self,
+            FunctionalOperator::Sin
+                | FunctionalOperator::Cos
+                | FunctionalOperator::Tan
+                | FunctionalOperator::Cot
+                | FunctionalOperator::Sec
+                | FunctionalOperator::Csc
+        )
+    }
+    /// Checks if the function gives an angle (i.e. arcsin), which is converted into the [`AngleMode`](crate::equation::AngleMode) of the equation.
+    pub fn gives_angle(&self) -> bool {
+        matches!(
+            self,
+            FunctionalOperator::Arcsin
+                | FunctionalOperator::Arccos
+                | FunctionalOperator::Arctan
+                | FunctionalOperator::Arccot
+                | FunctionalOperator::Arcsec
+                | FunctionalOperator::Arccsc
+        )
+    }
+    /// Gives the name the function is written with (i.e. `sin`), which is `!` for the factorial.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FunctionalOperator::Log => "log",
+            FunctionalOperator::Ln => "ln",
+            FunctionalOperator::Sin => "sin",
+            FunctionalOperator::Cos => "cos",
+            FunctionalOperator::Tan => "tan",
+            FunctionalOperator::Cot => "cot",
+            FunctionalOperator::Sec => "sec",
+            FunctionalOperator::Csc => "csc",
+            FunctionalOperator::Arcsin => "arcsin",
+            FunctionalOperator::Arccos => "arccos",
+            FunctionalOperator::Arctan => "arctan",
+            FunctionalOperator::Arccot => "arccot",
+            FunctionalOperator::Arcsec => "arcsec",
+            FunctionalOperator::Arccsc => "arccsc",
+            FunctionalOperator::Sqrt => "sqrt",
+            FunctionalOperator::Cbrt => "cbrt",
+            FunctionalOperator::Sinh => "sinh",
+            FunctionalOperator::Cosh => "cosh",
+            FunctionalOperator::Tanh => "tanh",
+            FunctionalOperator::Arcsinh => "arcsinh",
+            FunctionalOperator::Arccosh => "arccosh",
+            FunctionalOperator::Arctanh => "arctanh",
+            FunctionalOperator::Abs => "abs",
+            FunctionalOperator::Sign => "sign",
+            FunctionalOperator::Floor => "floor",
+            FunctionalOperator::Ceil => "ceil",
+            FunctionalOperator::Round => "round",
+            FunctionalOperator::Trunc => "trunc",
+            FunctionalOperator::Frac => "frac",
+            FunctionalOperator::Exp => "exp",
+            FunctionalOperator::Log2 => "log2",
+            FunctionalOperator::Gamma => "gamma",
+            FunctionalOperator::Lgamma => "lgamma",
+            FunctionalOperator::Erf => "erf",
+            FunctionalOperator::Erfc => "erfc",
+            FunctionalOperator::Factorial => "!",
+        }
+    }
+    /// Checks if the function is only defined for real numbers, even when evaluating over the complex numbers.
+    pub fn is_real_only(&self) -> bool {
+        matches!(
+            self,
+            FunctionalOperator::Gamma
+                | FunctionalOperator::Lgamma
+                | FunctionalOperator::Erf
+                | FunctionalOperator::Erfc
+                | FunctionalOperator::Factorial
+        )
+    }
+    /// Returns the complex value of the operator given the right value. Uses the principal branch of every function.
+    /// The rounding functions act on the real and imaginary parts separately, and the [real only](Self::is_real_only) functions are NaN off the real line.
+    #[cfg(feature = "complex")]
+    pub fn evaluate_complex(&self, x: Complex64) -> Complex64 {
+        match self {
+            FunctionalOperator::Log => x.log10(),
+            FunctionalOperator::Ln => x.ln(),
+            FunctionalOperator::Sin => x.sin(),
+            FunctionalOperator::Cos => x.cos(),
+            FunctionalOperator::Tan => x.tan(),
+            FunctionalOperator::Cot => x.tan().inv(),
+            FunctionalOperator::Sec => x.cos().inv(),
+            FunctionalOperator::Csc => x.sin().inv(),
+            FunctionalOperator::Arcsin => x.asin(),
+            FunctionalOperator::Arccos => x.acos(),
+            FunctionalOperator::Arctan => x.atan(),
+            FunctionalOperator::Arccot if x == Complex64::default() => {
+                Complex64::new(std::f64::consts::FRAC_PI_2, 0.0)
+            }
+            FunctionalOperator::Arccot => x.inv().atan(),
+            FunctionalOperator::Arcsec => x.inv().acos(),
+            FunctionalOperator::Arccsc => x.inv().asin(),
+            FunctionalOperator::Sqrt => x.sqrt(),
+            // the real root of a real number, like in real evaluation
+            FunctionalOperator::Cbrt if x.im == 0.0 => Complex64::new(x.re.cbrt(), 0.0),
+            FunctionalOperator::Cbrt => x.cbrt(),
+            FunctionalOperator::Sinh => x.sinh(),
+            FunctionalOperator::Cosh => x.cosh(),
+            FunctionalOperator::Tanh => x.tanh(),
+            FunctionalOperator::Arcsinh => x.asinh(),
+            FunctionalOperator::Arccosh => x.acosh(),
+            FunctionalOperator::Arctanh => x.atanh(),
+            FunctionalOperator::Abs => Complex64::new(x.norm(), 0.0),
+            FunctionalOperator::Sign if x == Complex64::default() => x,
+            FunctionalOperator::Sign => x / x.norm(),
+            FunctionalOperator::Floor
+            | FunctionalOperator::Ceil
+            | FunctionalOperator::Round
+            | FunctionalOperator::Trunc
+            | FunctionalOperator::Frac => Complex64::new(self.evaluate(x.re), self.evaluate(x.im)),
+            FunctionalOperator::Exp => x.exp(),
+            FunctionalOperator::Log2 => x.log2(),
+            _ if x.im != 0.0 => Complex64::new(f64::NAN, f64::NAN),
+            // ln|gamma| is real, the complex log gamma would need a branch for every negative pole
+            _ => Complex64::new(self.evaluate(x.re), 0.0),
         }
     }
 }
@@ -162,6 +482,17 @@ impl LeftAssociativeOperator {
             LeftAssociativeOperator::Power | LeftAssociativeOperator::Root => *order == 0,
         }
     }
+    /// Gives the symbol the operator is written with (i.e. `+`).
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            LeftAssociativeOperator::Add => "+",
+            LeftAssociativeOperator::Subtract => "-",
+            LeftAssociativeOperator::Multiply => "*",
+            LeftAssociativeOperator::Divide => "/",
+            LeftAssociativeOperator::Power => "^",
+            LeftAssociativeOperator::Root => "√",
+        }
+    }
     /// Returns the value of the operator given the left and right values.
     pub fn eval(&self, lhs: f64, rhs: f64) -> f64 {
         match self {
@@ -170,7 +501,44 @@ impl LeftAssociativeOperator {
             LeftAssociativeOperator::Multiply => lhs * rhs,
             LeftAssociativeOperator::Divide => lhs / rhs,
             LeftAssociativeOperator::Power => lhs.powf(rhs),
+            // odd roots of negative numbers are real
+            LeftAssociativeOperator::Root if rhs < 0.0 && is_odd(lhs) => -(-rhs).powf(lhs.recip()),
             LeftAssociativeOperator::Root => rhs.powf(lhs.recip()),
         }
     }
+    /// Returns the complex value of the operator given the left and right values. Powers and roots use the principal branch, except that odd roots of negative real numbers are real, like [`Cbrt`](FunctionalOperator::Cbrt).
+    #[cfg(feature = "complex")]
+    pub fn eval_complex(&self, lhs: Complex64, rhs: Complex64) -> Complex64 {
+        match self {
+            LeftAssociativeOperator::Add => lhs + rhs,
+            LeftAssociativeOperator::Subtract => lhs - rhs,
+            LeftAssociativeOperator::Multiply => lhs * rhs,
+            LeftAssociativeOperator::Divide => lhs / rhs,
+            LeftAssociativeOperator::Power => complex_pow(lhs, rhs),
+            // the real root of a real number, like in real evaluation
+            LeftAssociativeOperator::Root
+                if lhs.im == 0.0 && rhs.im == 0.0 && rhs.re < 0.0 && is_odd(lhs.re) =>
+            {
+                Complex64::new(self.eval(lhs.re, rhs.re), 0.0)
+            }
+            LeftAssociativeOperator::Root => complex_pow(rhs, lhs.inv()),
+        }
+    }
+}
+
+// checks if a number is an odd integer
+fn is_odd(x: f64) -> bool {
+    x % 2.0 == 1.0 || x % 2.0 == -1.0
+}
+
+// keeps integer powers and powers of non-negative reals exact, which the polar form of `powc` doesn't
+#[cfg(feature = "complex")]
+fn complex_pow(base: Complex64, exp: Complex64) -> Complex64 {
+    if exp.im == 0.0 && exp.re.fract() == 0.0 && exp.re.abs() <= i32::MAX as f64 {
+        base.powi(exp.re as i32)
+    } else if exp.im == 0.0 && base.im == 0.0 && base.re >= 0.0 {
+        Complex64::new(base.re.powf(exp.re), 0.0)
+    } else {
+        base.powc(exp)
+    }
 }
@@ -0,0 +1,197 @@
+use std::fmt::{Debug, Formatter};
+
+use cranelift_codegen::{
+    ir::{types, AbiParam, InstBuilder, MemFlags, Value},
+    settings::{self, Configurable},
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Linkage, Module};
+
+use crate::{
+    compile::{Compiled, Node},
+    item::{FunctionalOperator, LeftAssociativeOperator},
+};
+
+/// An equation compiled to machine code, see [`Equation::jit`](crate::Equation::jit).
+/// Arithmetic, `abs`, `sqrt`, `floor`, `ceil` and `trunc` are instructions, other functions and powers call into the interpreter's, and where the host isn't supported by the code generator it interprets the [`Compiled`] equation instead.
+pub struct CompiledFn {
+    // the machine code points at the operators of its steps, so they are never changed
+    compiled: Compiled,
+    native: Option<Native>,
+}
+
+// the machine code and the module that owns its memory
+struct Native {
+    module: Option<JITModule>,
+    function: extern "C" fn(*const f64) -> f64,
+}
+
+impl Drop for Native {
+    fn drop(&mut self) {
+        if let Some(module) = self.module.take() {
+            // SAFETY: the function is only called through `CompiledFn`, which is being dropped
+            unsafe { module.free_memory() };
+        }
+    }
+}
+
+impl CompiledFn {
+    /// Evaluates the equation with the values of its variables, in the order of [`variables`](Self::variables).
+    /// Panics when there aren't as many values as variables.
+    pub fn call(&self, values: &[f64]) -> f64 {
+        assert_eq!(
+            values.len(),
+            self.compiled.variables().len(),
+            "expected the values of {:?}",
+            self.compiled.variables()
+        );
+        match &self.native {
+            Some(native) => (native.function)(values.as_ptr()),
+            // evaluating in f64 only fails with the wrong number of values
+            None => self.compiled.evaluate(values).unwrap_or(f64::NAN),
+        }
+    }
+    /// Gives the variables in the order their values are given to [`call`](Self::call).
+    pub fn variables(&self) -> &[String] {
+        self.compiled.variables()
+    }
+    /// Checks if the equation was compiled to machine code, rather than falling back to the interpreter.
+    pub fn is_native(&self) -> bool {
+        self.native.is_some()
+    }
+    /// Gives the graph of steps that the machine code was generated from.
+    pub fn compiled(&self) -> &Compiled {
+        &self.compiled
+    }
+}
+
+impl Debug for CompiledFn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompiledFn")
+            .field("compiled", &self.compiled)
+            .field("native", &self.is_native())
+            .finish()
+    }
+}
+
+/// Generates machine code for a compiled equation, keeping it to interpret when the host isn't supported.
+pub(crate) fn jit(compiled: Compiled) -> CompiledFn {
+    let native = generate(&compiled);
+    CompiledFn { compiled, native }
+}
+
+// the functions the code calls for what there's no instruction for, which evaluate like the interpreter
+extern "C" fn function(func: &FunctionalOperator, x: f64) -> f64 {
+    func.evaluate(x)
+}
+
+extern "C" fn operator(op: &LeftAssociativeOperator, lhs: f64, rhs: f64) -> f64 {
+    op.eval(lhs, rhs)
+}
+
+// lowers every step to an instruction or a call, where the operators of calls point into the steps of the compiled equation
+fn generate(compiled: &Compiled) -> Option<Native> {
+    let mut flags = settings::builder();
+    flags.set("opt_level", "speed").ok()?;
+    flags.set("use_colocated_libcalls", "false").ok()?;
+    flags.set("is_pic", "false").ok()?;
+    let isa = cranelift_native::builder()
+        .ok()?
+        .finish(settings::Flags::new(flags))
+        .ok()?;
+    let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+    builder.symbol("function", function as *const u8);
+    builder.symbol("operator", operator as *const u8);
+    let mut module = JITModule::new(builder);
+    let pointer = module.target_config().pointer_type();
+
+    let mut unary = module.make_signature();
+    unary.params.push(AbiParam::new(pointer));
+    unary.params.push(AbiParam::new(types::F64));
+    unary.returns.push(AbiParam::new(types::F64));
+    let mut binary = unary.clone();
+    binary.params.push(AbiParam::new(types::F64));
+    let function = module
+        .declare_function("function", Linkage::Import, &unary)
+        .ok()?;
+    let operator = module
+        .declare_function("operator", Linkage::Import, &binary)
+        .ok()?;
+
+    let mut context = module.make_context();
+    context.func.signature.params.push(AbiParam::new(pointer));
+    context
+        .func
+        .signature
+        .returns
+        .push(AbiParam::new(types::F64));
+    let equation = module
+        .declare_function("equation", Linkage::Export, &context.func.signature)
+        .ok()?;
+    let mut function_context = FunctionBuilderContext::new();
+    let mut builder = FunctionBuilder::new(&mut context.func, &mut function_context);
+    let function = module.declare_func_in_func(function, builder.func);
+    let operator = module.declare_func_in_func(operator, builder.func);
+    let block = builder.create_block();
+    builder.append_block_params_for_function_params(block);
+    builder.switch_to_block(block);
+    builder.seal_block(block);
+    let values = builder.block_params(block)[0];
+
+    let mut steps: Vec<Value> = Vec::with_capacity(compiled.nodes().len());
+    for node in compiled.nodes() {
+        let step = match node {
+            Node::Value(value) => builder.ins().f64const(*value),
+            Node::Constant(constant) => builder.ins().f64const(constant.value()),
+            Node::Variable(i) => builder.ins().load(
+                types::F64,
+                MemFlags::trusted(),
+                values,
+                i32::try_from(i * 8).ok()?,
+            ),
+            Node::Negate(x) => builder.ins().fneg(steps[*x]),
+            Node::Function(func, x) => match func {
+                FunctionalOperator::Abs => builder.ins().fabs(steps[*x]),
+                FunctionalOperator::Sqrt => builder.ins().sqrt(steps[*x]),
+                FunctionalOperator::Floor => builder.ins().floor(steps[*x]),
+                FunctionalOperator::Ceil => builder.ins().ceil(steps[*x]),
+                FunctionalOperator::Trunc => builder.ins().trunc(steps[*x]),
+                _ => {
+                    let func = builder.ins().iconst(pointer, func as *const _ as i64);
+                    let call = builder.ins().call(function, &[func, steps[*x]]);
+                    builder.inst_results(call)[0]
+                }
+            },
+            Node::Operator(op, lhs, rhs) => {
+                let (lhs, rhs) = (steps[*lhs], steps[*rhs]);
+                match op {
+                    LeftAssociativeOperator::Add => builder.ins().fadd(lhs, rhs),
+                    LeftAssociativeOperator::Subtract => builder.ins().fsub(lhs, rhs),
+                    LeftAssociativeOperator::Multiply => builder.ins().fmul(lhs, rhs),
+                    LeftAssociativeOperator::Divide => builder.ins().fdiv(lhs, rhs),
+                    LeftAssociativeOperator::Power | LeftAssociativeOperator::Root => {
+                        let op = builder.ins().iconst(pointer, op as *const _ as i64);
+                        let call = builder.ins().call(operator, &[op, lhs, rhs]);
+                        builder.inst_results(call)[0]
+                    }
+                }
+            }
+        };
+        steps.push(step);
+    }
+    builder.ins().return_(&[steps[compiled.root()]]);
+    builder.finalize();
+
+    module.define_function(equation, &mut context).ok()?;
+    module.clear_context(&mut context);
+    module.finalize_definitions().ok()?;
+    let code = module.get_finalized_function(equation);
+    // SAFETY: the code was generated with this signature, and reads as many values as there are variables
+    let function =
+        unsafe { std::mem::transmute::<*const u8, extern "C" fn(*const f64) -> f64>(code) };
+    Some(Native {
+        module: Some(module),
+        function,
+    })
+}
@@ -5,15 +5,74 @@
 #![warn(missing_docs)]
 #![deny(missing_debug_implementations)]
 
+/// The calculus module contains the symbolic methods of calculus, which work on the tree of an equation.
+mod calculus;
+/// The compile module contains equations compiled into a graph of steps, where repeated subtrees are worked out once.
+pub mod compile;
+/// The constants module contains the table of named constants that the parser recognises.
+pub mod constants;
 /// The equation module contains the equation struct and all the items that can be used in an equation.
 pub mod equation;
+/// The equivalence module contains the result of comparing two equations.
+pub mod equivalence;
 /// The error module contains all associated things to errors that can be yielded in any stage of the equation solver.
 pub mod error;
+/// The expr module contains the tree of an equation that the symbolic methods work on.
+mod expr;
+/// The fit module contains least squares fits of the parameters of an equation to data.
+pub mod fit;
+/// The integration module contains the options and results of integrating an equation.
+pub mod integration;
+/// The interval module contains the interval type used to evaluate equations with guaranteed bounds.
+pub mod interval;
 /// The item module contains all the items that can appear in an equation.
 pub mod item;
+/// The jit module contains equations compiled to machine code, for evaluating them as fast as possible.
+#[cfg(feature = "jit")]
+pub mod jit;
+/// The limit module contains the sides that a limit of an equation can be approached from.
+pub mod limit;
+/// The model module contains sets of named equations that depend on each other, which are evaluated like a spreadsheet.
+pub mod model;
+/// The numeric module contains the number types that an equation can be evaluated in.
+pub mod numeric;
+/// The optimize module contains the methods that find the minimum of an equation and the optimum they find.
+pub mod optimize;
 /// The parse module contains the parser which is used to take strings and turn them into equations.
 pub mod parse;
+/// The plot module contains renderers that draw the samples of an equation as SVG or text.
+#[cfg(feature = "plot")]
+pub mod plot;
+/// The polynomial module contains polynomials in one variable and the methods that find their roots.
+pub mod polynomial;
+/// The precision module contains an arbitrary-precision number type for evaluating equations to any number of digits.
+#[cfg(feature = "precision")]
+pub mod precision;
+/// The program module contains programs of several statements, which can assign values to names.
+pub mod program;
+/// The rational module contains an exact fraction type for evaluating equations without rounding.
+pub mod rational;
+/// The sample module contains the adaptive sampling of an equation over a range, for plotting it.
+mod sample;
+/// The special module contains the gamma and error functions, which aren't in the standard library.
+pub mod special;
+/// The system module contains systems of simultaneous equations and their solutions.
+pub mod system;
+/// The units module contains units of measurement and the quantity type used to check the dimensions of an equation.
+pub mod units;
 
-
-pub use equation::Equation;
+pub use constants::Constants;
+pub use equation::{AngleMode, Equation};
 pub use error::{EquationError, EquationErrorType};
+pub use interval::Interval;
+pub use model::Model;
+#[cfg(feature = "complex")]
+pub use num_complex::Complex64;
+pub use numeric::Numeric;
+pub use polynomial::Polynomial;
+#[cfg(feature = "precision")]
+pub use precision::BigFloat;
+pub use program::Program;
+pub use rational::Rational;
+pub use system::System;
+pub use units::Quantity;
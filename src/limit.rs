@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::calculus;
+use crate::error::{EquationError, EquationErrorType};
+use crate::expr::Expr;
+use crate::item::LeftAssociativeOperator;
+use crate::numeric::unsupported;
+
+// how many times l'hopital's rule is applied before the limit is left to the numbers
+const MAX_DEPTH: u32 = 8;
+// how many times the distance to the point is halved
+const STEPS: i32 = 32;
+// how many columns of the richardson table are used
+const COLUMNS: usize = 6;
+// how close two limits have to be to count as the same
+const TOLERANCE: f64 = 1e-6;
+
+/// The side that [`Equation::limit`](crate::Equation::limit) approaches its point from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// Both sides, which must approach the same value.
+    #[default]
+    Both,
+    /// The values below the point.
+    Below,
+    /// The values above the point.
+    Above,
+}
+
+/// Finds the limit of `expr` as `x` approaches `point` (which can be infinite) from `direction`.
+pub(crate) fn limit(
+    expr: &Expr,
+    x: &str,
+    point: f64,
+    direction: Direction,
+) -> Result<f64, EquationError> {
+    if point.is_nan() {
+        return Err(unsupported("The limit at NaN".to_string()));
+    }
+    // infinity can only be approached from one side
+    if point.is_infinite() {
+        return match (direction, point > 0.0) {
+            (Direction::Both, _) | (Direction::Below, true) | (Direction::Above, false) => {
+                side(expr, x, point, point < 0.0, 0)
+            }
+            _ => Err(unsupported(format!(
+                "{} can't be approached from {:?}",
+                point, direction
+            ))),
+        };
+    }
+    match direction {
+        Direction::Below => side(expr, x, point, false, 0),
+        Direction::Above => side(expr, x, point, true, 0),
+        Direction::Both => {
+            let below = side(expr, x, point, false, 0);
+            let above = side(expr, x, point, true, 0);
+            match (below, above) {
+                (Ok(below), Ok(above)) if close(below, above) => Ok(if below == above {
+                    below
+                } else {
+                    below / 2.0 + above / 2.0
+                }),
+                (Ok(below), Ok(above)) => Err(no_limit(format!(
+                    "The limit at {} is {} from below and {} from above",
+                    point, below, above
+                ))),
+                (Err(err), _) | (_, Err(err)) => Err(err),
+            }
+        }
+    }
+}
+
+// the limit from one side, which is above the point when `above` is set
+fn side(expr: &Expr, x: &str, point: f64, above: bool, depth: u32) -> Result<f64, EquationError> {
+    let value = evaluate(expr, x, point)?;
+    let values = samples(expr, x, point, above)?;
+    // the value at the point is the limit wherever the equation is continuous
+    let continuous = if value.is_infinite() {
+        extrapolate(&values) == Some(value)
+    } else {
+        tends_to(&values, value)
+    };
+    if continuous {
+        return Ok(value);
+    }
+    if depth < MAX_DEPTH {
+        for (numerator, denominator) in indeterminate(expr, x, point)? {
+            let (Some(numerator), Some(denominator)) = (
+                calculus::derivative(&numerator, x),
+                calculus::derivative(&denominator, x),
+            ) else {
+                continue;
+            };
+            let quotient = (numerator / denominator).simplify();
+            if let Ok(value) = side(&quotient, x, point, above, depth + 1) {
+                return Ok(value);
+            }
+        }
+    }
+    extrapolate(&values).ok_or_else(|| {
+        no_limit(format!(
+            "{} doesn't approach a value as {} approaches {} from {}",
+            expr,
+            x,
+            point,
+            if above { "above" } else { "below" }
+        ))
+    })
+}
+
+// the ways of writing the expression as a quotient that is 0/0 or ∞/∞ at the point, for l'hopital's rule
+fn indeterminate(expr: &Expr, x: &str, point: f64) -> Result<Vec<(Expr, Expr)>, EquationError> {
+    let zero = |value: f64| value == 0.0;
+    let (numerator, denominator) = fraction(expr);
+    let (top, bottom) = (
+        evaluate(&numerator, x, point)?,
+        evaluate(&denominator, x, point)?,
+    );
+    if (zero(top) && zero(bottom)) || (top.is_infinite() && bottom.is_infinite()) {
+        return Ok(vec![(numerator, denominator)]);
+    }
+    // 0·∞ is tried as ∞/(1/0) first, since the derivative of a reciprocal of something infinite (i.e. `ln(x)`) tends to grow
+    let Expr::Operator(LeftAssociativeOperator::Multiply, lhs, rhs) = expr else {
+        return Ok(Vec::new());
+    };
+    let (left, right) = (evaluate(lhs, x, point)?, evaluate(rhs, x, point)?);
+    let (small, large) = if zero(left) && right.is_infinite() {
+        (lhs, rhs)
+    } else if left.is_infinite() && zero(right) {
+        (rhs, lhs)
+    } else {
+        return Ok(Vec::new());
+    };
+    let reciprocal = |x: &Expr| (Expr::Number(1.0) / x.clone()).simplify();
+    Ok(vec![
+        ((**large).clone(), reciprocal(small)),
+        ((**small).clone(), reciprocal(large)),
+    ])
+}
+
+// splits the products and quotients at the top of the expression into a numerator and a denominator
+fn fraction(expr: &Expr) -> (Expr, Expr) {
+    match expr {
+        Expr::Negate(inner) => {
+            let (numerator, denominator) = fraction(inner);
+            (-numerator, denominator)
+        }
+        Expr::Operator(LeftAssociativeOperator::Multiply, lhs, rhs) => {
+            let (left, right) = (fraction(lhs), fraction(rhs));
+            (left.0 * right.0, left.1 * right.1)
+        }
+        Expr::Operator(LeftAssociativeOperator::Divide, lhs, rhs) => {
+            let (left, right) = (fraction(lhs), fraction(rhs));
+            (left.0 * right.1, left.1 * right.0)
+        }
+        _ => (expr.clone(), Expr::Number(1.0)),
+    }
+}
+
+// the values ever closer to the point, at distances that halve each time
+fn samples(expr: &Expr, x: &str, point: f64, above: bool) -> Result<Vec<f64>, EquationError> {
+    // the distance `t` to the point, or the reciprocal of the variable at infinity
+    let position = |t: f64| {
+        if point.is_infinite() {
+            point.signum() / t
+        } else if above {
+            point + t
+        } else {
+            point - t
+        }
+    };
+    let start = if point.is_infinite() {
+        0.25
+    } else {
+        0.125 * point.abs().max(1.0)
+    };
+    let mut values = Vec::with_capacity(STEPS as usize);
+    for k in 0..STEPS {
+        let at = position(start * 2f64.powi(-k));
+        if at == point {
+            break;
+        }
+        values.push(evaluate(expr, x, at)?);
+    }
+    Ok(values)
+}
+
+// checks if the last values close in on `value`, which needn't be quickly (i.e. `sqrt(x)` at 0)
+fn tends_to(values: &[f64], value: f64) -> bool {
+    let distances: Vec<f64> = values[values.len().saturating_sub(8)..]
+        .iter()
+        .map(|v| (v - value).abs())
+        .collect();
+    let (Some(first), Some(last)) = (distances.first(), distances.last()) else {
+        return false;
+    };
+    distances.iter().all(|d| d.is_finite())
+        && distances.windows(2).all(|d| d[1] <= d[0])
+        && (*last == 0.0 || *last <= first / 2.0)
+        && *last <= TOLERANCE.sqrt() * value.abs().max(1.0)
+}
+
+// estimates the limit of the values, where `None` means they don't settle
+fn extrapolate(values: &[f64]) -> Option<f64> {
+    let &last = values.last()?;
+    // values that overflow on the way stay infinite
+    if last.is_infinite() && values.iter().rev().take(4).all(|v| *v == last) {
+        return Some(last);
+    }
+    // differences that don't shrink, all in the same direction, grow without bound (i.e. `1/x` or `ln(x)`)
+    let tail = &values[values.len().saturating_sub(12)..];
+    let differences: Vec<f64> = tail.windows(2).map(|w| w[1] - w[0]).collect();
+    let diverges = tail.iter().all(|v| v.is_finite())
+        && differences.iter().all(|d| *d != 0.0)
+        && differences
+            .windows(2)
+            .all(|d| d[0].signum() == d[1].signum() && d[1].abs() >= 0.9 * d[0].abs());
+    if diverges {
+        return Some(f64::INFINITY.copysign(differences[0]));
+    }
+    // richardson extrapolation, assuming the values are a series in the distance, keeping the estimate that changed the least
+    let mut best: Option<(f64, f64)> = None;
+    let mut previous: Vec<f64> = Vec::new();
+    for &value in values {
+        let mut row = vec![value];
+        for j in 1..COLUMNS.min(previous.len() + 1) {
+            let factor = 2f64.powi(j as i32) - 1.0;
+            row.push(row[j - 1] + (row[j - 1] - previous[j - 1]) / factor);
+        }
+        for j in 1..row.len() {
+            let error = (row[j] - row[j - 1]).abs();
+            if row[j].is_finite() && error.is_finite() && best.is_none_or(|(_, best)| error < best)
+            {
+                best = Some((row[j], error));
+            }
+        }
+        previous = row;
+    }
+    best.and_then(|(estimate, error)| {
+        (error <= TOLERANCE * estimate.abs().max(1.0)).then_some(estimate)
+    })
+}
+
+fn evaluate(expr: &Expr, x: &str, at: f64) -> Result<f64, EquationError> {
+    expr.evaluate_as(&HashMap::from([(x, at)]))
+}
+
+fn close(a: f64, b: f64) -> bool {
+    a == b || (a.is_finite() && b.is_finite() && (a - b).abs() <= TOLERANCE * b.abs().max(1.0))
+}
+
+fn no_limit(message: String) -> EquationError {
+    EquationError::new(message, EquationErrorType::NoLimit)
+}
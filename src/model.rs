@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    equation::Equation,
+    error::{EquationError, EquationErrorType},
+};
+
+/// A set of named equations and inputs, where each equation can use the values of the others by name, like the cells of a spreadsheet.
+/// Values are cached, and changing an input or an equation only recomputes the equations that depend on it.
+#[derive(Debug, Default)]
+pub struct Model {
+    equations: HashMap<String, Equation>,
+    inputs: HashMap<String, f64>,
+    // the values of the equations that are up to date
+    values: HashMap<String, f64>,
+}
+
+impl Model {
+    /// Creates an empty model.
+    pub fn new() -> Model {
+        Model::default()
+    }
+    /// Sets the equation for a name, replacing any equation or input it had.
+    pub fn set_equation(&mut self, name: &str, equation: Equation) -> &mut Self {
+        self.inputs.remove(name);
+        self.equations.insert(name.to_string(), equation);
+        self.invalidate(name);
+        self
+    }
+    /// Sets an input value for a name, replacing any equation or input it had.
+    pub fn set_input(&mut self, name: &str, value: f64) -> &mut Self {
+        self.equations.remove(name);
+        self.inputs.insert(name.to_string(), value);
+        self.invalidate(name);
+        self
+    }
+    /// Gives the names that the equation for `name` uses directly.
+    pub fn dependencies(&self, name: &str) -> HashSet<String> {
+        self.equations
+            .get(name)
+            .map(Equation::list_vars)
+            .unwrap_or_default()
+    }
+    /// Checks if the equation for `name` has to be recomputed the next time it is evaluated.
+    pub fn is_dirty(&self, name: &str) -> bool {
+        self.equations.contains_key(name) && !self.values.contains_key(name)
+    }
+    /// Gives the names of every equation, ordered so that each comes after the equations it depends on.
+    pub fn order(&self) -> Result<Vec<String>, EquationError> {
+        let mut names: Vec<&String> = self.equations.keys().collect();
+        names.sort();
+        let mut order = Vec::with_capacity(names.len());
+        let mut visited = HashSet::new();
+        for name in names {
+            self.visit(name, &mut Vec::new(), &mut visited, &mut order)?;
+        }
+        Ok(order)
+    }
+    /// Evaluates the value of a name, computing whatever it depends on that is out of date.
+    pub fn evaluate(&mut self, name: &str) -> Result<f64, EquationError> {
+        self.compute(name, &mut Vec::new())
+    }
+    /// Evaluates every equation in the model.
+    pub fn evaluate_all(&mut self) -> Result<HashMap<String, f64>, EquationError> {
+        let mut values = HashMap::with_capacity(self.equations.len());
+        for name in self.order()? {
+            let value = self.evaluate(&name)?;
+            values.insert(name, value);
+        }
+        Ok(values)
+    }
+    // depth first, the path is the chain of equations being visited to find loops
+    fn visit(
+        &self,
+        name: &str,
+        path: &mut Vec<String>,
+        visited: &mut HashSet<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), EquationError> {
+        if visited.contains(name) || !self.equations.contains_key(name) {
+            return Ok(());
+        }
+        check_path(path, name)?;
+        path.push(name.to_string());
+        let mut dependencies: Vec<String> = self.dependencies(name).into_iter().collect();
+        dependencies.sort();
+        for dependency in dependencies {
+            self.visit(&dependency, path, visited, order)?;
+        }
+        path.pop();
+        visited.insert(name.to_string());
+        order.push(name.to_string());
+        Ok(())
+    }
+    fn compute(&mut self, name: &str, path: &mut Vec<String>) -> Result<f64, EquationError> {
+        if let Some(value) = self.values.get(name).or_else(|| self.inputs.get(name)) {
+            return Ok(*value);
+        }
+        if !self.equations.contains_key(name) {
+            return Err(EquationError::new(
+                format!("Variable {} not set", name),
+                EquationErrorType::UnsetVariable,
+            ));
+        }
+        check_path(path, name)?;
+        path.push(name.to_string());
+        let mut bindings = HashMap::new();
+        for dependency in self.dependencies(name) {
+            let value = self.compute(&dependency, path)?;
+            bindings.insert(dependency, value);
+        }
+        path.pop();
+        let bindings = bindings
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect();
+        let value = self.equations[name].evaluate_with_bindings(&bindings)?;
+        self.values.insert(name.to_string(), value);
+        Ok(value)
+    }
+    // drops the cached values of a name and everything that depends on it
+    fn invalidate(&mut self, name: &str) {
+        self.values.remove(name);
+        // a cached value only depends on cached values, so the search can stop at values that aren't cached
+        let dependents: Vec<String> = self
+            .equations
+            .iter()
+            .filter(|(dependent, equation)| {
+                self.values.contains_key(*dependent) && equation.list_vars().contains(name)
+            })
+            .map(|(dependent, _)| dependent.clone())
+            .collect();
+        for dependent in dependents {
+            self.invalidate(&dependent);
+        }
+    }
+}
+
+// errors if `name` is already being evaluated further up the chain
+fn check_path(path: &[String], name: &str) -> Result<(), EquationError> {
+    match path.iter().position(|visiting| visiting == name) {
+        Some(start) => Err(EquationError::new(
+            format!("{} -> {}", path[start..].join(" -> "), name),
+            EquationErrorType::CircularDependency,
+        )),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,340 @@
+#[cfg(feature = "complex")]
+use num_complex::Complex64;
+
+use std::collections::HashMap;
+
+use crate::{
+    equation::AngleMode,
+    error::{EquationError, EquationErrorType},
+    item::{Constant, FunctionalOperator, LeftAssociativeOperator},
+};
+
+/// A number type that an equation can be evaluated in, see [`Equation::evaluate_as`](crate::Equation::evaluate_as).
+/// Implement this to evaluate equations in your own number type (i.e. big decimals).
+pub trait Numeric: Clone + std::fmt::Debug {
+    /// Converts a value stored in the equation into this number type.
+    fn from_f64(value: f64) -> Result<Self, EquationError>;
+    /// Gives the value of a mathematical constant.
+    fn constant(constant: &Constant) -> Result<Self, EquationError> {
+        Self::from_f64(constant.value())
+    }
+    /// Gives the value of a variable that was never set, if this number type has a meaning for it.
+    fn variable(_name: &str) -> Option<Self> {
+        None
+    }
+    /// Negates the value (unary minus).
+    fn negate(self) -> Result<Self, EquationError>;
+    /// Applies a functional operator to the value.
+    fn function(func: &FunctionalOperator, x: Self) -> Result<Self, EquationError>;
+    /// Applies a left-associative operator to the two values.
+    fn operator(op: &LeftAssociativeOperator, lhs: Self, rhs: Self) -> Result<Self, EquationError>;
+}
+
+/// Evaluates a group, the number type along with any settings it needs (i.e. the precision).
+pub(crate) trait Evaluator {
+    /// The number type that the group is evaluated in.
+    type Value: Clone + std::fmt::Debug;
+    /// Converts a value stored in the equation.
+    fn value(&self, value: f64) -> Result<Self::Value, EquationError>;
+    /// Gives the value of a mathematical constant.
+    fn constant(&self, constant: &Constant) -> Result<Self::Value, EquationError>;
+    /// Gives the value of a variable that was never set, if there is one.
+    fn variable(&self, name: &str) -> Option<Self::Value>;
+    /// Negates the value (unary minus).
+    fn negate(&self, x: Self::Value) -> Result<Self::Value, EquationError>;
+    /// Applies a functional operator to the value.
+    fn function(
+        &self,
+        func: &FunctionalOperator,
+        x: Self::Value,
+    ) -> Result<Self::Value, EquationError>;
+    /// Applies a left-associative operator to the two values.
+    fn operator(
+        &self,
+        op: &LeftAssociativeOperator,
+        lhs: Self::Value,
+        rhs: Self::Value,
+    ) -> Result<Self::Value, EquationError>;
+}
+
+/// Evaluates in a [`Numeric`] type, optionally with values for the variables that aren't set.
+#[derive(Debug)]
+pub(crate) struct NumericEvaluator<'a, N> {
+    bindings: Option<&'a HashMap<&'a str, N>>,
+}
+
+impl<'a, N> NumericEvaluator<'a, N> {
+    pub(crate) fn new() -> Self {
+        NumericEvaluator { bindings: None }
+    }
+    pub(crate) fn with_bindings(bindings: &'a HashMap<&'a str, N>) -> Self {
+        NumericEvaluator {
+            bindings: Some(bindings),
+        }
+    }
+}
+
+impl<N: Numeric> Evaluator for NumericEvaluator<'_, N> {
+    type Value = N;
+    fn value(&self, value: f64) -> Result<N, EquationError> {
+        N::from_f64(value)
+    }
+    fn constant(&self, constant: &Constant) -> Result<N, EquationError> {
+        N::constant(constant)
+    }
+    fn variable(&self, name: &str) -> Option<N> {
+        self.bindings
+            .and_then(|bindings| bindings.get(name).cloned())
+            .or_else(|| N::variable(name))
+    }
+    fn negate(&self, x: N) -> Result<N, EquationError> {
+        x.negate()
+    }
+    fn function(&self, func: &FunctionalOperator, x: N) -> Result<N, EquationError> {
+        N::function(func, x)
+    }
+    fn operator(&self, op: &LeftAssociativeOperator, lhs: N, rhs: N) -> Result<N, EquationError> {
+        N::operator(op, lhs, rhs)
+    }
+}
+
+/// Converts the angles of the trigonometric functions between radians and another unit, evaluating everything else with the inner evaluator.
+#[derive(Debug)]
+pub(crate) struct AngleEvaluator<'a, E> {
+    inner: &'a E,
+    mode: AngleMode,
+}
+
+impl<'a, E: Evaluator> AngleEvaluator<'a, E> {
+    pub(crate) fn new(inner: &'a E, mode: AngleMode) -> Self {
+        AngleEvaluator { inner, mode }
+    }
+    // one unit of the angle mode in radians
+    fn unit(&self) -> Result<E::Value, EquationError> {
+        match self.mode {
+            AngleMode::Radians => self.inner.value(1.0),
+            AngleMode::Degrees => self.inner.constant(&Constant::Deg),
+            AngleMode::Gradians => self.inner.operator(
+                &LeftAssociativeOperator::Divide,
+                self.inner.constant(&Constant::Pi)?,
+                self.inner.value(200.0)?,
+            ),
+        }
+    }
+}
+
+impl<E: Evaluator> Evaluator for AngleEvaluator<'_, E> {
+    type Value = E::Value;
+    fn value(&self, value: f64) -> Result<E::Value, EquationError> {
+        self.inner.value(value)
+    }
+    fn constant(&self, constant: &Constant) -> Result<E::Value, EquationError> {
+        self.inner.constant(constant)
+    }
+    fn variable(&self, name: &str) -> Option<E::Value> {
+        self.inner.variable(name)
+    }
+    fn negate(&self, x: E::Value) -> Result<E::Value, EquationError> {
+        self.inner.negate(x)
+    }
+    fn function(&self, func: &FunctionalOperator, x: E::Value) -> Result<E::Value, EquationError> {
+        if self.mode == AngleMode::Radians {
+            self.inner.function(func, x)
+        } else if func.takes_angle() {
+            let x = self
+                .inner
+                .operator(&LeftAssociativeOperator::Multiply, x, self.unit()?)?;
+            self.inner.function(func, x)
+        } else if func.gives_angle() {
+            let angle = self.inner.function(func, x)?;
+            self.inner
+                .operator(&LeftAssociativeOperator::Divide, angle, self.unit()?)
+        } else {
+            self.inner.function(func, x)
+        }
+    }
+    fn operator(
+        &self,
+        op: &LeftAssociativeOperator,
+        lhs: E::Value,
+        rhs: E::Value,
+    ) -> Result<E::Value, EquationError> {
+        self.inner.operator(op, lhs, rhs)
+    }
+}
+
+impl Numeric for f64 {
+    fn from_f64(value: f64) -> Result<Self, EquationError> {
+        Ok(value)
+    }
+    fn negate(self) -> Result<Self, EquationError> {
+        Ok(-self)
+    }
+    fn function(func: &FunctionalOperator, x: Self) -> Result<Self, EquationError> {
+        Ok(func.evaluate(x))
+    }
+    fn operator(op: &LeftAssociativeOperator, lhs: Self, rhs: Self) -> Result<Self, EquationError> {
+        Ok(op.eval(lhs, rhs))
+    }
+}
+
+// arithmetic is done in f32, functions and powers are computed in f64 and rounded back
+impl Numeric for f32 {
+    fn from_f64(value: f64) -> Result<Self, EquationError> {
+        Ok(value as f32)
+    }
+    fn negate(self) -> Result<Self, EquationError> {
+        Ok(-self)
+    }
+    fn function(func: &FunctionalOperator, x: Self) -> Result<Self, EquationError> {
+        Ok(func.evaluate(x as f64) as f32)
+    }
+    fn operator(op: &LeftAssociativeOperator, lhs: Self, rhs: Self) -> Result<Self, EquationError> {
+        Ok(match op {
+            LeftAssociativeOperator::Add => lhs + rhs,
+            LeftAssociativeOperator::Subtract => lhs - rhs,
+            LeftAssociativeOperator::Multiply => lhs * rhs,
+            LeftAssociativeOperator::Divide => lhs / rhs,
+            _ => op.eval(lhs as f64, rhs as f64) as f32,
+        })
+    }
+}
+
+// checked integer arithmetic, division rounds towards zero like rust's `/`
+impl Numeric for i64 {
+    fn from_f64(value: f64) -> Result<Self, EquationError> {
+        if value.fract() != 0.0 || !value.is_finite() {
+            return Err(unsupported(format!("{} is not an integer", value)));
+        }
+        if value < i64::MIN as f64 || value >= i64::MAX as f64 {
+            return Err(overflow());
+        }
+        Ok(value as i64)
+    }
+    fn negate(self) -> Result<Self, EquationError> {
+        self.checked_neg().ok_or_else(overflow)
+    }
+    fn function(func: &FunctionalOperator, x: Self) -> Result<Self, EquationError> {
+        match func {
+            FunctionalOperator::Sqrt => i64::operator(&LeftAssociativeOperator::Root, 2, x),
+            FunctionalOperator::Cbrt => i64::operator(&LeftAssociativeOperator::Root, 3, x),
+            FunctionalOperator::Abs => x.checked_abs().ok_or_else(overflow),
+            FunctionalOperator::Sign => Ok(x.signum()),
+            FunctionalOperator::Floor
+            | FunctionalOperator::Ceil
+            | FunctionalOperator::Round
+            | FunctionalOperator::Trunc => Ok(x),
+            FunctionalOperator::Frac => Ok(0),
+            FunctionalOperator::Factorial => factorial(x),
+            _ => Err(unsupported(format!(
+                "{:?} is not defined for integers",
+                func
+            ))),
+        }
+    }
+    fn operator(op: &LeftAssociativeOperator, lhs: Self, rhs: Self) -> Result<Self, EquationError> {
+        match op {
+            LeftAssociativeOperator::Add => lhs.checked_add(rhs).ok_or_else(overflow),
+            LeftAssociativeOperator::Subtract => lhs.checked_sub(rhs).ok_or_else(overflow),
+            LeftAssociativeOperator::Multiply => lhs.checked_mul(rhs).ok_or_else(overflow),
+            LeftAssociativeOperator::Divide if rhs == 0 => Err(division_by_zero()),
+            LeftAssociativeOperator::Divide => lhs.checked_div(rhs).ok_or_else(overflow),
+            LeftAssociativeOperator::Power => {
+                let exp = u32::try_from(rhs).map_err(|_| {
+                    unsupported("Integer powers need a non-negative exponent".to_string())
+                })?;
+                lhs.checked_pow(exp).ok_or_else(overflow)
+            }
+            LeftAssociativeOperator::Root => integer_root(rhs, lhs).ok_or_else(|| {
+                unsupported(format!("The root {} of {} is not an integer", lhs, rhs))
+            }),
+        }
+    }
+}
+
+/// Gives `n!`, checking for overflow.
+pub(crate) fn factorial<I>(n: I) -> Result<I, EquationError>
+where
+    I: Copy + TryFrom<i128> + Into<i128>,
+{
+    let n: i128 = n.into();
+    if n < 0 {
+        return Err(unsupported(format!(
+            "The factorial of {} is not defined",
+            n
+        )));
+    }
+    let product = (2..=n).try_fold(1i128, |product, k| product.checked_mul(k));
+    product
+        .and_then(|product| I::try_from(product).ok())
+        .ok_or_else(overflow)
+}
+
+/// Finds the exact integer `n`th root of `x`, if there is one.
+pub(crate) fn integer_root<I>(x: I, n: I) -> Option<I>
+where
+    I: Copy + TryInto<u32> + TryFrom<i128> + Into<i128>,
+{
+    let n: u32 = n.try_into().ok().filter(|n| *n > 0)?;
+    let x: i128 = x.into();
+    let abs = x.checked_abs()?;
+    if x < 0 && n.is_multiple_of(2) {
+        return None;
+    }
+    // the float guess is only ever off by one
+    let guess = (abs as f64).powf(1.0 / n as f64).round() as i128;
+    for root in [guess - 1, guess, guess + 1] {
+        if root >= 0 && root.checked_pow(n) == Some(abs) {
+            return I::try_from(if x < 0 { -root } else { root }).ok();
+        }
+    }
+    None
+}
+
+pub(crate) fn overflow() -> EquationError {
+    EquationError::new(
+        "Arithmetic overflow".to_string(),
+        EquationErrorType::Overflow,
+    )
+}
+
+pub(crate) fn division_by_zero() -> EquationError {
+    EquationError::new(
+        "Division by zero".to_string(),
+        EquationErrorType::DivisionByZero,
+    )
+}
+
+pub(crate) fn unsupported(message: String) -> EquationError {
+    EquationError::new(message, EquationErrorType::Unsupported)
+}
+
+#[cfg(feature = "complex")]
+impl Numeric for Complex64 {
+    fn from_f64(value: f64) -> Result<Self, EquationError> {
+        Ok(Complex64::new(value, 0.0))
+    }
+    // both the mathematical `i` and the engineering `j` are the imaginary unit
+    fn variable(name: &str) -> Option<Self> {
+        match name {
+            "i" | "j" => Some(Complex64::i()),
+            _ => None,
+        }
+    }
+    // `-self` would give `-0i` for real values, putting `(-1)^(1/2)` on the wrong side of the branch cut
+    fn negate(self) -> Result<Self, EquationError> {
+        Ok(Complex64::default() - self)
+    }
+    fn function(func: &FunctionalOperator, x: Self) -> Result<Self, EquationError> {
+        if func.is_real_only() && x.im != 0.0 {
+            return Err(unsupported(format!(
+                "{:?} is only defined for real numbers",
+                func
+            )));
+        }
+        Ok(func.evaluate_complex(x))
+    }
+    fn operator(op: &LeftAssociativeOperator, lhs: Self, rhs: Self) -> Result<Self, EquationError> {
+        Ok(op.eval_complex(lhs, rhs))
+    }
+}
@@ -0,0 +1,598 @@
+use std::collections::HashMap;
+
+use crate::{
+    equation::Equation, error::EquationError, item::FunctionalOperator, numeric::unsupported,
+};
+
+// the relative distance between points that counts as the same point
+const TOLERANCE: f64 = 1e-10;
+// the most steps any method takes for each variable
+const MAX_ITERATIONS: usize = 500;
+// the largest gradient relative to the value that counts as flat
+const GRADIENT_TOLERANCE: f64 = 1e-8;
+// 1 / the golden ratio
+const INVERSE_PHI: f64 = 0.618_033_988_749_894_9;
+
+/// How [`Equation::minimize`](crate::Equation::minimize) searches for a minimum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OptimizeMethod {
+    /// Brent's method for one variable within finite bounds, otherwise BFGS when every derivative is known in closed form and the equation has no kinks or steps (i.e. `abs` or `floor`), otherwise Nelder-Mead.
+    #[default]
+    Auto,
+    /// Golden-section search, which narrows the bounds of one variable by the golden ratio each step.
+    GoldenSection,
+    /// Brent's method, which speeds up golden-section search with parabolas through the last three points.
+    Brent,
+    /// The Nelder-Mead simplex, which only needs the values of the equation.
+    NelderMead,
+    /// The BFGS quasi-newton method, with the symbolic gradient when there is one and finite differences otherwise.
+    Bfgs,
+}
+
+/// The minimum or maximum found by [`Equation::minimize`](crate::Equation::minimize) or [`Equation::maximize`](crate::Equation::maximize).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Optimum {
+    values: HashMap<String, f64>,
+    value: f64,
+    iterations: usize,
+    converged: bool,
+    method: OptimizeMethod,
+}
+
+impl Optimum {
+    /// Gives the value of every variable at the optimum.
+    pub fn values(&self) -> &HashMap<String, f64> {
+        &self.values
+    }
+    /// Gives the value of a variable at the optimum.
+    pub fn get(&self, variable: &str) -> Option<f64> {
+        self.values.get(variable).copied()
+    }
+    /// Gives the value of the equation at the optimum.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+    /// Gives the number of steps the method took.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+    /// Checks if the method settled within its tolerance. When it didn't, the values are the best that were found.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+    /// Gives the method that found the optimum, which is never [`Auto`](OptimizeMethod::Auto).
+    pub fn method(&self) -> OptimizeMethod {
+        self.method
+    }
+}
+
+// the point a method settled on, before it is named
+struct Search {
+    x: Vec<f64>,
+    value: f64,
+    iterations: usize,
+    converged: bool,
+}
+
+/// Minimizes `equation` over `variables` within `bounds`, negating it first when `maximize` is set.
+pub(crate) fn optimize(
+    equation: &Equation,
+    variables: &[&str],
+    bounds: &[(f64, f64)],
+    method: OptimizeMethod,
+    maximize: bool,
+) -> Result<Optimum, EquationError> {
+    if variables.is_empty() || variables.len() != bounds.len() {
+        return Err(unsupported(format!(
+            "{} variables with {} bounds",
+            variables.len(),
+            bounds.len()
+        )));
+    }
+    if let Some(variable) = variables
+        .iter()
+        .enumerate()
+        .find_map(|(i, variable)| variables[..i].contains(variable).then_some(variable))
+    {
+        return Err(unsupported(format!(
+            "The variable {} given twice",
+            variable
+        )));
+    }
+    if let Some((lo, hi)) = bounds
+        .iter()
+        .find(|(lo, hi)| lo.is_nan() || hi.is_nan() || lo > hi)
+    {
+        return Err(unsupported(format!("The bounds {} to {}", lo, hi)));
+    }
+    let sign = if maximize { -1.0 } else { 1.0 };
+    let f = |x: &[f64]| -> Result<f64, EquationError> {
+        let value = sign * equation.evaluate_with_bindings(&bind(variables, x))?;
+        // outside the domain of the equation is never a minimum
+        Ok(if value.is_nan() { f64::INFINITY } else { value })
+    };
+    let finite = bounds
+        .iter()
+        .all(|(lo, hi)| lo.is_finite() && hi.is_finite());
+    // the symbolic gradient, if every derivative is known
+    let gradient: Option<Vec<Equation>> =
+        if matches!(method, OptimizeMethod::Auto | OptimizeMethod::Bfgs) {
+            variables
+                .iter()
+                .map(|variable| equation.derivative(variable))
+                .collect()
+        } else {
+            None
+        };
+    // a gradient that jumps leads bfgs astray, and one that is zero on steps stops it where it starts
+    let smooth = |equation: &Equation| !equation.has_function(&is_kink);
+    let method = match method {
+        OptimizeMethod::Auto if variables.len() == 1 && finite => OptimizeMethod::Brent,
+        OptimizeMethod::Auto
+            if smooth(equation)
+                && gradient
+                    .as_ref()
+                    .is_some_and(|gradient| gradient.iter().all(smooth)) =>
+        {
+            OptimizeMethod::Bfgs
+        }
+        OptimizeMethod::Auto => OptimizeMethod::NelderMead,
+        method => method,
+    };
+    let search = match method {
+        OptimizeMethod::GoldenSection | OptimizeMethod::Brent => {
+            let [(lo, hi)] = bounds else {
+                return Err(unsupported(format!(
+                    "{:?} in {} variables",
+                    method,
+                    variables.len()
+                )));
+            };
+            if !finite {
+                return Err(unsupported(format!(
+                    "{:?} between {} and {}",
+                    method, lo, hi
+                )));
+            }
+            let g = |x: f64| f(&[x]);
+            if method == OptimizeMethod::Brent {
+                brent(g, *lo, *hi)?
+            } else {
+                golden_section(g, *lo, *hi)?
+            }
+        }
+        OptimizeMethod::NelderMead => nelder_mead(&f, bounds)?,
+        _ => match &gradient {
+            Some(gradient) => bfgs(
+                &f,
+                |x: &[f64]| {
+                    let bindings = bind(variables, x);
+                    gradient
+                        .iter()
+                        .map(|derivative| Ok(sign * derivative.evaluate_with_bindings(&bindings)?))
+                        .collect()
+                },
+                bounds,
+            )?,
+            None => bfgs(&f, |x: &[f64]| differences(&f, x, bounds), bounds)?,
+        },
+    };
+    Ok(Optimum {
+        values: variables
+            .iter()
+            .map(|variable| variable.to_string())
+            .zip(search.x)
+            .collect(),
+        value: sign * search.value,
+        iterations: search.iterations,
+        converged: search.converged,
+        method,
+    })
+}
+
+// the functions whose derivative jumps or is zero wherever it exists
+fn is_kink(func: &FunctionalOperator) -> bool {
+    matches!(
+        func,
+        FunctionalOperator::Abs
+            | FunctionalOperator::Sign
+            | FunctionalOperator::Floor
+            | FunctionalOperator::Ceil
+            | FunctionalOperator::Round
+            | FunctionalOperator::Trunc
+            | FunctionalOperator::Frac
+    )
+}
+
+fn bind<'a>(variables: &[&'a str], x: &[f64]) -> HashMap<&'a str, f64> {
+    variables.iter().copied().zip(x.iter().copied()).collect()
+}
+
+// narrows the bounds around the smaller of two points that split them by the golden ratio
+fn golden_section(
+    f: impl Fn(f64) -> Result<f64, EquationError>,
+    lo: f64,
+    hi: f64,
+) -> Result<Search, EquationError> {
+    let (mut a, mut b) = (lo, hi);
+    let mut c = b - INVERSE_PHI * (b - a);
+    let mut d = a + INVERSE_PHI * (b - a);
+    let (mut fc, mut fd) = (f(c)?, f(d)?);
+    let mut iterations = 0;
+    while b - a > tolerance(a.abs().max(b.abs())) && iterations < MAX_ITERATIONS {
+        iterations += 1;
+        if fc <= fd {
+            (b, d, fd) = (d, c, fc);
+            c = b - INVERSE_PHI * (b - a);
+            fc = f(c)?;
+        } else {
+            (a, c, fc) = (c, d, fd);
+            d = a + INVERSE_PHI * (b - a);
+            fd = f(d)?;
+        }
+    }
+    let converged = b - a <= tolerance(a.abs().max(b.abs()));
+    let (x, value) = if fc <= fd { (c, fc) } else { (d, fd) };
+    with_ends(&f, x, value, lo, hi, iterations, converged)
+}
+
+// brent's localmin, which takes a parabolic step through the best three points whenever it lands well inside the bounds
+fn brent(
+    f: impl Fn(f64) -> Result<f64, EquationError>,
+    lo: f64,
+    hi: f64,
+) -> Result<Search, EquationError> {
+    let (mut a, mut b) = (lo, hi);
+    // x is the best point so far, w the second best and v the one before w
+    let mut x = a + (1.0 - INVERSE_PHI) * (b - a);
+    let (mut w, mut v) = (x, x);
+    let mut fx = f(x)?;
+    let (mut fw, mut fv) = (fx, fx);
+    // the step before last, which a parabolic step has to beat by half
+    let (mut d, mut e): (f64, f64) = (0.0, 0.0);
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < MAX_ITERATIONS {
+        let middle = a / 2.0 + b / 2.0;
+        let tol = tolerance(x.abs()) / 2.0;
+        if (x - middle).abs() <= 2.0 * tol - (b - a) / 2.0 {
+            converged = true;
+            break;
+        }
+        iterations += 1;
+        let mut golden = true;
+        if e.abs() > tol {
+            let r = (x - w) * (fx - fv);
+            let mut q = (x - v) * (fx - fw);
+            let mut p = (x - v) * q - (x - w) * r;
+            q = 2.0 * (q - r);
+            if q > 0.0 {
+                p = -p;
+            }
+            q = q.abs();
+            if p.abs() < (0.5 * q * e).abs() && p > q * (a - x) && p < q * (b - x) {
+                e = d;
+                d = p / q;
+                let u = x + d;
+                // a point too close to the bounds is moved away from them
+                if u - a < 2.0 * tol || b - u < 2.0 * tol {
+                    d = if x < middle { tol } else { -tol };
+                }
+                golden = false;
+            }
+        }
+        if golden {
+            e = if x < middle { b - x } else { a - x };
+            d = (1.0 - INVERSE_PHI) * e;
+        }
+        let u = if d.abs() >= tol {
+            x + d
+        } else {
+            x + tol.copysign(d)
+        };
+        let fu = f(u)?;
+        if fu <= fx {
+            if u < x {
+                b = x;
+            } else {
+                a = x;
+            }
+            (v, fv, w, fw, x, fx) = (w, fw, x, fx, u, fu);
+        } else {
+            if u < x {
+                a = u;
+            } else {
+                b = u;
+            }
+            if fu <= fw || w == x {
+                (v, fv, w, fw) = (w, fw, u, fu);
+            } else if fu <= fv || v == x || v == w {
+                (v, fv) = (u, fu);
+            }
+        }
+    }
+    with_ends(&f, x, fx, lo, hi, iterations, converged)
+}
+
+// the search only gets within its tolerance of the bounds, so an end that is smaller wins (i.e. `x` from 0 to 1)
+fn with_ends(
+    f: impl Fn(f64) -> Result<f64, EquationError>,
+    x: f64,
+    value: f64,
+    lo: f64,
+    hi: f64,
+    iterations: usize,
+    converged: bool,
+) -> Result<Search, EquationError> {
+    let mut best = (x, value);
+    for end in [lo, hi] {
+        let at = f(end)?;
+        if at < best.1 {
+            best = (end, at);
+        }
+    }
+    Ok(Search {
+        x: vec![best.0],
+        value: best.1,
+        iterations,
+        converged,
+    })
+}
+
+fn tolerance(x: f64) -> f64 {
+    TOLERANCE * x.max(1.0)
+}
+
+// a point strictly within the bounds to start from, which is the middle of finite bounds, since equations are often undefined on a bound (i.e. `ln(x)` from 0)
+fn start(bounds: &[(f64, f64)]) -> Vec<f64> {
+    bounds
+        .iter()
+        .map(|(lo, hi)| match (lo.is_finite(), hi.is_finite()) {
+            (true, true) => lo / 2.0 + hi / 2.0,
+            (true, false) => (lo + lo.abs().max(1.0)).max(0.0),
+            (false, true) => (hi - hi.abs().max(1.0)).min(0.0),
+            (false, false) => 0.0,
+        })
+        .collect()
+}
+
+// the value at the start, which has to be a number for the search to go anywhere
+fn at_start(
+    f: &dyn Fn(&[f64]) -> Result<f64, EquationError>,
+    x: &[f64],
+) -> Result<f64, EquationError> {
+    let value = f(x)?;
+    if !value.is_finite() {
+        return Err(unsupported(format!(
+            "A search from {:?}, where the equation is {}",
+            x, value
+        )));
+    }
+    Ok(value)
+}
+
+fn clamp(x: &mut [f64], bounds: &[(f64, f64)]) {
+    for (x, (lo, hi)) in x.iter_mut().zip(bounds) {
+        *x = x.clamp(*lo, *hi);
+    }
+}
+
+// the nelder-mead simplex, whose points are kept within the bounds
+fn nelder_mead(
+    f: &dyn Fn(&[f64]) -> Result<f64, EquationError>,
+    bounds: &[(f64, f64)],
+) -> Result<Search, EquationError> {
+    let n = bounds.len();
+    let first = start(bounds);
+    let mut simplex = vec![(first.clone(), at_start(f, &first)?)];
+    for (i, (lo, hi)) in bounds.iter().enumerate() {
+        let mut point = first.clone();
+        let step = if lo.is_finite() && hi.is_finite() {
+            (hi - lo) / 4.0
+        } else {
+            first[i].abs().max(1.0) / 2.0
+        };
+        // a step past the upper bound goes the other way
+        point[i] = if point[i] + step <= *hi {
+            point[i] + step
+        } else {
+            point[i] - step
+        };
+        clamp(&mut point, bounds);
+        let value = f(&point)?;
+        simplex.push((point, value));
+    }
+    let along = |from: &[f64], to: &[f64], t: f64| {
+        let mut point: Vec<f64> = from.iter().zip(to).map(|(a, b)| a + t * (b - a)).collect();
+        clamp(&mut point, bounds);
+        point
+    };
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < MAX_ITERATIONS * n {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (simplex[0].1, simplex[n].1);
+        let size = simplex[1..]
+            .iter()
+            .flat_map(|(point, _)| point.iter().zip(&simplex[0].0).map(|(a, b)| (a - b).abs()))
+            .fold(0.0, f64::max);
+        let scale = simplex[0]
+            .0
+            .iter()
+            .fold(0.0, |scale: f64, x| scale.max(x.abs()));
+        // the values can stop changing before the points do, once they are within rounding of each other
+        if size <= tolerance(scale)
+            || (worst - best <= 4.0 * f64::EPSILON * best.abs() && size <= tolerance(scale).sqrt())
+        {
+            converged = true;
+            break;
+        }
+        iterations += 1;
+        let centroid: Vec<f64> = (0..n)
+            .map(|i| simplex[..n].iter().map(|(point, _)| point[i]).sum::<f64>() / n as f64)
+            .collect();
+        let reflected = along(&centroid, &simplex[n].0, -1.0);
+        let fr = f(&reflected)?;
+        if fr < best {
+            let expanded = along(&centroid, &simplex[n].0, -2.0);
+            let fe = f(&expanded)?;
+            simplex[n] = if fe < fr {
+                (expanded, fe)
+            } else {
+                (reflected, fr)
+            };
+        } else if fr < simplex[n - 1].1 {
+            simplex[n] = (reflected, fr);
+        } else {
+            // contract towards the better of the reflected and the worst point
+            let (t, against) = if fr < worst { (-0.5, fr) } else { (0.5, worst) };
+            let contracted = along(&centroid, &simplex[n].0, t);
+            let fc = f(&contracted)?;
+            if fc < against {
+                simplex[n] = (contracted, fc);
+            } else {
+                let best = simplex[0].0.clone();
+                for (point, value) in simplex[1..].iter_mut() {
+                    *point = along(&best, point, 0.5);
+                    *value = f(point)?;
+                }
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let (x, value) = simplex.swap_remove(0);
+    Ok(Search {
+        x,
+        value,
+        iterations,
+        converged,
+    })
+}
+
+// central differences, which step inwards at the bounds
+fn differences(
+    f: &dyn Fn(&[f64]) -> Result<f64, EquationError>,
+    x: &[f64],
+    bounds: &[(f64, f64)],
+) -> Result<Vec<f64>, EquationError> {
+    let mut gradient = Vec::with_capacity(x.len());
+    for (j, (lo, hi)) in bounds.iter().enumerate() {
+        let h = f64::EPSILON.cbrt() * x[j].abs().max(1.0);
+        let mut above = x.to_vec();
+        above[j] = (x[j] + h).min(*hi);
+        let mut below = x.to_vec();
+        below[j] = (x[j] - h).max(*lo);
+        gradient.push((f(&above)? - f(&below)?) / (above[j] - below[j]));
+    }
+    Ok(gradient)
+}
+
+// the part of the gradient that doesn't push against a bound
+fn projected(gradient: &[f64], x: &[f64], bounds: &[(f64, f64)]) -> Vec<f64> {
+    gradient
+        .iter()
+        .zip(x)
+        .zip(bounds)
+        .map(|((g, x), (lo, hi))| {
+            if (*x <= *lo && *g > 0.0) || (*x >= *hi && *g < 0.0) {
+                0.0
+            } else {
+                *g
+            }
+        })
+        .collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+// bfgs with a backtracking line search, where steps are clamped to the bounds
+fn bfgs(
+    f: &dyn Fn(&[f64]) -> Result<f64, EquationError>,
+    grad: impl Fn(&[f64]) -> Result<Vec<f64>, EquationError>,
+    bounds: &[(f64, f64)],
+) -> Result<Search, EquationError> {
+    let n = bounds.len();
+    let identity = |n: usize| -> Vec<Vec<f64>> {
+        (0..n)
+            .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+            .collect()
+    };
+    let mut x = start(bounds);
+    let mut fx = at_start(f, &x)?;
+    let mut g = grad(&x)?;
+    // the approximation of the inverse hessian
+    let mut h = identity(n);
+    let mut iterations = 0;
+    let mut converged = false;
+    let flat = |pg: &[f64], fx: f64| {
+        pg.iter()
+            .all(|g| g.abs() <= GRADIENT_TOLERANCE * fx.abs().max(1.0))
+    };
+    while iterations < MAX_ITERATIONS * n {
+        let pg = projected(&g, &x, bounds);
+        if flat(&pg, fx) {
+            converged = true;
+            break;
+        }
+        iterations += 1;
+        let mut p: Vec<f64> = h.iter().map(|row| -dot(row, &pg)).collect();
+        // a direction that doesn't go downhill starts the approximation again
+        if dot(&p, &pg) >= 0.0 {
+            h = identity(n);
+            p = pg.iter().map(|g| -g).collect();
+        }
+        let mut t = 1.0;
+        let mut next = None;
+        for _ in 0..60 {
+            let mut candidate: Vec<f64> = x.iter().zip(&p).map(|(x, p)| x + t * p).collect();
+            clamp(&mut candidate, bounds);
+            let step: Vec<f64> = candidate.iter().zip(&x).map(|(a, b)| a - b).collect();
+            let fc = f(&candidate)?;
+            if fc <= fx + 1e-4 * dot(&pg, &step) {
+                next = Some((candidate, fc, step));
+                break;
+            }
+            t /= 2.0;
+        }
+        let Some((candidate, fc, s)) = next else {
+            // no step gets any lower, so this is as close as it gets, which is close enough if the rounding of the gradient is all that's left
+            converged = pg
+                .iter()
+                .all(|g| g.abs() <= GRADIENT_TOLERANCE.sqrt() * fx.abs().max(1.0));
+            break;
+        };
+        let small = s
+            .iter()
+            .zip(&candidate)
+            .all(|(s, x)| s.abs() <= tolerance(x.abs()));
+        let gc = grad(&candidate)?;
+        let y: Vec<f64> = gc.iter().zip(&g).map(|(a, b)| a - b).collect();
+        let sy = dot(&s, &y);
+        if sy > 0.0 {
+            // H = (I - ρsyᵀ) H (I - ρysᵀ) + ρssᵀ
+            let rho = 1.0 / sy;
+            let hy: Vec<f64> = h.iter().map(|row| dot(row, &y)).collect();
+            let yhy = dot(&y, &hy);
+            for i in 0..n {
+                for j in 0..n {
+                    h[i][j] +=
+                        (1.0 + rho * yhy) * rho * s[i] * s[j] - rho * (hy[i] * s[j] + s[i] * hy[j]);
+                }
+            }
+        }
+        (x, fx, g) = (candidate, fc, gc);
+        // steps this small go nowhere, which is only a minimum if the gradient agrees
+        if small {
+            converged = flat(&projected(&g, &x, bounds), fx);
+            break;
+        }
+    }
+    Ok(Search {
+        x,
+        value: fx,
+        iterations,
+        converged,
+    })
+}
@@ -1,30 +1,58 @@
 use crate::{
+    constants::Constants,
     error::{EquationError, EquationErrorType},
-    item::Item,
+    item::{Constant, Item, Operator},
     item::{FunctionalOperator, LeftAssociativeOperator},
+    units::{is_unit, parse_unit, Unit},
 };
 
+// a group that is still being parsed
+struct Depth {
+    items: Vec<Item>,
+    closing: Option<char>,
+    // the arguments before each comma, only `root(n, x)` takes more than one
+    arguments: Vec<Vec<Item>>,
+    root: bool,
+}
+
+impl Depth {
+    fn new(closing: Option<char>, root: bool) -> Depth {
+        Depth {
+            items: Vec::new(),
+            closing,
+            arguments: Vec::new(),
+            root,
+        }
+    }
+}
+
 /// A parse stream represents a string that is to be parsed into an equation.
 #[derive(Debug)]
-pub struct ParseStream(String);
+pub struct ParseStream {
+    stream: String,
+    constants: Constants,
+}
 
 impl ParseStream {
-    /// Creates a new parse stream from a string.
+    /// Creates a new parse stream from a string, with the default [`Constants`].
     pub fn new(stream: String) -> ParseStream {
-        ParseStream(stream)
+        ParseStream::with_constants(stream, Constants::default())
+    }
+    /// Creates a new parse stream from a string, where the names in `constants` are parsed as constants.
+    pub fn with_constants(stream: String, constants: Constants) -> ParseStream {
+        ParseStream { stream, constants }
     }
     /// Parses all items in the parse stream into a [`Vec<Item>`](crate::item::Item).
     pub fn parse_items(&self) -> Result<Vec<Item>, EquationError> {
-        let mut depths: Vec<(Vec<Item>, Option<char>)> = Vec::new();
-        depths.push((Vec::new(), None));
+        let mut depths: Vec<Depth> = vec![Depth::new(None, false)];
+        let chars: Vec<char> = self.stream.chars().collect();
         let mut i = 0;
         let mut current_numeric: Option<String> = None;
-        while i < self.0.len() {
+        // the next group holds the arguments of `root`
+        let mut root_next = false;
+        while i < chars.len() {
             let current_group = depths.last_mut().unwrap();
-            let c = self.0.chars().nth(i).ok_or(EquationError::new(
-                "Unexpected end of stream".to_string(),
-                EquationErrorType::UnexpectedToken,
-            ))?;
+            let c = chars[i];
             i += 1;
 
             match (&current_numeric, c) {
@@ -44,38 +72,80 @@ impl ParseStream {
                     continue;
                 }
                 (Some(val), _) => {
-                    current_group.0.push(Item::Value(val.parse().map_err(|_| {
+                    let value = Item::Value(val.parse().map_err(|_| {
                         EquationError::new(
                             "Invalid number".to_string(),
                             EquationErrorType::UnexpectedToken,
                         )
-                    })?));
+                    })?);
                     current_numeric = None;
                     i -= 1;
+                    // a unit after a literal goes with just that literal, written after it (i.e. `3m / 2s` or `1 kW`) or in brackets (i.e. `3 [m] / 2 [s]`)
+                    let start = (i..chars.len())
+                        .find(|i| chars[*i] != ' ')
+                        .unwrap_or(chars.len());
+                    let unit = if chars.get(start) == Some(&'[') {
+                        bracketed_unit(&chars[start..])?
+                    } else {
+                        parse_unit(&chars[start..], false)
+                    };
+                    match unit {
+                        Some((unit, length)) => {
+                            if chars[start] != '[' {
+                                self.check_unit(
+                                    &chars[i..start + length],
+                                    start > i,
+                                    &chars[start + length..],
+                                )?;
+                            }
+                            current_group.items.push(
+                                vec![
+                                    value,
+                                    LeftAssociativeOperator::Multiply.into(),
+                                    Constant::Unit(unit).into(),
+                                ]
+                                .into(),
+                            );
+                            i = start + length;
+                        }
+                        None => current_group.items.push(value),
+                    }
                     continue;
                 }
                 (None, c) if c.is_alphabetic() => {
-                    // peak the next character
-                    let mut temp_i = i;
-                    while temp_i < self.0.len() {
-                        let temp_c = self.0.chars().nth(temp_i).ok_or(EquationError::new(
-                            "Unexpected end of stream".to_string(),
-                            EquationErrorType::UnexpectedToken,
-                        ))?;
-                        temp_i += 1;
-                        if temp_c.is_alphabetic() {
-                            continue;
-                        } else {
-                            break;
-                        }
+                    // peak ahead to the end of the name, after an underscore it can have digits too (i.e. `k_B` or `x_1`)
+                    let mut end = i;
+                    let mut subscript = false;
+                    while end < chars.len()
+                        && (chars[end].is_alphabetic()
+                            || chars[end] == '_'
+                            || subscript && chars[end].is_numeric())
+                    {
+                        subscript |= chars[end] == '_';
+                        end += 1;
                     }
-                    // special case
-                    if temp_i >= self.0.len() - 1 {
-                        temp_i = self.0.len() + 1;
+                    // `log2` is the base 2 log, unless the 2 starts a longer number (i.e. log20)
+                    let digit =
+                        |i: usize| chars.get(i).is_some_and(|c| c.is_numeric() || *c == '.');
+                    if chars[(i - 1)..end] == ['l', 'o', 'g']
+                        && chars.get(end) == Some(&'2')
+                        && !digit(end + 1)
+                    {
+                        end += 1;
                     }
-                    let var_name = self.0.get((i - 1)..(temp_i - 1)).unwrap();
-                    // dbg!(var_name, &self.0, i, temp_i, self.0.len());
-                    current_group.0.push(match var_name {
+                    let var_name: String = chars[(i - 1)..end].iter().collect();
+                    if var_name == "root" || var_name == "nthroot" {
+                        if chars[end..].iter().find(|c| **c != ' ') != Some(&'(') {
+                            return Err(EquationError::new(
+                                format!("Expected arguments after {}", var_name),
+                                EquationErrorType::MissingItems,
+                            ));
+                        }
+                        root_next = true;
+                        i = end;
+                        continue;
+                    }
+                    current_group.items.push(match var_name.as_str() {
                         "log" => FunctionalOperator::Log.into(),
                         "ln" => FunctionalOperator::Ln.into(),
                         "sin" => FunctionalOperator::Sin.into(),
@@ -90,25 +160,119 @@ impl ParseStream {
                         "arccot" => FunctionalOperator::Arccot.into(),
                         "arcsec" => FunctionalOperator::Arcsec.into(),
                         "arccsc" => FunctionalOperator::Arccsc.into(),
-                        _ => var_name.to_string().into(),
+                        "sqrt" => FunctionalOperator::Sqrt.into(),
+                        "cbrt" => FunctionalOperator::Cbrt.into(),
+                        "sinh" => FunctionalOperator::Sinh.into(),
+                        "cosh" => FunctionalOperator::Cosh.into(),
+                        "tanh" => FunctionalOperator::Tanh.into(),
+                        "arcsinh" => FunctionalOperator::Arcsinh.into(),
+                        "arccosh" => FunctionalOperator::Arccosh.into(),
+                        "arctanh" => FunctionalOperator::Arctanh.into(),
+                        "abs" => FunctionalOperator::Abs.into(),
+                        "sign" => FunctionalOperator::Sign.into(),
+                        "floor" => FunctionalOperator::Floor.into(),
+                        "ceil" => FunctionalOperator::Ceil.into(),
+                        "round" => FunctionalOperator::Round.into(),
+                        "trunc" => FunctionalOperator::Trunc.into(),
+                        "frac" => FunctionalOperator::Frac.into(),
+                        "exp" => FunctionalOperator::Exp.into(),
+                        "log2" => FunctionalOperator::Log2.into(),
+                        "gamma" => FunctionalOperator::Gamma.into(),
+                        "lgamma" => FunctionalOperator::Lgamma.into(),
+                        "erf" => FunctionalOperator::Erf.into(),
+                        "erfc" => FunctionalOperator::Erfc.into(),
+                        _ => match self.constants.get(&var_name) {
+                            Some(constant) => constant.into(),
+                            None => var_name.into(),
+                        },
                     });
-                    i = temp_i - 1;
+                    i = end;
                 }
                 (None, c) if c == '(' || c == '[' => {
-                    depths.push((Vec::new(), Some(if c == '(' { ')' } else { ']' })));
+                    let closing = if c == '(' { ')' } else { ']' };
+                    depths.push(Depth::new(Some(closing), std::mem::take(&mut root_next)));
                 }
-                (None, c) if Some(c) == current_group.1 => {
-                    let current_group = depths.pop().unwrap();
+                (None, ',') if current_group.root => {
+                    let argument = std::mem::take(&mut current_group.items);
+                    current_group.arguments.push(argument);
+                }
+                (None, c) if Some(c) == current_group.closing => {
+                    let mut current_group = depths.pop().unwrap();
+                    let item = if current_group.root {
+                        current_group.arguments.push(current_group.items);
+                        // root(n, x) is n √ x
+                        match <[Vec<Item>; 2]>::try_from(current_group.arguments) {
+                            Ok([degree, x]) => vec![
+                                degree.into(),
+                                LeftAssociativeOperator::Root.into(),
+                                x.into(),
+                            ]
+                            .into(),
+                            Err(_) => {
+                                return Err(EquationError::new(
+                                    "root takes a degree and a value".to_string(),
+                                    EquationErrorType::MissingItems,
+                                ))
+                            }
+                        }
+                    } else {
+                        current_group.items.into()
+                    };
                     if let Some(parent_group) = depths.last_mut() {
-                        parent_group.0.push(current_group.0.into());
+                        parent_group.items.push(item);
                     }
                 }
-                (None, c) => current_group.0.push(match c {
+                // the factorial applies to the item before it, along with the functions called on it when it's in brackets (i.e. `abs(-2)!`), so it binds tighter than any operator
+                (None, '!') => match current_group.items.pop() {
+                    Some(
+                        item @ (Item::Value(_)
+                        | Item::Constant(_)
+                        | Item::Variable(_)
+                        | Item::Group(_)),
+                    ) => {
+                        let mut call = vec![item];
+                        if matches!(call[0], Item::Group(_)) {
+                            while let Some(Item::Operator(Operator::Functional(_))) =
+                                current_group.items.last()
+                            {
+                                call.insert(0, current_group.items.pop().unwrap());
+                            }
+                        }
+                        let item = match call.len() {
+                            1 => call.pop().unwrap(),
+                            _ => call.into(),
+                        };
+                        current_group
+                            .items
+                            .push(vec![FunctionalOperator::Factorial.into(), item].into())
+                    }
+                    _ => {
+                        return Err(EquationError::new(
+                            "Expected a value before !".to_string(),
+                            EquationErrorType::MissingItems,
+                        ))
+                    }
+                },
+                // with a value on the left it's the nth root (i.e. `3√8`), otherwise the square root
+                (None, '√')
+                    if matches!(
+                        current_group.items.last(),
+                        Some(
+                            Item::Value(_) | Item::Constant(_) | Item::Variable(_) | Item::Group(_)
+                        )
+                    ) =>
+                {
+                    current_group
+                        .items
+                        .push(LeftAssociativeOperator::Root.into())
+                }
+                (None, c) => current_group.items.push(match c {
                     '+' => LeftAssociativeOperator::Add.into(),
                     '-' => LeftAssociativeOperator::Subtract.into(),
                     '*' => LeftAssociativeOperator::Multiply.into(),
                     '/' => LeftAssociativeOperator::Divide.into(),
                     '^' => LeftAssociativeOperator::Power.into(),
+                    '√' => FunctionalOperator::Sqrt.into(),
                     ' ' => continue,
                     _ => {
                         return Err(EquationError::new(
@@ -134,7 +298,7 @@ impl ParseStream {
             depths
                 .last_mut()
                 .unwrap()
-                .0
+                .items
                 .push(Item::Value(val.parse().map_err(|_| {
                     EquationError::new(
                         "Invalid number".to_string(),
@@ -148,6 +312,57 @@ impl ParseStream {
                 EquationErrorType::MissingItems,
             ));
         }
-        Ok(depths.pop().unwrap().0)
+        Ok(depths.pop().unwrap().items)
     }
+    // fails on a unit without brackets after a literal that could also be read as a name, where `unit` starts right after the literal and `rest` follows it:
+    // after a space a one letter name could be a variable (i.e. the `h` of `2 h`), a name in the constants table could be that constant (i.e. `h` with the physics pack),
+    // and a unit after a `*` could be a variable (i.e. the `t` of `2s*t`)
+    fn check_unit(&self, unit: &[char], spaced: bool, rest: &[char]) -> Result<(), EquationError> {
+        let written: String = unit.iter().collect::<String>().trim().to_string();
+        if rest.first() == Some(&'*') {
+            let next: String = rest[1..].iter().take_while(|c| c.is_alphabetic()).collect();
+            if is_unit(&next) {
+                return Err(EquationError::new(
+                    format!(
+                        "{}*{} could be a unit or a product, write the unit in brackets (i.e. [{}*{}]) or put spaces around the *",
+                        written, next, written, next
+                    ),
+                    EquationErrorType::UnexpectedToken,
+                ));
+            }
+        }
+        for name in written.split(|c: char| !c.is_alphabetic()) {
+            let constant = self.constants.get(name).filter(|constant| {
+                // `deg` is the same as its constant
+                Unit::parse(name).map_or(true, |unit| {
+                    !unit.dimension().is_dimensionless() || unit.factor() != constant.value()
+                })
+            });
+            if constant.is_some() {
+                return Err(EquationError::new(
+                    format!("Unit {} in {} is also a constant", name, written),
+                    EquationErrorType::UnexpectedToken,
+                ));
+            }
+            if spaced && name.chars().count() == 1 {
+                return Err(EquationError::new(
+                    format!(
+                        "Unit {} could be a variable, write {} straight after the number or in brackets",
+                        name, written
+                    ),
+                    EquationErrorType::UnexpectedToken,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// the unit in brackets at the start of `chars` (i.e. `[km/h]`), and the number of characters up to the closing bracket
+fn bracketed_unit(chars: &[char]) -> Result<Option<(Unit, usize)>, EquationError> {
+    let Some(end) = chars.iter().position(|c| *c == ']') else {
+        return Ok(None);
+    };
+    let unit = Unit::parse(&chars[1..end].iter().collect::<String>())?;
+    Ok(Some((unit, end + 1)))
 }
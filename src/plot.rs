@@ -0,0 +1,161 @@
+use std::fmt::Write;
+
+use crate::error::EquationError;
+
+// the share of the width at either end whose values can fall outside the plot, so that a pole (i.e. 0 for `1/x`) doesn't flatten the rest
+const OUTLIERS: f64 = 0.01;
+// the space left above and below the values, as a fraction of their height
+const MARGIN: f64 = 0.1;
+
+/// Draws samples (see [`Equation::sample`](crate::Equation::sample)) as an SVG image of `width` by `height` pixels, with the axes where they are in view.
+/// The curve is broken wherever the equation isn't defined or jumps across the view (i.e. at the poles of `tan(x)`).
+pub fn svg(samples: &[(f64, Result<f64, EquationError>)], width: u32, height: u32) -> String {
+    let (width, height) = (width as f64, height as f64);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n<rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n",
+        w = width,
+        h = height
+    );
+    let Some(view) = View::of(samples) else {
+        svg.push_str("</svg>\n");
+        return svg;
+    };
+    let to_x = |x: f64| (x - view.left) / (view.right - view.left) * width;
+    let to_y = |y: f64| (view.top - y) / (view.top - view.bottom) * height;
+    if view.bottom <= 0.0 && view.top >= 0.0 {
+        let y = to_y(0.0);
+        let _ = writeln!(
+            svg,
+            "<line x1=\"0\" y1=\"{y:.2}\" x2=\"{width}\" y2=\"{y:.2}\" stroke=\"gray\"/>"
+        );
+    }
+    if view.left <= 0.0 && view.right >= 0.0 {
+        let x = to_x(0.0);
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{x:.2}\" y1=\"0\" x2=\"{x:.2}\" y2=\"{height}\" stroke=\"gray\"/>"
+        );
+    }
+    for segment in view.segments(samples) {
+        let points: Vec<String> = segment
+            .iter()
+            .map(|(x, y)| format!("{:.2},{:.2}", to_x(*x), to_y(*y)))
+            .collect();
+        let _ = writeln!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"1.5\"/>",
+            points.join(" ")
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Draws samples (see [`Equation::sample`](crate::Equation::sample)) as text of `columns` by `rows` characters, where the curve is `*` and the axes are `-` and `|`.
+pub fn ascii(samples: &[(f64, Result<f64, EquationError>)], columns: usize, rows: usize) -> String {
+    if columns == 0 || rows == 0 {
+        return String::new();
+    }
+    let mut grid = vec![vec![' '; columns]; rows];
+    if let Some(view) = View::of(samples) {
+        let column = |x: f64| {
+            ((x - view.left) / (view.right - view.left) * (columns - 1) as f64).round() as usize
+        };
+        let row = |y: f64| ((view.top - y) / (view.top - view.bottom) * (rows - 1) as f64).round();
+        if view.bottom <= 0.0 && view.top >= 0.0 {
+            grid[row(0.0) as usize].fill('-');
+        }
+        if view.left <= 0.0 && view.right >= 0.0 {
+            let x = column(0.0);
+            for line in grid.iter_mut() {
+                line[x] = if line[x] == '-' { '+' } else { '|' };
+            }
+        }
+        for (x, y) in view.segments(samples).into_iter().flatten() {
+            let y = row(y);
+            if (0.0..rows as f64).contains(&y) {
+                grid[y as usize][column(x)] = '*';
+            }
+        }
+    }
+    let mut text = String::with_capacity((columns + 1) * rows);
+    for line in grid {
+        text.extend(line);
+        text.push('\n');
+    }
+    text
+}
+
+// the part of the plane that is drawn
+struct View {
+    left: f64,
+    right: f64,
+    bottom: f64,
+    top: f64,
+}
+
+impl View {
+    fn of(samples: &[(f64, Result<f64, EquationError>)]) -> Option<View> {
+        let (first, last) = (samples.first()?.0, samples.last()?.0);
+        let (left, right) = (first.min(last), first.max(last));
+        let (left, right) = if right > left {
+            (left, right)
+        } else {
+            (left - 1.0, left + 1.0)
+        };
+        // every value weighs as much as the width around it, since the points bunch up where the equation is steep
+        let mut values: Vec<(f64, f64)> = (0..samples.len())
+            .filter_map(|i| {
+                let y = *samples[i].1.as_ref().ok().filter(|y| y.is_finite())?;
+                let before = samples[i.saturating_sub(1)].0;
+                let after = samples[(i + 1).min(samples.len() - 1)].0;
+                Some((y, (after - before).abs().max(f64::MIN_POSITIVE)))
+            })
+            .collect();
+        values.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let total: f64 = values.iter().map(|(_, weight)| weight).sum();
+        let quantile = |q: f64| {
+            let mut sum = 0.0;
+            values
+                .iter()
+                .find(|(_, weight)| {
+                    sum += weight;
+                    sum >= q * total
+                })
+                .or(values.last())
+                .map(|(y, _)| *y)
+        };
+        let (bottom, top) = (quantile(OUTLIERS)?, quantile(1.0 - OUTLIERS)?);
+        let margin = if top > bottom {
+            (top - bottom) * MARGIN
+        } else {
+            1.0
+        };
+        Some(View {
+            left,
+            right,
+            bottom: bottom - margin,
+            top: top + margin,
+        })
+    }
+    // the runs of finite values, split where the equation jumps from one side of the view to the other between neighbours (i.e. `tan(x)` at a pole)
+    fn segments(&self, samples: &[(f64, Result<f64, EquationError>)]) -> Vec<Vec<(f64, f64)>> {
+        let mut segments: Vec<Vec<(f64, f64)>> = Vec::new();
+        let mut current: Vec<(f64, f64)> = Vec::new();
+        for (x, y) in samples {
+            let Some(y) = y.as_ref().ok().copied().filter(|y| y.is_finite()) else {
+                segments.extend((!current.is_empty()).then(|| std::mem::take(&mut current)));
+                continue;
+            };
+            if let Some((_, previous)) = current.last() {
+                let (below, above) = (|y: f64| y < self.bottom, |y: f64| y > self.top);
+                if (below(*previous) && above(y)) || (above(*previous) && below(y)) {
+                    segments.push(std::mem::take(&mut current));
+                }
+            }
+            current.push((*x, y));
+        }
+        segments.extend((!current.is_empty()).then_some(current));
+        segments
+    }
+}
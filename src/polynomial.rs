@@ -0,0 +1,457 @@
+use std::f64::consts::PI;
+
+#[cfg(feature = "complex")]
+use num_complex::Complex64;
+
+use crate::{
+    error::EquationError,
+    item::{Constant, FunctionalOperator, LeftAssociativeOperator},
+    numeric::{unsupported, Evaluator},
+};
+
+// the highest degree a power can raise a polynomial to
+const MAX_DEGREE: usize = 1024;
+
+/// A polynomial in one variable, see [`Equation::as_polynomial`](crate::Equation::as_polynomial).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    // lowest degree first, without zeros on the end
+    coefficients: Vec<f64>,
+}
+
+/// A root of a [`Polynomial`], which can be complex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Root {
+    /// The real part.
+    pub re: f64,
+    /// The imaginary part.
+    pub im: f64,
+}
+
+impl Root {
+    fn real(re: f64) -> Root {
+        Root { re, im: 0.0 }
+    }
+    /// Checks if the root is a real number.
+    pub fn is_real(&self) -> bool {
+        self.im == 0.0
+    }
+    /// Gives the distance of the root from zero.
+    pub fn norm(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+    fn add(self, other: Root) -> Root {
+        Root {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+    fn sub(self, other: Root) -> Root {
+        Root {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+    fn mul(self, other: Root) -> Root {
+        Root {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+    fn div(self, other: Root) -> Root {
+        let norm = other.re * other.re + other.im * other.im;
+        Root {
+            re: (self.re * other.re + self.im * other.im) / norm,
+            im: (self.im * other.re - self.re * other.im) / norm,
+        }
+    }
+    // the principal square root
+    fn sqrt(self) -> Root {
+        let norm = self.norm();
+        let re = ((norm + self.re) / 2.0).sqrt();
+        let im = ((norm - self.re) / 2.0).sqrt();
+        Root {
+            re,
+            im: if self.im < 0.0 { -im } else { im },
+        }
+    }
+}
+
+#[cfg(feature = "complex")]
+impl From<Root> for Complex64 {
+    fn from(root: Root) -> Self {
+        Complex64::new(root.re, root.im)
+    }
+}
+
+impl Polynomial {
+    /// Creates a polynomial from its coefficients, lowest degree first (i.e. `[1.0, 0.0, 2.0]` is `2x^2 + 1`).
+    pub fn new(coefficients: Vec<f64>) -> Polynomial {
+        let mut coefficients = coefficients;
+        while coefficients.last() == Some(&0.0) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+    /// Gives the coefficients, lowest degree first.
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+    /// Gives the degree, which is zero for constants (including zero itself).
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+    /// Evaluates the polynomial at `x`.
+    pub fn evaluate(&self, x: f64) -> f64 {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |sum, c| sum.mul_add(x, *c))
+    }
+    /// Gives every root, complex ones included, repeated as many times as they divide the polynomial and sorted by their real then imaginary parts.
+    /// Degrees up to 4 are solved in closed form, higher degrees with the Durand-Kerner method. Constants have no roots.
+    pub fn roots(&self) -> Vec<Root> {
+        // roots at zero are exact
+        let zeros = self.coefficients.iter().take_while(|c| **c == 0.0).count();
+        let mut roots = vec![Root::real(0.0); zeros.min(self.degree())];
+        let c = &self.coefficients[roots.len()..];
+        roots.extend(match c.len().saturating_sub(1) {
+            0 => Vec::new(),
+            1 => vec![Root::real(-c[0] / c[1])],
+            2 => self.polish(quadratic(c[2], c[1], c[0]).to_vec()),
+            3 => self.polish(cubic(c[3], c[2], c[1], c[0]).to_vec()),
+            4 => self.polish(quartic(c[4], c[3], c[2], c[1], c[0]).to_vec()),
+            _ => self.polish(durand_kerner(c)),
+        });
+        let mut roots = self.merge_multiple(roots);
+        roots.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+        roots
+    }
+    /// Gives the real roots, repeated as many times as they divide the polynomial, in increasing order.
+    pub fn real_roots(&self) -> Vec<f64> {
+        self.roots()
+            .into_iter()
+            .filter(Root::is_real)
+            .map(|root| root.re)
+            .collect()
+    }
+    // the (k)th derivative
+    fn derivative(&self, k: usize) -> Polynomial {
+        Polynomial::new(
+            self.coefficients
+                .iter()
+                .enumerate()
+                .skip(k)
+                .map(|(i, c)| (i + 1 - k..=i).fold(*c, |c, factor| c * factor as f64))
+                .collect(),
+        )
+    }
+    // a root of multiplicity k comes out as k roots spread in a little circle around it, which are replaced with the root of the (k - 1)th derivative
+    // near their mean, as long as the polynomial there is zero to within its rounding error
+    fn merge_multiple(&self, mut roots: Vec<Root>) -> Vec<Root> {
+        let mut merged = vec![false; roots.len()];
+        for i in 0..roots.len() {
+            if merged[i] {
+                continue;
+            }
+            let radius = 1e-2 * roots[i].norm().max(1.0);
+            let cluster: Vec<usize> = (i..roots.len())
+                .filter(|j| !merged[*j] && roots[*j].sub(roots[i]).norm() <= radius)
+                .collect();
+            if cluster.len() < 2 {
+                continue;
+            }
+            let k = cluster.len();
+            let sum = cluster
+                .iter()
+                .fold(Root::real(0.0), |sum, j| sum.add(roots[*j]));
+            let mut center = sum.div(Root::real(k as f64));
+            if center.im.abs() <= 1e-9 * center.norm().max(1.0) {
+                center.im = 0.0;
+            }
+            let derivative = self.derivative(k - 1);
+            for _ in 0..5 {
+                let (value, slope) = derivative.evaluate_complex(center);
+                if slope.norm() == 0.0 {
+                    break;
+                }
+                center = center.sub(value.div(slope));
+            }
+            let size = self
+                .coefficients
+                .iter()
+                .rev()
+                .fold(0.0, |sum, c| sum * center.norm() + c.abs());
+            if self.evaluate_complex(center).0.norm() <= 16.0 * k as f64 * f64::EPSILON * size {
+                for j in cluster {
+                    roots[j] = center;
+                    merged[j] = true;
+                }
+            }
+        }
+        roots
+    }
+    fn evaluate_complex(&self, z: Root) -> (Root, Root) {
+        // horner's method for the value and the derivative together
+        let mut value = Root::real(0.0);
+        let mut derivative = Root::real(0.0);
+        for c in self.coefficients.iter().rev() {
+            derivative = derivative.mul(z).add(value);
+            value = value.mul(z).add(Root::real(*c));
+        }
+        (value, derivative)
+    }
+    // a few newton steps on the closed forms, which can lose digits to cancellation and rounding
+    fn polish(&self, roots: Vec<Root>) -> Vec<Root> {
+        roots
+            .into_iter()
+            .map(|mut root| {
+                for _ in 0..3 {
+                    let (value, derivative) = self.evaluate_complex(root);
+                    if derivative.norm() == 0.0 {
+                        break;
+                    }
+                    let mut next = root.sub(value.div(derivative));
+                    if root.is_real() {
+                        next.im = 0.0;
+                    }
+                    // a NaN is no better either
+                    let better = self.evaluate_complex(next).0.norm() < value.norm();
+                    if !better {
+                        break;
+                    }
+                    root = next;
+                }
+                root
+            })
+            .collect()
+    }
+}
+
+// roots of a x^2 + b x + c, without the cancellation of the schoolbook formula
+fn quadratic(a: f64, b: f64, c: f64) -> [Root; 2] {
+    let discriminant = b.mul_add(b, -4.0 * a * c);
+    if discriminant < 0.0 {
+        let re = -b / (2.0 * a);
+        let im = (-discriminant).sqrt() / (2.0 * a).abs();
+        return [Root { re, im: -im }, Root { re, im }];
+    }
+    if b == 0.0 {
+        // the roots are symmetric, and a single square root keeps them correctly rounded
+        let root = (-c / a).sqrt();
+        return [Root::real(-root), Root::real(root)];
+    }
+    let q = -(b + discriminant.sqrt().copysign(b)) / 2.0;
+    if q == 0.0 {
+        return [Root::real(0.0), Root::real(0.0)];
+    }
+    [Root::real(q / a), Root::real(c / q)]
+}
+
+// roots of a x^3 + b x^2 + c x + d, through the depressed cubic t^3 + p t + q with x = t - b / 3a
+fn cubic(a: f64, b: f64, c: f64, d: f64) -> [Root; 3] {
+    let (b, c, d) = (b / a, c / a, d / a);
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = (q / 2.0) * (q / 2.0) + (p / 3.0) * (p / 3.0) * (p / 3.0);
+    if discriminant > 0.0 {
+        // one real root and a complex pair, cardano's formula
+        let u = (-q / 2.0 + discriminant.sqrt().copysign(-q)).cbrt();
+        let v = if u == 0.0 { 0.0 } else { -p / (3.0 * u) };
+        let re = -(u + v) / 2.0 - shift;
+        let im = (u - v).abs() * 3f64.sqrt() / 2.0;
+        [
+            Root::real(u + v - shift),
+            Root { re, im: -im },
+            Root { re, im },
+        ]
+    } else if discriminant == 0.0 {
+        if p == 0.0 {
+            return [Root::real(-shift); 3];
+        }
+        // a double root
+        let single = 3.0 * q / p;
+        let double = -3.0 * q / (2.0 * p);
+        [
+            Root::real(single - shift),
+            Root::real(double - shift),
+            Root::real(double - shift),
+        ]
+    } else {
+        // three real roots, the trigonometric form
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let angle = (3.0 * q / (p * r)).clamp(-1.0, 1.0).acos() / 3.0;
+        [0.0, 1.0, 2.0].map(|k| Root::real(r * (angle - 2.0 * PI * k / 3.0).cos() - shift))
+    }
+}
+
+// roots of a x^4 + b x^3 + c x^2 + d x + e with ferrari's method, through the depressed quartic y^4 + p y^2 + q y + r with x = y - b / 4a
+fn quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> [Root; 4] {
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+    let shift = Root::real(b / 4.0);
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+    let roots = if q == 0.0 {
+        // a quadratic in y^2
+        let [z1, z2] = quadratic(1.0, p, r);
+        let (y1, y2) = (z1.sqrt(), z2.sqrt());
+        [y1, Root::real(0.0).sub(y1), y2, Root::real(0.0).sub(y2)]
+    } else {
+        // the largest root of the resolvent cubic 8m^3 + 8p m^2 + (2p^2 - 8r) m - q^2 is positive
+        let m = cubic(8.0, 8.0 * p, 2.0 * p * p - 8.0 * r, -q * q)
+            .iter()
+            .filter(|root| root.is_real())
+            .map(|root| root.re)
+            .fold(f64::NEG_INFINITY, f64::max)
+            .max(f64::MIN_POSITIVE);
+        let s = (2.0 * m).sqrt();
+        let [y1, y2] = quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s));
+        let [y3, y4] = quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s));
+        [y1, y2, y3, y4]
+    };
+    roots.map(|root| root.sub(shift))
+}
+
+// every root at once, each one repeatedly divided by its distance from the others
+fn durand_kerner(coefficients: &[f64]) -> Vec<Root> {
+    let n = coefficients.len() - 1;
+    let lead = coefficients[n];
+    let monic: Vec<f64> = coefficients.iter().map(|c| c / lead).collect();
+    // every root lies within cauchy's bound
+    let bound = 1.0 + monic[..n].iter().fold(0.0, |max: f64, c| max.max(c.abs()));
+    let seed = Root { re: 0.4, im: 0.9 };
+    let mut roots: Vec<Root> = (0..n)
+        .scan(Root::real(bound / 2.0), |z, _| {
+            *z = z.mul(seed);
+            Some(*z)
+        })
+        .collect();
+    let evaluate = |z: Root| {
+        monic
+            .iter()
+            .rev()
+            .fold(Root::real(0.0), |sum, c| sum.mul(z).add(Root::real(*c)))
+    };
+    for _ in 0..1000 {
+        let mut change: f64 = 0.0;
+        for i in 0..n {
+            let denominator = (0..n)
+                .filter(|j| *j != i)
+                .fold(Root::real(1.0), |product, j| {
+                    product.mul(roots[i].sub(roots[j]))
+                });
+            let step = evaluate(roots[i]).div(denominator);
+            if step.re.is_finite() && step.im.is_finite() {
+                roots[i] = roots[i].sub(step);
+                change = change.max(step.norm() / roots[i].norm().max(1.0));
+            }
+        }
+        if change <= f64::EPSILON {
+            break;
+        }
+    }
+    // the roots of a real polynomial that are real only keep a trace of an imaginary part
+    for root in roots.iter_mut() {
+        if root.im.abs() <= 1e-9 * root.norm().max(1.0) {
+            root.im = 0.0;
+        }
+    }
+    roots
+}
+
+fn not_polynomial() -> EquationError {
+    unsupported("The equation is not a polynomial".to_string())
+}
+
+// evaluates an equation as a polynomial in one variable, failing if it isn't one
+#[derive(Debug)]
+pub(crate) struct PolynomialEvaluator<'a> {
+    variable: &'a str,
+}
+
+impl<'a> PolynomialEvaluator<'a> {
+    pub(crate) fn new(variable: &'a str) -> Self {
+        PolynomialEvaluator { variable }
+    }
+}
+
+fn constant(value: f64) -> Vec<f64> {
+    vec![value]
+}
+
+fn is_constant(p: &[f64]) -> bool {
+    p[1..].iter().all(|c| *c == 0.0)
+}
+
+fn multiply(lhs: &[f64], rhs: &[f64]) -> Result<Vec<f64>, EquationError> {
+    if lhs.len() + rhs.len() - 1 > MAX_DEGREE + 1 {
+        return Err(not_polynomial());
+    }
+    let mut product = vec![0.0; lhs.len() + rhs.len() - 1];
+    for (i, a) in lhs.iter().enumerate() {
+        for (j, b) in rhs.iter().enumerate() {
+            product[i + j] += a * b;
+        }
+    }
+    Ok(product)
+}
+
+impl Evaluator for PolynomialEvaluator<'_> {
+    type Value = Vec<f64>;
+    fn value(&self, value: f64) -> Result<Vec<f64>, EquationError> {
+        Ok(constant(value))
+    }
+    fn constant(&self, constant: &Constant) -> Result<Vec<f64>, EquationError> {
+        Ok(vec![constant.value()])
+    }
+    fn variable(&self, name: &str) -> Option<Vec<f64>> {
+        (name == self.variable).then(|| vec![0.0, 1.0])
+    }
+    fn negate(&self, x: Vec<f64>) -> Result<Vec<f64>, EquationError> {
+        Ok(x.into_iter().map(|c| -c).collect())
+    }
+    fn function(&self, func: &FunctionalOperator, x: Vec<f64>) -> Result<Vec<f64>, EquationError> {
+        if !is_constant(&x) {
+            return Err(not_polynomial());
+        }
+        Ok(constant(func.evaluate(x[0])))
+    }
+    fn operator(
+        &self,
+        op: &LeftAssociativeOperator,
+        lhs: Vec<f64>,
+        rhs: Vec<f64>,
+    ) -> Result<Vec<f64>, EquationError> {
+        match op {
+            LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => {
+                let sign = if *op == LeftAssociativeOperator::Add {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let length = lhs.len().max(rhs.len());
+                Ok((0..length)
+                    .map(|i| {
+                        lhs.get(i).copied().unwrap_or(0.0)
+                            + sign * rhs.get(i).copied().unwrap_or(0.0)
+                    })
+                    .collect())
+            }
+            LeftAssociativeOperator::Multiply => multiply(&lhs, &rhs),
+            LeftAssociativeOperator::Divide if is_constant(&rhs) => {
+                Ok(lhs.into_iter().map(|c| c / rhs[0]).collect())
+            }
+            _ if is_constant(&lhs) && is_constant(&rhs) => Ok(constant(op.eval(lhs[0], rhs[0]))),
+            LeftAssociativeOperator::Power if is_constant(&rhs) => {
+                let exponent = rhs[0];
+                if exponent < 0.0 || exponent.fract() != 0.0 || exponent > MAX_DEGREE as f64 {
+                    return Err(not_polynomial());
+                }
+                (0..exponent as usize).try_fold(constant(1.0), |power, _| multiply(&power, &lhs))
+            }
+            _ => Err(not_polynomial()),
+        }
+    }
+}
@@ -0,0 +1,1218 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+};
+
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{
+    error::EquationError,
+    item::{Constant, FunctionalOperator, LeftAssociativeOperator},
+    numeric::{division_by_zero, overflow, unsupported, Evaluator},
+};
+
+/// An arbitrary-precision binary floating point number, the result of [`Equation::evaluate_with_precision`](crate::Equation::evaluate_with_precision).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigFloat {
+    // the value is `mantissa * 2^exponent`, the mantissa never has trailing zero bits
+    mantissa: BigInt,
+    exponent: i64,
+}
+
+impl BigFloat {
+    fn new(mantissa: BigInt, exponent: i64) -> BigFloat {
+        match mantissa.trailing_zeros() {
+            Some(zeros) => BigFloat {
+                mantissa: mantissa >> zeros,
+                exponent: exponent + zeros as i64,
+            },
+            None => BigFloat::zero(),
+        }
+    }
+    fn zero() -> BigFloat {
+        BigFloat {
+            mantissa: BigInt::zero(),
+            exponent: 0,
+        }
+    }
+    fn from_int(val: i64) -> BigFloat {
+        BigFloat::new(val.into(), 0)
+    }
+    /// Checks if the number is zero.
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+    /// Checks if the number is below zero.
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.is_negative()
+    }
+    /// Gives the closest f64 to the number.
+    pub fn to_f64(&self) -> f64 {
+        self.to_decimal(17).parse().unwrap_or(f64::NAN)
+    }
+    /// Gives the number rounded to `digits` significant decimal digits.
+    pub fn to_decimal(&self, digits: usize) -> String {
+        let (digits, exponent) = self.round_decimal(digits.max(1));
+        format_decimal(self.is_negative(), digits, exponent)
+    }
+    // the value is below 2^magnitude and at least 2^(magnitude - 1)
+    fn magnitude(&self) -> i64 {
+        self.exponent + self.mantissa.bits() as i64
+    }
+    fn abs(&self) -> BigFloat {
+        BigFloat {
+            mantissa: self.mantissa.abs(),
+            exponent: self.exponent,
+        }
+    }
+    fn neg(&self) -> BigFloat {
+        BigFloat {
+            mantissa: -&self.mantissa,
+            exponent: self.exponent,
+        }
+    }
+    fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+    fn to_i64(&self) -> Option<i64> {
+        if !self.is_integer() || self.magnitude() > 62 {
+            return None;
+        }
+        (&self.mantissa << self.exponent as u64).to_i64()
+    }
+    fn cmp_abs(&self, other: &BigFloat) -> Ordering {
+        let e = self.exponent.min(other.exponent);
+        let a = self.mantissa.magnitude() << (self.exponent - e) as u64;
+        let b = other.mantissa.magnitude() << (other.exponent - e) as u64;
+        a.cmp(&b)
+    }
+    // the number as a fixed point integer scaled by 2^scale, rounded down
+    fn to_fixed(&self, scale: u64) -> BigInt {
+        let shift = self.exponent + scale as i64;
+        if shift >= 0 {
+            &self.mantissa << shift as u64
+        } else {
+            &self.mantissa >> (-shift) as u64
+        }
+    }
+    // the decimal digits and the decimal exponent, so the value is `0.digits * 10^exponent`
+    fn round_decimal(&self, digits: usize) -> (String, i64) {
+        if self.is_zero() {
+            return ("0".to_string(), 1);
+        }
+        let (mut numer, mut denom) = (self.mantissa.magnitude().clone(), BigUint::one());
+        if self.exponent >= 0 {
+            numer <<= self.exponent as u64;
+        } else {
+            denom <<= (-self.exponent) as u64;
+        }
+        // the guess can be off by one, which the loop fixes
+        let mut exponent =
+            ((self.magnitude() - 1) as f64 * std::f64::consts::LOG10_2).floor() as i64 + 1;
+        loop {
+            let shift = digits as i64 - exponent;
+            let ten = BigUint::from(10u32);
+            let (n, d) = if shift >= 0 {
+                (&numer * ten.pow(shift as u32), denom.clone())
+            } else {
+                (numer.clone(), &denom * ten.pow((-shift) as u32))
+            };
+            let scaled = (n * 2u32 + &d) / (d * 2u32);
+            let scaled = scaled.to_string();
+            match scaled.len().cmp(&digits) {
+                Ordering::Greater => exponent += 1,
+                Ordering::Less => exponent -= 1,
+                Ordering::Equal => return (scaled, exponent),
+            }
+        }
+    }
+    // how many significant digits the exact decimal expansion has, if it has at most `limit`
+    fn exact_digits(&self, limit: usize) -> Option<usize> {
+        let estimate = (self.mantissa.bits() as i64 + (-self.exponent).max(0) * 3) as f64
+            * std::f64::consts::LOG10_2;
+        if estimate > limit as f64 + 2.0 {
+            return None;
+        }
+        let digits = if self.exponent >= 0 {
+            (self.mantissa.magnitude() << self.exponent as u64).to_string()
+        } else {
+            (self.mantissa.magnitude() * BigUint::from(5u32).pow((-self.exponent) as u32))
+                .to_string()
+        };
+        let digits = digits.trim_end_matches('0').len();
+        (digits <= limit).then_some(digits)
+    }
+}
+
+// `0.digits * 10^exponent`, written out positionally unless that would be very long
+fn format_decimal(negative: bool, digits: String, exponent: i64) -> String {
+    let digits = digits.trim_end_matches('0');
+    let digits = if digits.is_empty() { "0" } else { digits };
+    let sign = if negative { "-" } else { "" };
+    if (-8..=40).contains(&exponent) {
+        if exponent <= 0 {
+            format!("{}0.{}{}", sign, "0".repeat((-exponent) as usize), digits)
+        } else if digits.len() as i64 <= exponent {
+            format!(
+                "{}{}{}",
+                sign,
+                digits,
+                "0".repeat((exponent - digits.len() as i64) as usize)
+            )
+        } else {
+            let (whole, fraction) = digits.split_at(exponent as usize);
+            format!("{}{}.{}", sign, whole, fraction)
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let point = if rest.is_empty() { "" } else { "." };
+        format!("{}{}{}{}e{}", sign, first, point, rest, exponent - 1)
+    }
+}
+
+// shows as many digits as the mantissa holds, or the exact value if that is short
+impl Display for BigFloat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let digits = ((self.mantissa.bits() as f64 * std::f64::consts::LOG10_2) as usize).max(1);
+        let digits = self.exact_digits(digits.max(17)).unwrap_or(digits);
+        write!(f, "{}", self.to_decimal(digits))
+    }
+}
+
+// rounds `mantissa * 2^exponent` to `bits` significant bits, ties to even
+fn round(mantissa: BigInt, exponent: i64, bits: u64) -> BigFloat {
+    let len = mantissa.bits();
+    if len <= bits {
+        return BigFloat::new(mantissa, exponent);
+    }
+    let shift = len - bits;
+    let (sign, magnitude) = mantissa.into_parts();
+    let mut kept = &magnitude >> shift;
+    let rest = magnitude - (&kept << shift);
+    let half = BigUint::one() << (shift - 1);
+    let up = match rest.cmp(&half) {
+        Ordering::Greater => true,
+        Ordering::Less => false,
+        Ordering::Equal => kept.bit(0),
+    };
+    if up {
+        kept += 1u32;
+    }
+    BigFloat::new(BigInt::from_biguint(sign, kept), exponent + shift as i64)
+}
+
+// sets the lowest bit if anything was cut off, so rounding can tell a tie from slightly above one
+fn sticky(mantissa: BigInt, inexact: bool) -> BigInt {
+    let sticky = if !inexact {
+        0
+    } else if mantissa.is_negative() {
+        -1
+    } else {
+        1
+    };
+    (mantissa << 1u64) + sticky
+}
+
+fn add(a: &BigFloat, b: &BigFloat, bits: u64) -> BigFloat {
+    if a.is_zero() || b.is_zero() {
+        let val = if a.is_zero() { b } else { a };
+        return round(val.mantissa.clone(), val.exponent, bits);
+    }
+    let (big, small) = if a.magnitude() >= b.magnitude() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    // a far smaller value only matters for which way the bigger one rounds
+    let extra = (bits + 2).saturating_sub(big.mantissa.bits());
+    if small.magnitude() < big.exponent - extra as i64 - 2 {
+        let shifted = &big.mantissa << (extra + 2);
+        let nudge = if small.is_negative() { -1 } else { 1 };
+        return round(
+            (shifted << 1u64) + nudge,
+            big.exponent - extra as i64 - 3,
+            bits,
+        );
+    }
+    let e = a.exponent.min(b.exponent);
+    let mantissa =
+        (&a.mantissa << (a.exponent - e) as u64) + (&b.mantissa << (b.exponent - e) as u64);
+    round(mantissa, e, bits)
+}
+
+fn mul(a: &BigFloat, b: &BigFloat, bits: u64) -> BigFloat {
+    round(&a.mantissa * &b.mantissa, a.exponent + b.exponent, bits)
+}
+
+fn div(a: &BigFloat, b: &BigFloat, bits: u64) -> Result<BigFloat, EquationError> {
+    if b.is_zero() {
+        return Err(division_by_zero());
+    }
+    let shift = (bits as i64 + 2 + b.mantissa.bits() as i64 - a.mantissa.bits() as i64).max(0);
+    let numer = &a.mantissa << shift as u64;
+    let quotient = &numer / &b.mantissa;
+    let inexact = &quotient * &b.mantissa != numer;
+    Ok(round(
+        sticky(quotient, inexact),
+        a.exponent - b.exponent - shift - 1,
+        bits,
+    ))
+}
+
+fn sqrt(a: &BigFloat, bits: u64) -> Result<BigFloat, EquationError> {
+    if a.is_negative() {
+        return Err(unsupported(
+            "The square root of a negative number is not real".to_string(),
+        ));
+    }
+    let mut shift = (2 * (bits as i64 + 2) - a.mantissa.bits() as i64).max(0);
+    if (a.exponent - shift) % 2 != 0 {
+        shift += 1;
+    }
+    let scaled = &a.mantissa << shift as u64;
+    let root = scaled.sqrt();
+    let inexact = &root * &root != scaled;
+    Ok(round(
+        sticky(root, inexact),
+        (a.exponent - shift) / 2 - 1,
+        bits,
+    ))
+}
+
+// an approximation `mantissa * 2^exponent`, off by at most `error * 2^exponent`
+struct Approx {
+    mantissa: BigInt,
+    exponent: i64,
+    error: BigInt,
+}
+
+impl Approx {
+    // a fixed point approximation scaled by 2^scale
+    fn fixed(mantissa: BigInt, scale: u64, error: impl Into<BigInt>) -> Approx {
+        Approx {
+            mantissa,
+            exponent: -(scale as i64),
+            error: error.into(),
+        }
+    }
+}
+
+// ziv's strategy: keeps adding working precision until the whole error range rounds to the same number
+fn correctly_rounded(
+    bits: u64,
+    approximate: impl Fn(u64) -> Result<Approx, EquationError>,
+) -> Result<BigFloat, EquationError> {
+    let mut working = bits + 32;
+    loop {
+        let approx = approximate(working)?;
+        let low = round(&approx.mantissa - &approx.error, approx.exponent, bits);
+        let high = round(&approx.mantissa + &approx.error, approx.exponent, bits);
+        // only exact results never settle, and those are caught before getting here
+        if low == high || working > 64 * bits + 8192 {
+            return Ok(round(approx.mantissa, approx.exponent, bits));
+        }
+        working *= 2;
+    }
+}
+
+// the guard bits every fixed point calculation keeps on top of the requested scale
+const GUARD: u64 = 64;
+
+fn one(scale: u64) -> BigInt {
+    BigInt::one() << scale
+}
+
+// the fixed point `a * b`, adding at most one unit of error. Rounds towards zero so that series terms reach zero
+fn fixed_mul(a: &BigInt, b: &BigInt, scale: u64) -> BigInt {
+    let product = a * b;
+    if product.is_negative() {
+        -((-product) >> scale)
+    } else {
+        product >> scale
+    }
+}
+
+// the fixed point `a / b`, with `a` and `b` off by at most `a_error` and `b_error`
+fn fixed_div(a: &BigInt, a_error: &BigInt, b: &BigInt, b_error: &BigInt, scale: u64) -> Approx {
+    let (a_abs, b_abs) = (a.abs(), b.abs());
+    if b_abs <= *b_error {
+        // b could be zero, ask for more precision
+        return Approx::fixed(BigInt::zero(), scale, one(2 * scale));
+    }
+    let quotient = (a << scale) / b;
+    let error = ((a_error * &b_abs + b_error * &a_abs) << scale) / (&b_abs * (&b_abs - b_error));
+    Approx::fixed(quotient, scale, error + 2)
+}
+
+// atan(1 / n) scaled by 2^scale, off by at most the returned error
+fn atan_inverse(n: u64, scale: u64, hyperbolic: bool) -> (BigInt, u64) {
+    let square = BigInt::from(n * n);
+    let mut term = one(scale) / n;
+    let mut sum = BigInt::zero();
+    let mut k = 0u64;
+    while !term.is_zero() {
+        let next = &term / (2 * k + 1);
+        if k % 2 == 1 && !hyperbolic {
+            sum -= next;
+        } else {
+            sum += next;
+        }
+        term /= &square;
+        k += 1;
+    }
+    (sum, 2 * k + 2)
+}
+
+fn pi_fixed(scale: u64) -> (BigInt, u64) {
+    // machin's formula: pi = 16 atan(1/5) - 4 atan(1/239)
+    let (a, a_error) = atan_inverse(5, scale, false);
+    let (b, b_error) = atan_inverse(239, scale, false);
+    (a * 16 - b * 4, 16 * a_error + 4 * b_error)
+}
+
+fn ln2_fixed(scale: u64) -> (BigInt, u64) {
+    // ln 2 = 2 atanh(1/3)
+    let (a, error) = atan_inverse(3, scale, true);
+    (a * 2, 2 * error)
+}
+
+// exp of the fixed point `x` (off by `x_error`), relative to the size of the result
+fn exp_fixed(x: &BigInt, x_error: &BigInt, scale: u64) -> Result<Approx, EquationError> {
+    // x = k ln2 + r, so exp(x) = 2^k exp(r) with |r| <= ln2 / 2
+    let (ln2, _) = ln2_fixed(scale + GUARD);
+    let k: BigInt = ((x << (GUARD + 1)) + &ln2) / (&ln2 * 2);
+    let k = k
+        .to_i64()
+        .filter(|k| k.abs() < 1 << 60)
+        .ok_or_else(overflow)?;
+    let r = x - ((&ln2 * k) >> GUARD);
+    let mut sum = one(scale);
+    let mut term = one(scale);
+    let mut n = 1u64;
+    while !term.is_zero() {
+        term = fixed_mul(&term, &r, scale) / n;
+        sum += &term;
+        n += 1;
+    }
+    // exp(r) < 1.5, so the error of r grows by at most half again
+    let error: BigInt = x_error * 2 + 3 * n + 4;
+    Ok(Approx {
+        mantissa: sum,
+        exponent: k - scale as i64,
+        error,
+    })
+}
+
+// ln of `x`, scaled by 2^scale
+fn ln_fixed(x: &BigFloat, scale: u64) -> (BigInt, BigInt) {
+    // x = m 2^k with m near 1, so ln(x) = k ln2 + ln(m)
+    let mut k = x.magnitude();
+    let mut m = BigFloat::new(x.mantissa.clone(), x.exponent - k);
+    // m is in [0.5, 1), get it into [0.707, 1.414)
+    if m.to_fixed(8) < BigInt::from(181) {
+        m.exponent += 1;
+        k -= 1;
+    }
+    let wide = scale + GUARD;
+    let m = m.to_fixed(wide);
+    // ln(m) = 2 atanh(z) with z = (m - 1) / (m + 1), which is below 0.18
+    let z = ((&m - one(wide)) << wide) / (&m + one(wide));
+    let square = fixed_mul(&z, &z, wide);
+    let mut term = z;
+    let mut sum = BigInt::zero();
+    let mut n = 0u64;
+    while !term.is_zero() {
+        sum += &term / (2 * n + 1);
+        term = fixed_mul(&term, &square, wide);
+        n += 1;
+    }
+    // k is below 2^63, so ln2 with another GUARD bits keeps the error of k ln2 below one unit
+    let (ln2, _) = ln2_fixed(wide + GUARD);
+    let value = (sum * 2 + ((ln2 * k) >> GUARD)) >> GUARD;
+    (value, BigInt::from(2))
+}
+
+fn ln_approx(x: &BigFloat, scale: u64) -> Approx {
+    let (value, error) = ln_fixed(x, scale);
+    Approx::fixed(value, scale, error)
+}
+
+// sin(x) and cos(x) scaled by 2^scale, both off by at most the returned error
+fn sin_cos_fixed(x: &BigFloat, scale: u64) -> (BigInt, BigInt, BigInt) {
+    // x = k pi/2 + r, with |r| <= pi/4
+    let wide = scale + GUARD + x.magnitude().max(0) as u64;
+    let (pi, _) = pi_fixed(wide);
+    let half_pi = pi >> 1u64;
+    let fixed = x.to_fixed(wide);
+    let k = floor_div(&((&fixed << 1u64) + &half_pi), &(&half_pi << 1u64));
+    let r = (fixed - &k * &half_pi) >> (wide - scale - 32);
+    let scale = scale + 32;
+    let square = fixed_mul(&r, &r, scale);
+    let (mut sin, mut cos) = (r.clone(), one(scale));
+    let (mut sin_term, mut cos_term) = (r, one(scale));
+    let mut n = 1u64;
+    while !sin_term.is_zero() || !cos_term.is_zero() {
+        sin_term = -fixed_mul(&sin_term, &square, scale) / ((2 * n) * (2 * n + 1));
+        cos_term = -fixed_mul(&cos_term, &square, scale) / ((2 * n - 1) * (2 * n));
+        sin += &sin_term;
+        cos += &cos_term;
+        n += 1;
+    }
+    let (sin, cos) = (sin >> 32u64, cos >> 32u64);
+    let quadrant = (k % 4u32 + 4u32) % 4u32;
+    let (sin, cos) = match quadrant.to_u32() {
+        Some(0) => (sin, cos),
+        Some(1) => (cos, -sin),
+        Some(2) => (-sin, -cos),
+        _ => (-cos, sin),
+    };
+    (sin, cos, BigInt::from(2))
+}
+
+fn floor_div(a: &BigInt, b: &BigInt) -> BigInt {
+    let quotient = a / b;
+    if (a.is_negative() != b.is_negative()) && &quotient * b != *a {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+// pi scaled by 2^scale, off by at most one unit
+fn pi_at(scale: u64) -> BigInt {
+    pi_fixed(scale + GUARD).0 >> GUARD
+}
+
+// atan of the fixed point `x` with |x| <= 1, scaled by 2^scale
+fn atan_fixed(x: BigInt, scale: u64) -> BigInt {
+    let wide = scale + GUARD;
+    let mut x = x << GUARD;
+    // atan(x) = 2 atan(x / (1 + sqrt(1 + x^2))), twice takes |x| below 0.2
+    for _ in 0..2 {
+        let root = ((one(wide) << wide) + &x * &x).sqrt();
+        x = (x << wide) / (one(wide) + root);
+    }
+    let square = fixed_mul(&x, &x, wide);
+    let mut term = x;
+    let mut sum = BigInt::zero();
+    let mut n = 0u64;
+    while !term.is_zero() {
+        sum += &term / (2 * n + 1);
+        term = -fixed_mul(&term, &square, wide);
+        n += 1;
+    }
+    (sum * 4) >> GUARD
+}
+
+fn atan_approx(x: &BigFloat, scale: u64) -> Approx {
+    let value = if x.cmp_abs(&BigFloat::from_int(1)) != Ordering::Greater {
+        atan_fixed(x.to_fixed(scale), scale)
+    } else {
+        // atan(x) = pi/2 - atan(1/x) for positive x
+        let wide = scale + GUARD;
+        let inverse = (one(wide) << wide) / x.to_fixed(wide);
+        let inverse = atan_fixed(inverse >> GUARD, scale);
+        let half_pi = pi_at(scale) >> 1u64;
+        if x.is_negative() {
+            -half_pi - inverse
+        } else {
+            half_pi - inverse
+        }
+    };
+    Approx::fixed(value, scale, 4)
+}
+
+fn asin_fixed(x: &BigFloat, bits: u64, scale: u64) -> Result<BigInt, EquationError> {
+    // asin(x) = 2 atan(x / (1 + sqrt(1 - x^2))), where the atan argument stays within [-1, 1]
+    let one_minus_square = add(
+        &BigFloat::from_int(1),
+        &mul(x, x, 2 * bits + 2).neg(),
+        2 * bits + 2 * scale,
+    );
+    let root = sqrt(&one_minus_square, scale + GUARD)?.to_fixed(scale + GUARD);
+    let wide = scale + GUARD;
+    let ratio = (x.to_fixed(wide) << wide) / (one(wide) + root);
+    Ok(atan_fixed(ratio >> GUARD, scale) * 2)
+}
+
+fn pow_integer(base: &BigFloat, exp: i64, bits: u64) -> Result<BigFloat, EquationError> {
+    let mantissa = base.mantissa.pow(exp.unsigned_abs() as u32);
+    let power = BigFloat::new(mantissa, base.exponent * exp.abs());
+    if exp < 0 {
+        div(&BigFloat::from_int(1), &power, bits)
+    } else {
+        Ok(round(power.mantissa, power.exponent, bits))
+    }
+}
+
+// a + b and a * b without rounding
+fn exact_add(a: &BigFloat, b: &BigFloat) -> BigFloat {
+    if a.is_zero() || b.is_zero() {
+        return if a.is_zero() { b.clone() } else { a.clone() };
+    }
+    let e = a.exponent.min(b.exponent);
+    BigFloat::new(
+        (&a.mantissa << (a.exponent - e) as u64) + (&b.mantissa << (b.exponent - e) as u64),
+        e,
+    )
+}
+
+fn exact_mul(a: &BigFloat, b: &BigFloat) -> BigFloat {
+    BigFloat::new(&a.mantissa * &b.mantissa, a.exponent + b.exponent)
+}
+
+// the largest integer not above x
+fn floor(x: &BigFloat) -> BigInt {
+    x.to_fixed(0)
+}
+
+// x rounded towards zero
+fn truncate(x: &BigFloat) -> BigInt {
+    if x.is_negative() {
+        -floor(&x.neg())
+    } else {
+        floor(x)
+    }
+}
+
+// e^x as a fixed point number, x being the fixed point `x` off by `x_error`
+fn exp_at(x: &BigInt, x_error: &BigInt, scale: u64) -> Result<(BigInt, BigInt), EquationError> {
+    let exp = exp_fixed(x, x_error, scale)?;
+    let shift = exp.exponent + scale as i64;
+    Ok(if shift >= 0 {
+        (exp.mantissa << shift as u64, exp.error << shift as u64)
+    } else {
+        (
+            exp.mantissa >> (-shift) as u64,
+            (exp.error >> (-shift) as u64) + 1,
+        )
+    })
+}
+
+// e^x - e^-x and e^x + e^-x scaled by 2^scale, both off by at most the returned error
+fn exp_sum_difference(x: &BigFloat, scale: u64) -> Result<(BigInt, BigInt, BigInt), EquationError> {
+    let fixed = x.to_fixed(scale);
+    let (up, up_error) = exp_at(&fixed, &BigInt::one(), scale)?;
+    let (down, down_error) = exp_at(&-fixed, &BigInt::one(), scale)?;
+    Ok((&up - &down, up + down, up_error + down_error))
+}
+
+// the tangent numbers 1, 2, 16, 272, ... up to the nth, which give the bernoulli numbers
+fn tangent_numbers(n: usize) -> Vec<BigInt> {
+    let mut tangent = vec![BigInt::one(); n];
+    for k in 1..n {
+        tangent[k] = &tangent[k - 1] * k;
+    }
+    for k in 1..n {
+        for j in k..n {
+            tangent[j] = &tangent[j - 1] * (j - k) + &tangent[j] * (j - k + 2);
+        }
+    }
+    tangent
+}
+
+// ln(gamma(x)) for x > 0, scaled by 2^scale
+fn ln_gamma_positive(x: &BigFloat, scale: u64) -> (BigInt, BigInt) {
+    let wide = scale + GUARD;
+    // stirling's series needs x to be large, so gamma(x) = gamma(x + n) / (x (x + 1) ... (x + n - 1))
+    let target = BigInt::from(wide / 2 + 16);
+    let shifts = (target - floor(x)).to_u64().unwrap_or(0);
+    let mut z = x.clone();
+    let mut product = BigFloat::from_int(1);
+    for _ in 0..shifts {
+        product = exact_mul(&product, &z);
+        z = exact_add(&z, &BigFloat::from_int(1));
+    }
+    // (z - 1/2) ln(z) - z + ln(2 pi) / 2 + sum of B_2k / (2k (2k - 1) z^(2k - 1))
+    let precise = wide + z.magnitude() as u64;
+    let (ln_z, _) = ln_fixed(&z, precise);
+    let half = exact_add(&z, &BigFloat::new(BigInt::from(-1), -1));
+    let mut sum =
+        BigFloat::new(ln_z * &half.mantissa, half.exponent - precise as i64).to_fixed(wide);
+    sum -= z.to_fixed(wide);
+    let pi = BigFloat::new(pi_fixed(wide + GUARD).0, -((wide + GUARD) as i64));
+    let (ln_pi, _) = ln_fixed(&pi, wide);
+    let (ln2, ln2_error) = ln2_fixed(wide);
+    sum += (ln_pi + ln2) >> 1u64;
+    // B_2k / (2k (2k - 1)) = (-1)^(k - 1) T_k / (4^k (4^k - 1) (2k - 1))
+    let terms = (wide / 8 + 16) as usize;
+    let tangent = tangent_numbers(terms);
+    let square = &z.mantissa * &z.mantissa;
+    let mut power = z.mantissa.clone();
+    let mut error = BigInt::from(8 + ln2_error);
+    for (k, tangent) in tangent.iter().enumerate() {
+        let k = k as u64 + 1;
+        let four = BigInt::one() << (2 * k);
+        let mut denom = (&four - 1) * four * (2 * k - 1) * &power;
+        let shift = z.exponent * (2 * k as i64 - 1);
+        let mut numer = tangent << wide;
+        if shift >= 0 {
+            denom <<= shift as u64;
+        } else {
+            numer <<= (-shift) as u64;
+        }
+        let term: BigInt = numer / denom;
+        if term.is_zero() {
+            break;
+        }
+        if k % 2 == 1 {
+            sum += term;
+        } else {
+            sum -= term;
+        }
+        error += 1;
+        power *= &square;
+    }
+    if shifts > 0 {
+        let (ln_product, _) = ln_fixed(&product, wide);
+        sum -= ln_product;
+    }
+    (sum >> GUARD, (error >> GUARD) + 2)
+}
+
+// ln|gamma(x)| scaled by 2^scale for any x that isn't a pole, and whether gamma(x) is negative
+fn ln_gamma_fixed(x: &BigFloat, scale: u64) -> Result<(BigInt, BigInt, bool), EquationError> {
+    let half = BigFloat::new(BigInt::one(), -1);
+    if x.is_negative() || x.cmp_abs(&half) == Ordering::Less {
+        // the reflection formula: gamma(x) gamma(1 - x) = pi / sin(pi x)
+        let wide = scale + GUARD;
+        let precise = wide + GUARD + x.magnitude().max(0) as u64;
+        let pi_x = BigFloat::new(
+            pi_fixed(precise).0 * &x.mantissa,
+            x.exponent - precise as i64,
+        );
+        let (sin, _, _) = sin_cos_fixed(&pi_x, wide);
+        // the error of sin is two units, plus one from pi x
+        let sin_error = BigInt::from(3);
+        if sin.abs() <= sin_error {
+            return Ok((BigInt::zero(), one(2 * scale), false));
+        }
+        let (ln_sin, _) = ln_fixed(&BigFloat::new(sin.abs(), -(wide as i64)), wide);
+        let ln_sin_error = ((&sin_error << wide) / (sin.abs() - &sin_error)) + 2;
+        let pi = BigFloat::new(pi_fixed(wide + GUARD).0, -((wide + GUARD) as i64));
+        let (ln_pi, _) = ln_fixed(&pi, wide);
+        let (ln_gamma, error) =
+            ln_gamma_positive(&exact_add(&BigFloat::from_int(1), &x.neg()), wide);
+        let value = (ln_pi - ln_sin - ln_gamma) >> GUARD;
+        let error = ((ln_sin_error + error) >> GUARD) + 2;
+        return Ok((value, error, sin.is_negative()));
+    }
+    let (value, error) = ln_gamma_positive(x, scale);
+    Ok((value, error, false))
+}
+
+// erf(x) for x >= 0, scaled by 2^scale
+fn erf_fixed(x: &BigFloat, scale: u64) -> Result<(BigInt, BigInt), EquationError> {
+    // erf(x) = 2/sqrt(pi) e^(-x^2) sum 2^n x^(2n + 1) / (1 3 5 ... (2n + 1)), where every term is positive.
+    // the terms grow until n is about x^2, the extra bits cover the error that builds up over all of them
+    let magnitude = x.magnitude().max(0) as u64;
+    let terms = 4 * (1u64 << (2 * magnitude)) + scale + 64;
+    let extra = 2 * (64 - terms.leading_zeros() as u64) + 2 * magnitude + 16;
+    let wide = scale + GUARD + extra;
+    let fixed = x.to_fixed(wide);
+    let square = fixed_mul(&fixed, &fixed, wide);
+    let mut term = fixed.clone();
+    let mut sum = fixed;
+    let mut n = 0u64;
+    while !term.is_zero() {
+        n += 1;
+        term = fixed_mul(&term, &square, wide) * 2 / (2 * n + 1);
+        sum += &term;
+    }
+    // squaring the truncated x is off by about 2x units
+    let square_error = (BigInt::one() << (magnitude + 1)) + 2;
+    let exp = exp_fixed(&-square, &square_error, wide)?;
+    // 2 / sqrt(pi), and e^(-x^2) is below one so its exponent isn't above -wide
+    let root_pi = (pi_fixed(2 * wide).0).sqrt();
+    let factor = (BigInt::from(2) << (2 * wide)) / root_pi;
+    let shift = (wide as i64 - exp.exponent) as u64;
+    let value = (exp.mantissa * sum * factor) >> shift;
+    let error = exp.error * 4 + 4 * n + 16;
+    Ok((value >> (GUARD + extra), (error >> (GUARD + extra)) + 4))
+}
+
+fn not_real(what: &str) -> EquationError {
+    unsupported(format!("The {} is not a real number", what))
+}
+
+/// Evaluates with [`BigFloat`]s of a fixed number of bits, correctly rounding every step.
+#[derive(Debug)]
+pub(crate) struct PrecisionEvaluator {
+    bits: u64,
+}
+
+impl PrecisionEvaluator {
+    pub(crate) fn new(bits: u64) -> Result<Self, EquationError> {
+        if bits < 2 {
+            return Err(unsupported(
+                "The precision must be at least 2 bits".to_string(),
+            ));
+        }
+        Ok(PrecisionEvaluator { bits })
+    }
+    fn pi(&self) -> Result<BigFloat, EquationError> {
+        correctly_rounded(self.bits, |scale| {
+            let (pi, error) = pi_fixed(scale);
+            Ok(Approx::fixed(pi, scale, error))
+        })
+    }
+    fn ln(&self, x: &BigFloat) -> Result<BigFloat, EquationError> {
+        if x.is_negative() || x.is_zero() {
+            return Err(not_real("logarithm of a number that is not positive"));
+        }
+        if *x == BigFloat::from_int(1) {
+            return Ok(BigFloat::zero());
+        }
+        correctly_rounded(self.bits, |scale| Ok(ln_approx(x, scale)))
+    }
+    fn log10(&self, x: &BigFloat) -> Result<BigFloat, EquationError> {
+        self.ln(x)?;
+        // the only exact results are for powers of ten
+        let (mut power, mut five) = (0i64, BigInt::one());
+        while five.bits() < x.mantissa.bits() {
+            five *= 5;
+            power += 1;
+        }
+        if five == x.mantissa && x.exponent == power {
+            return Ok(BigFloat::from_int(power));
+        }
+        let ten = BigFloat::from_int(10);
+        correctly_rounded(self.bits, |scale| {
+            let (ln, ln_error) = ln_fixed(x, scale);
+            let (ln_ten, ln_ten_error) = ln_fixed(&ten, scale);
+            Ok(fixed_div(&ln, &ln_error, &ln_ten, &ln_ten_error, scale))
+        })
+    }
+    fn exp(&self, x: &BigFloat) -> Result<BigFloat, EquationError> {
+        if x.is_zero() {
+            return Ok(BigFloat::from_int(1));
+        }
+        if x.magnitude() > 62 {
+            return Err(overflow());
+        }
+        correctly_rounded(self.bits, |scale| {
+            exp_fixed(&x.to_fixed(scale + GUARD), &BigInt::one(), scale + GUARD)
+        })
+    }
+    fn sin_cos(
+        &self,
+        x: &BigFloat,
+        pick: impl Fn(BigInt, BigInt, BigInt, u64) -> Approx,
+    ) -> Result<BigFloat, EquationError> {
+        correctly_rounded(self.bits, |scale| {
+            let (sin, cos, error) = sin_cos_fixed(x, scale);
+            Ok(pick(sin, cos, error, scale))
+        })
+    }
+    fn apply(&self, func: &FunctionalOperator, x: &BigFloat) -> Result<BigFloat, EquationError> {
+        let unit = BigFloat::from_int(1);
+        let odd = matches!(
+            func,
+            FunctionalOperator::Sin
+                | FunctionalOperator::Tan
+                | FunctionalOperator::Arcsin
+                | FunctionalOperator::Arctan
+                | FunctionalOperator::Sinh
+                | FunctionalOperator::Tanh
+                | FunctionalOperator::Arcsinh
+                | FunctionalOperator::Arctanh
+        );
+        // the exact results at zero would never settle
+        if x.is_zero() {
+            match func {
+                _ if odd => return Ok(BigFloat::zero()),
+                FunctionalOperator::Erf => return Ok(BigFloat::zero()),
+                FunctionalOperator::Cos
+                | FunctionalOperator::Sec
+                | FunctionalOperator::Cosh
+                | FunctionalOperator::Erfc => return Ok(unit),
+                FunctionalOperator::Cot | FunctionalOperator::Csc => return Err(division_by_zero()),
+                FunctionalOperator::Arccot => {
+                    let pi = self.pi()?;
+                    return Ok(BigFloat::new(pi.mantissa, pi.exponent - 1));
+                }
+                _ => {}
+            }
+        }
+        // these are x plus something in x^3, which is too small to change the rounding of a tiny x
+        if odd && x.magnitude() * 2 < -(self.bits as i64) - 8 {
+            return Ok(x.clone());
+        }
+        match func {
+            FunctionalOperator::Log => self.log10(x),
+            FunctionalOperator::Ln => self.ln(x),
+            FunctionalOperator::Sin => {
+                self.sin_cos(x, |sin, _, error, scale| Approx::fixed(sin, scale, error))
+            }
+            FunctionalOperator::Cos => {
+                self.sin_cos(x, |_, cos, error, scale| Approx::fixed(cos, scale, error))
+            }
+            FunctionalOperator::Tan => self.sin_cos(x, |sin, cos, error, scale| {
+                fixed_div(&sin, &error, &cos, &error, scale)
+            }),
+            FunctionalOperator::Cot => self.sin_cos(x, |sin, cos, error, scale| {
+                fixed_div(&cos, &error, &sin, &error, scale)
+            }),
+            FunctionalOperator::Sec => self.sin_cos(x, |_, cos, error, scale| {
+                fixed_div(&one(scale), &BigInt::zero(), &cos, &error, scale)
+            }),
+            FunctionalOperator::Csc => self.sin_cos(x, |sin, _, error, scale| {
+                fixed_div(&one(scale), &BigInt::zero(), &sin, &error, scale)
+            }),
+            FunctionalOperator::Arctan => {
+                correctly_rounded(self.bits, |scale| Ok(atan_approx(x, scale)))
+            }
+            FunctionalOperator::Arcsin | FunctionalOperator::Arccos => {
+                if x.cmp_abs(&unit) == Ordering::Greater {
+                    return Err(not_real(
+                        "inverse sine or cosine of a number outside [-1, 1]",
+                    ));
+                }
+                if *func == FunctionalOperator::Arccos && *x == unit {
+                    return Ok(BigFloat::zero());
+                }
+                let bits = self.bits;
+                correctly_rounded(bits, |scale| {
+                    let asin = asin_fixed(x, bits, scale)?;
+                    if *func == FunctionalOperator::Arcsin {
+                        return Ok(Approx::fixed(asin, scale, 16));
+                    }
+                    Ok(Approx::fixed((pi_at(scale) >> 1u64) - asin, scale, 16))
+                })
+            }
+            // atan(1/x) = pi/2 - atan(x) for positive x and -pi/2 - atan(x) for negative x
+            FunctionalOperator::Arccot => correctly_rounded(self.bits, |scale| {
+                let atan = atan_approx(x, scale);
+                let half_pi = pi_at(scale) >> 1u64;
+                let half_pi = if x.is_negative() { -half_pi } else { half_pi };
+                Ok(Approx::fixed(
+                    half_pi - atan.mantissa,
+                    scale,
+                    atan.error + 1,
+                ))
+            }),
+            FunctionalOperator::Arcsec | FunctionalOperator::Arccsc => {
+                if x.cmp_abs(&unit) == Ordering::Less {
+                    return Err(not_real(
+                        "inverse secant or cosecant of a number inside (-1, 1)",
+                    ));
+                }
+                if *func == FunctionalOperator::Arcsec && *x == unit {
+                    return Ok(BigFloat::zero());
+                }
+                // acos(1/x) = atan(sqrt(x^2 - 1)) and asin(1/x) = pi/2 - atan(sqrt(x^2 - 1)) for positive x,
+                // which doesn't lose precision near 1 like taking the reciprocal does
+                correctly_rounded(self.bits, |scale| {
+                    let square = exact_add(&exact_mul(x, x), &unit.neg());
+                    let atan = atan_approx(&sqrt(&square, scale + GUARD)?, scale);
+                    let error = atan.error + 1;
+                    let value = match (func, x.is_negative()) {
+                        (FunctionalOperator::Arcsec, false) => atan.mantissa,
+                        (FunctionalOperator::Arcsec, true) => pi_at(scale) - atan.mantissa,
+                        (_, false) => (pi_at(scale) >> 1u64) - atan.mantissa,
+                        (_, true) => atan.mantissa - (pi_at(scale) >> 1u64),
+                    };
+                    Ok(Approx::fixed(value, scale, error))
+                })
+            }
+            FunctionalOperator::Sqrt => self.root(&BigFloat::from_int(2), x),
+            FunctionalOperator::Cbrt => self.root(&BigFloat::from_int(3), x),
+            FunctionalOperator::Sinh => correctly_rounded(self.bits, |scale| {
+                let (difference, _, error) = exp_sum_difference(x, scale + GUARD)?;
+                Ok(Approx::fixed(difference >> 1u64, scale + GUARD, error + 1))
+            }),
+            FunctionalOperator::Cosh => correctly_rounded(self.bits, |scale| {
+                let (_, sum, error) = exp_sum_difference(x, scale + GUARD)?;
+                Ok(Approx::fixed(sum >> 1u64, scale + GUARD, error + 1))
+            }),
+            FunctionalOperator::Tanh => correctly_rounded(self.bits, |scale| {
+                let (difference, sum, error) = exp_sum_difference(x, scale + GUARD)?;
+                Ok(fixed_div(&difference, &error, &sum, &error, scale))
+            }),
+            FunctionalOperator::Arcsinh => {
+                // asinh(x) = ln(|x| + sqrt(x^2 + 1)), with the sign of x
+                let value = correctly_rounded(self.bits, |scale| {
+                    let precise = scale + GUARD;
+                    let square = exact_add(&exact_mul(x, x), &unit);
+                    let inner = add(&x.abs(), &sqrt(&square, precise)?, precise);
+                    Ok(ln_approx(&inner, scale))
+                })?;
+                Ok(if x.is_negative() { value.neg() } else { value })
+            }
+            FunctionalOperator::Arccosh => {
+                if x.is_negative() || x.cmp_abs(&unit) == Ordering::Less {
+                    return Err(not_real("inverse hyperbolic cosine of a number below 1"));
+                }
+                if *x == unit {
+                    return Ok(BigFloat::zero());
+                }
+                // acosh(x) = ln(x + sqrt(x^2 - 1))
+                correctly_rounded(self.bits, |scale| {
+                    let precise = scale + GUARD;
+                    let square = exact_add(&exact_mul(x, x), &unit.neg());
+                    let inner = add(x, &sqrt(&square, precise)?, precise);
+                    Ok(ln_approx(&inner, scale))
+                })
+            }
+            FunctionalOperator::Arctanh => {
+                if x.cmp_abs(&unit) != Ordering::Less {
+                    return Err(not_real(
+                        "inverse hyperbolic tangent of a number outside (-1, 1)",
+                    ));
+                }
+                // atanh(x) = ln((1 + x) / (1 - x)) / 2
+                correctly_rounded(self.bits, |scale| {
+                    let ratio = div(
+                        &exact_add(&unit, x),
+                        &exact_add(&unit, &x.neg()),
+                        scale + GUARD,
+                    )?;
+                    // halving is one more bit of scale
+                    let (ln, error) = ln_fixed(&ratio, scale);
+                    Ok(Approx::fixed(ln, scale + 1, error + 1))
+                })
+            }
+            FunctionalOperator::Abs => Ok(x.abs()),
+            FunctionalOperator::Sign => Ok(BigFloat::from_int(
+                x.mantissa.signum().to_i64().unwrap_or(0),
+            )),
+            FunctionalOperator::Floor => Ok(round(floor(x), 0, self.bits)),
+            FunctionalOperator::Ceil => Ok(round(-floor(&x.neg()), 0, self.bits)),
+            FunctionalOperator::Trunc => Ok(round(truncate(x), 0, self.bits)),
+            // halfway cases go away from zero
+            FunctionalOperator::Round => {
+                let half = BigFloat::new(BigInt::one(), -1);
+                let rounded = floor(&exact_add(&x.abs(), &half));
+                let rounded = if x.is_negative() { -rounded } else { rounded };
+                Ok(round(rounded, 0, self.bits))
+            }
+            FunctionalOperator::Frac => Ok(exact_add(x, &BigFloat::new(-truncate(x), 0))),
+            FunctionalOperator::Exp => self.exp(x),
+            FunctionalOperator::Log2 => {
+                self.ln(x)?;
+                // the only exact results are for powers of two
+                if x.mantissa.is_one() {
+                    return Ok(BigFloat::from_int(x.exponent));
+                }
+                correctly_rounded(self.bits, |scale| {
+                    let (ln, ln_error) = ln_fixed(x, scale);
+                    let (ln2, ln2_error) = ln2_fixed(scale);
+                    Ok(fixed_div(&ln, &ln_error, &ln2, &ln2_error.into(), scale))
+                })
+            }
+            FunctionalOperator::Gamma => self.gamma(x),
+            FunctionalOperator::Lgamma => {
+                if x.is_integer() && (x.is_negative() || x.is_zero()) {
+                    return Err(division_by_zero());
+                }
+                // ln(gamma(1)) and ln(gamma(2)) are exactly zero
+                if *x == unit || *x == BigFloat::from_int(2) {
+                    return Ok(BigFloat::zero());
+                }
+                correctly_rounded(self.bits, |scale| {
+                    let (value, error, _) = ln_gamma_fixed(x, scale)?;
+                    Ok(Approx::fixed(value, scale, error))
+                })
+            }
+            FunctionalOperator::Factorial => self.gamma(&exact_add(x, &unit)),
+            FunctionalOperator::Erf => {
+                // erf(x) is within 2^-bits of 1 once e^(-x^2) is
+                if x.magnitude() > 0 && exact_mul(x, x).to_fixed(0) > BigInt::from(self.bits + 4) {
+                    return Ok(if x.is_negative() { unit.neg() } else { unit });
+                }
+                let value = correctly_rounded(self.bits, |scale| {
+                    let (value, error) = erf_fixed(&x.abs(), scale)?;
+                    Ok(Approx::fixed(value, scale, error))
+                })?;
+                Ok(if x.is_negative() { value.neg() } else { value })
+            }
+            FunctionalOperator::Erfc => {
+                if x.is_negative() {
+                    return correctly_rounded(self.bits, |scale| {
+                        let (value, error) = erf_fixed(&x.abs(), scale)?;
+                        Ok(Approx::fixed(one(scale) + value, scale, error))
+                    });
+                }
+                if x.magnitude() > 6 {
+                    return Err(unsupported(
+                        "The complementary error function of numbers from 64 up is too small to compute"
+                            .to_string(),
+                    ));
+                }
+                // erfc(x) is about e^(-x^2), so 1 - erf(x) needs that many more bits
+                let square: BigInt = exact_mul(x, x).to_fixed(0);
+                let cancelled = (square * 3u32 / 2u32).to_u64().unwrap_or(0) + 8;
+                correctly_rounded(self.bits, |scale| {
+                    let wide = scale + cancelled;
+                    let (value, error) = erf_fixed(x, wide)?;
+                    Ok(Approx::fixed(one(wide) - value, wide, error))
+                })
+            }
+        }
+    }
+    fn gamma(&self, x: &BigFloat) -> Result<BigFloat, EquationError> {
+        if x.is_integer() {
+            if !x.is_negative() && !x.is_zero() {
+                // (n - 1)! is exact, and the ziv loop would never settle on it
+                if let Some(n) = x.to_i64().filter(|n| *n <= 10_000) {
+                    let product = (2..n).fold(BigInt::one(), |product, k| product * k);
+                    return Ok(round(product, 0, self.bits));
+                }
+            } else {
+                return Err(division_by_zero());
+            }
+        }
+        correctly_rounded(self.bits, |scale| {
+            let wide = scale + GUARD;
+            let (value, error, negative) = ln_gamma_fixed(x, wide)?;
+            let mut exp = exp_fixed(&value, &error, wide)?;
+            if negative {
+                exp.mantissa = -exp.mantissa;
+            }
+            Ok(exp)
+        })
+    }
+    fn pow(&self, base: &BigFloat, exp: &BigFloat) -> Result<BigFloat, EquationError> {
+        if let Some(n) = exp.to_i64() {
+            // exact powers get rounded once, as long as they're a reasonable size
+            if n.unsigned_abs() <= 1 << 20 && base.mantissa.bits() * n.unsigned_abs() <= 1 << 22 {
+                if base.is_zero() && n < 0 {
+                    return Err(division_by_zero());
+                }
+                return pow_integer(base, n, self.bits);
+            }
+        }
+        if base.is_zero() {
+            return if exp.is_negative() {
+                Err(division_by_zero())
+            } else {
+                Ok(BigFloat::zero())
+            };
+        }
+        let negate = if base.is_negative() {
+            match exp.to_i64() {
+                Some(n) => n % 2 != 0,
+                // a normalised mantissa is odd, so only an exponent of zero gives an odd integer
+                None if exp.is_integer() => exp.exponent == 0,
+                None => return Err(not_real("fractional power of a negative number")),
+            }
+        } else {
+            false
+        };
+        let base = base.abs();
+        let val = correctly_rounded(self.bits, |scale| {
+            // x^y = exp(y ln(x)), the error of ln(x) grows with y
+            let wide = scale + GUARD + exp.magnitude().max(0) as u64;
+            let (ln, ln_error) = ln_fixed(&base, wide);
+            let product =
+                BigFloat::new(ln * &exp.mantissa, exp.exponent - wide as i64).to_fixed(wide);
+            let error = ln_error * exp.mantissa.abs();
+            let error = BigFloat::new(error, exp.exponent).to_fixed(0) + 2;
+            exp_fixed(&product, &error, wide)
+        })?;
+        Ok(if negate { val.neg() } else { val })
+    }
+    fn root(&self, degree: &BigFloat, x: &BigFloat) -> Result<BigFloat, EquationError> {
+        if *degree == BigFloat::from_int(2) {
+            return sqrt(x, self.bits);
+        }
+        if degree.is_zero() {
+            return Err(division_by_zero());
+        }
+        if x.is_negative() {
+            // odd roots of negative numbers are real
+            return match degree.to_i64() {
+                Some(n) if n % 2 != 0 => Ok(self.root(degree, &x.neg())?.neg()),
+                _ => Err(not_real("even root of a negative number")),
+            };
+        }
+        if x.is_zero() {
+            return Ok(BigFloat::zero());
+        }
+        let approximate = |scale: u64| {
+            // x^(1/n) = exp(ln(x) / n)
+            let wide = scale + GUARD;
+            let (ln, ln_error) = ln_fixed(x, wide);
+            let degree = degree.to_fixed(wide);
+            let exponent = fixed_div(&ln, &ln_error, &degree, &BigInt::one(), wide);
+            exp_fixed(&exponent.mantissa, &exponent.error, wide)
+        };
+        // exact roots would never settle, so check for them first
+        if let Some(n) = degree.to_i64().filter(|n| *n > 0 && *n <= 1 << 16) {
+            let approx = approximate(self.bits + 32)?;
+            let candidate = round(approx.mantissa, approx.exponent, self.bits);
+            if pow_integer(&candidate, n, u64::MAX)? == *x {
+                return Ok(candidate);
+            }
+        }
+        correctly_rounded(self.bits, approximate)
+    }
+}
+
+impl Evaluator for PrecisionEvaluator {
+    type Value = BigFloat;
+    // goes through the shortest decimal that round trips, so the literal `0.1` is one tenth and not the f64 closest to it
+    fn value(&self, value: f64) -> Result<BigFloat, EquationError> {
+        if !value.is_finite() {
+            return Err(not_real(&format!("value {}", value)));
+        }
+        let decimal = value.to_string();
+        let (whole, fraction) = decimal.split_once('.').unwrap_or((&decimal, ""));
+        let numer: BigInt = format!("{}{}", whole, fraction)
+            .parse()
+            .map_err(|_| overflow())?;
+        let denom = BigInt::from(10u32).pow(fraction.len() as u32);
+        div(
+            &BigFloat::new(numer, 0),
+            &BigFloat::new(denom, 0),
+            self.bits,
+        )
+    }
+    fn constant(&self, constant: &Constant) -> Result<BigFloat, EquationError> {
+        match constant {
+            Constant::Pi => self.pi(),
+            Constant::Tau => {
+                let pi = self.pi()?;
+                Ok(BigFloat::new(pi.mantissa, pi.exponent + 1))
+            }
+            Constant::E => self.exp(&BigFloat::from_int(1)),
+            Constant::Deg => correctly_rounded(self.bits, |scale| {
+                let (pi, error) = pi_fixed(scale);
+                Ok(Approx::fixed(pi / 180, scale, error / 180 + 1))
+            }),
+            Constant::Custom(value) => self.value(*value),
+            Constant::Unit(unit) => self.value(unit.factor()),
+        }
+    }
+    fn variable(&self, _name: &str) -> Option<BigFloat> {
+        None
+    }
+    fn negate(&self, x: BigFloat) -> Result<BigFloat, EquationError> {
+        Ok(x.neg())
+    }
+    fn function(&self, func: &FunctionalOperator, x: BigFloat) -> Result<BigFloat, EquationError> {
+        self.apply(func, &x)
+    }
+    fn operator(
+        &self,
+        op: &LeftAssociativeOperator,
+        lhs: BigFloat,
+        rhs: BigFloat,
+    ) -> Result<BigFloat, EquationError> {
+        match op {
+            LeftAssociativeOperator::Add => Ok(add(&lhs, &rhs, self.bits)),
+            LeftAssociativeOperator::Subtract => Ok(add(&lhs, &rhs.neg(), self.bits)),
+            LeftAssociativeOperator::Multiply => Ok(mul(&lhs, &rhs, self.bits)),
+            LeftAssociativeOperator::Divide => div(&lhs, &rhs, self.bits),
+            LeftAssociativeOperator::Power => self.pow(&lhs, &rhs),
+            LeftAssociativeOperator::Root => self.root(&lhs, &rhs),
+        }
+    }
+}
+
+impl From<BigFloat> for f64 {
+    fn from(val: BigFloat) -> Self {
+        val.to_f64()
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    constants::Constants,
+    equation::Group,
+    error::{EquationError, EquationErrorType},
+    item::Item,
+    numeric::NumericEvaluator,
+    parse::ParseStream,
+};
+
+// a single statement, which assigns its value to `name` if it has one
+#[derive(Debug)]
+struct Statement {
+    name: Option<String>,
+    group: Group,
+}
+
+/// A program is a list of statements separated by `;` or new lines (i.e. `a = x + 1; b = a^2; a * b`).
+/// A statement can assign its value to a name (`a = x + 1` or `let a = x + 1`), which the statements after it can use, and the value of the program is the value of its last statement.
+#[derive(Debug)]
+pub struct Program {
+    statements: Vec<Statement>,
+}
+
+impl Program {
+    /// Parses a program from a string, with the default [`Constants`].
+    pub fn new(s: impl Into<String>) -> Result<Program, EquationError> {
+        Program::with_constants(s, &Constants::default())
+    }
+    /// Parses a program from a string, where the names in `constants` are constants and can't be assigned to.
+    pub fn with_constants(
+        s: impl Into<String>,
+        constants: &Constants,
+    ) -> Result<Program, EquationError> {
+        let parse =
+            |s: &str| ParseStream::with_constants(s.to_string(), constants.clone()).parse_items();
+        let mut statements = Vec::new();
+        for statement in s.into().split([';', '\n']) {
+            if statement.trim().is_empty() {
+                continue;
+            }
+            let (name, expression) = match statement.split_once('=') {
+                Some((name, expression)) => {
+                    let name = name.trim();
+                    let name = name.strip_prefix("let ").unwrap_or(name);
+                    // only a name that would otherwise be a variable can be assigned to
+                    match parse(name)?.as_slice() {
+                        [Item::Variable(name)] => (Some(name.clone()), expression),
+                        _ => {
+                            return Err(EquationError::new(
+                                format!("Can't assign to {}", name.trim()),
+                                EquationErrorType::UnexpectedToken,
+                            ))
+                        }
+                    }
+                }
+                None => (None, statement),
+            };
+            statements.push(Statement {
+                name,
+                group: parse(expression)?.into(),
+            });
+        }
+        if statements.is_empty() {
+            return Err(EquationError::new(
+                "Empty program".to_string(),
+                EquationErrorType::MissingItems,
+            ));
+        }
+        Ok(Program { statements })
+    }
+    /// Evaluates the program, with `bindings` giving the values of the variables that aren't assigned in it.
+    pub fn evaluate(&self, bindings: &HashMap<&str, f64>) -> Result<f64, EquationError> {
+        self.run(bindings).map(|(_, value)| value)
+    }
+    /// Evaluates the program and gives the value of every name it assigns, as of the end of the program.
+    pub fn values(
+        &self,
+        bindings: &HashMap<&str, f64>,
+    ) -> Result<HashMap<String, f64>, EquationError> {
+        self.run(bindings).map(|(values, _)| values)
+    }
+    /// Gives a HashSet of all the variables that are used before (or without) being assigned, which need to be bound to evaluate the program.
+    pub fn list_vars(&self) -> HashSet<String> {
+        let mut assigned = HashSet::new();
+        let mut vars = HashSet::new();
+        for statement in self.statements.iter() {
+            vars.extend(statement.group.list_vars().difference(&assigned).cloned());
+            if let Some(name) = &statement.name {
+                assigned.insert(name.clone());
+            }
+        }
+        vars
+    }
+    // evaluates every statement in order, giving the assigned values and the value of the last statement
+    fn run(
+        &self,
+        bindings: &HashMap<&str, f64>,
+    ) -> Result<(HashMap<String, f64>, f64), EquationError> {
+        let mut values: HashMap<String, f64> = HashMap::new();
+        let mut last = 0.0;
+        for statement in self.statements.iter() {
+            // assigned names shadow the bindings
+            let mut scope = bindings.clone();
+            scope.extend(values.iter().map(|(name, value)| (name.as_str(), *value)));
+            last = statement
+                .group
+                .evaluate_with(&NumericEvaluator::with_bindings(&scope))?;
+            if let Some(name) = &statement.name {
+                values.insert(name.clone(), last);
+            }
+        }
+        Ok((values, last))
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    error::EquationError,
+    item::{FunctionalOperator, LeftAssociativeOperator},
+    numeric::{division_by_zero, factorial, integer_root, overflow, unsupported, Numeric},
+};
+
+/// An exact fraction, used to evaluate equations without rounding (i.e. for money).
+/// Every operation is checked and gives an [`Overflow`](crate::EquationErrorType::Overflow) error instead of rounding.
+/// Literals are stored as f64 and rounded to it before they become fractions, through the shortest decimal that gives the same f64. So `0.1` is exactly 1/10, but a literal with more than 15 significant digits (i.e. `12345678901234567890123`) can become a different fraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: i128,
+    denom: i128,
+}
+
+impl Rational {
+    /// Creates a new rational from a numerator and a denominator, reduced to its lowest terms.
+    pub fn new(numer: i128, denom: i128) -> Result<Rational, EquationError> {
+        if denom == 0 {
+            return Err(division_by_zero());
+        }
+        let gcd = gcd(numer, denom);
+        let (mut numer, mut denom) = (numer / gcd, denom / gcd);
+        if denom < 0 {
+            numer = numer.checked_neg().ok_or_else(overflow)?;
+            denom = denom.checked_neg().ok_or_else(overflow)?;
+        }
+        Ok(Rational { numer, denom })
+    }
+    /// Gives the numerator (which carries the sign).
+    pub fn numer(&self) -> i128 {
+        self.numer
+    }
+    /// Gives the denominator (which is always positive).
+    pub fn denom(&self) -> i128 {
+        self.denom
+    }
+    /// Gives the closest f64 to the rational.
+    pub fn to_f64(&self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+    fn pow(self, exp: u32) -> Result<Rational, EquationError> {
+        Rational::new(
+            self.numer.checked_pow(exp).ok_or_else(overflow)?,
+            self.denom.checked_pow(exp).ok_or_else(overflow)?,
+        )
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    // only zero has a gcd of zero, and it's denominator is never zero
+    a.max(1) as i128
+}
+
+impl Numeric for Rational {
+    // goes through the shortest decimal that round trips, so the literal `0.1` is exactly 1/10, the digits the f64 lost aren't recovered
+    fn from_f64(value: f64) -> Result<Self, EquationError> {
+        if !value.is_finite() {
+            return Err(unsupported(format!("{} is not a rational number", value)));
+        }
+        let decimal = value.to_string();
+        let (whole, fraction) = decimal.split_once('.').unwrap_or((&decimal, ""));
+        let denom = 10i128
+            .checked_pow(fraction.len() as u32)
+            .ok_or_else(overflow)?;
+        let numer: i128 = format!("{}{}", whole, fraction)
+            .parse()
+            .map_err(|_| overflow())?;
+        Rational::new(numer, denom)
+    }
+    fn negate(self) -> Result<Self, EquationError> {
+        Rational::new(self.numer.checked_neg().ok_or_else(overflow)?, self.denom)
+    }
+    fn function(func: &FunctionalOperator, x: Self) -> Result<Self, EquationError> {
+        let root = |n| Rational::operator(&LeftAssociativeOperator::Root, Rational::new(n, 1)?, x);
+        let integer = |n: i128| Rational::new(n, 1);
+        let (numer, denom) = (x.numer, x.denom);
+        match func {
+            FunctionalOperator::Sqrt => root(2),
+            FunctionalOperator::Cbrt => root(3),
+            FunctionalOperator::Abs => {
+                Rational::new(numer.checked_abs().ok_or_else(overflow)?, denom)
+            }
+            FunctionalOperator::Sign => integer(numer.signum()),
+            FunctionalOperator::Floor => integer(numer.div_euclid(denom)),
+            FunctionalOperator::Ceil => integer(-(-numer).div_euclid(denom)),
+            FunctionalOperator::Trunc => integer(numer / denom),
+            // (2n ± d) / 2d, truncated, moves halfway cases away from zero
+            FunctionalOperator::Round => {
+                let twice = numer.checked_mul(2).ok_or_else(overflow)?;
+                let shifted = twice
+                    .checked_add(numer.signum() * denom)
+                    .ok_or_else(overflow)?;
+                integer(shifted / denom.checked_mul(2).ok_or_else(overflow)?)
+            }
+            FunctionalOperator::Frac => Rational::new(numer % denom, denom),
+            FunctionalOperator::Factorial if denom == 1 => integer(factorial(numer)?),
+            _ => Err(unsupported(format!(
+                "{:?} is not defined for rational numbers",
+                func
+            ))),
+        }
+    }
+    fn operator(op: &LeftAssociativeOperator, lhs: Self, rhs: Self) -> Result<Self, EquationError> {
+        let mul = |a: i128, b: i128| a.checked_mul(b).ok_or_else(overflow);
+        match op {
+            LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => {
+                let right = mul(rhs.numer, lhs.denom)?;
+                let left = mul(lhs.numer, rhs.denom)?;
+                let numer = if *op == LeftAssociativeOperator::Add {
+                    left.checked_add(right)
+                } else {
+                    left.checked_sub(right)
+                };
+                Rational::new(numer.ok_or_else(overflow)?, mul(lhs.denom, rhs.denom)?)
+            }
+            LeftAssociativeOperator::Multiply => {
+                Rational::new(mul(lhs.numer, rhs.numer)?, mul(lhs.denom, rhs.denom)?)
+            }
+            LeftAssociativeOperator::Divide => {
+                Rational::new(mul(lhs.numer, rhs.denom)?, mul(lhs.denom, rhs.numer)?)
+            }
+            LeftAssociativeOperator::Power if rhs.denom == 1 => {
+                let exp = u32::try_from(rhs.numer.unsigned_abs()).map_err(|_| overflow())?;
+                let val = lhs.pow(exp)?;
+                if rhs.numer < 0 {
+                    Rational::new(val.denom, val.numer)
+                } else {
+                    Ok(val)
+                }
+            }
+            // a fractional power is an integer power of a root
+            LeftAssociativeOperator::Power => {
+                let root = Rational::operator(
+                    &LeftAssociativeOperator::Root,
+                    Rational::new(rhs.denom, 1)?,
+                    lhs,
+                )?;
+                Rational::operator(
+                    &LeftAssociativeOperator::Power,
+                    root,
+                    Rational::new(rhs.numer, 1)?,
+                )
+            }
+            LeftAssociativeOperator::Root => {
+                let not_rational =
+                    || unsupported(format!("The root {} of {} is not rational", lhs, rhs));
+                if lhs.denom != 1 {
+                    return Err(not_rational());
+                }
+                Rational::new(
+                    integer_root(rhs.numer, lhs.numer).ok_or_else(not_rational)?,
+                    integer_root(rhs.denom, lhs.numer).ok_or_else(not_rational)?,
+                )
+            }
+        }
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Rational { numer: 0, denom: 1 }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
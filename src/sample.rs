@@ -0,0 +1,95 @@
+use crate::error::EquationError;
+
+// how many times the gap between two evenly spaced points can be halved
+const MAX_DEPTH: u32 = 8;
+// how far the middle of a gap can be from the line between its ends, as a fraction of the height of the samples
+const FLATNESS: f64 = 0.002;
+
+/// Samples `f` at `n` evenly spaced points from `a` to `b`, adding points wherever it bends sharply or jumps between the even ones.
+/// A reversed range (i.e. `5.0..=0.0`) is sampled from low to high and given from `a` to `b`, and a range with an end that isn't finite has no samples.
+pub(crate) fn sample(
+    f: impl Fn(f64) -> Result<f64, EquationError>,
+    a: f64,
+    b: f64,
+    n: usize,
+) -> Vec<(f64, Result<f64, EquationError>)> {
+    if !a.is_finite() || !b.is_finite() {
+        return Vec::new();
+    }
+    if a > b && n > 1 {
+        let mut samples = sample(f, b, a, n);
+        samples.reverse();
+        return samples;
+    }
+    let even: Vec<(f64, Result<f64, EquationError>)> = match n {
+        0 => Vec::new(),
+        1 => vec![(a, f(a))],
+        _ => (0..n)
+            .map(|i| {
+                let x = if i == n - 1 {
+                    b
+                } else {
+                    a + (b - a) * i as f64 / (n - 1) as f64
+                };
+                (x, f(x))
+            })
+            .collect(),
+    };
+    let (lo, hi) = even
+        .iter()
+        .filter_map(|(_, y)| y.as_ref().ok().filter(|y| y.is_finite()))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), y| {
+            (lo.min(*y), hi.max(*y))
+        });
+    let height = if hi > lo { hi - lo } else { 1.0 };
+    let mut samples = Vec::with_capacity(even.len());
+    let mut points = even.into_iter();
+    let Some(mut previous) = points.next() else {
+        return samples;
+    };
+    for next in points {
+        let (x0, y0) = (previous.0, finite(&previous.1));
+        let (x1, y1) = (next.0, finite(&next.1));
+        samples.push(previous);
+        refine(&f, (x0, y0), (x1, y1), height, 0, &mut samples);
+        previous = next;
+    }
+    samples.push(previous);
+    samples
+}
+
+// the value where it is a finite number
+fn finite(y: &Result<f64, EquationError>) -> Option<f64> {
+    y.as_ref().ok().copied().filter(|y| y.is_finite())
+}
+
+// adds the points between two neighbours in order, halving the gap while its middle is off the line between them
+fn refine(
+    f: &impl Fn(f64) -> Result<f64, EquationError>,
+    (x0, y0): (f64, Option<f64>),
+    (x1, y1): (f64, Option<f64>),
+    height: f64,
+    depth: u32,
+    samples: &mut Vec<(f64, Result<f64, EquationError>)>,
+) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    let middle = x0 / 2.0 + x1 / 2.0;
+    if middle <= x0 || middle >= x1 {
+        return;
+    }
+    let value = f(middle);
+    let y = finite(&value);
+    let steep = match (y0, y, y1) {
+        (Some(y0), Some(y), Some(y1)) => (y - (y0 + y1) / 2.0).abs() > FLATNESS * height,
+        // the edge of where the equation is defined
+        (None, None, None) => false,
+        _ => true,
+    };
+    if steep {
+        refine(f, (x0, y0), (middle, y), height, depth + 1, samples);
+        samples.push((middle, value));
+        refine(f, (middle, y), (x1, y1), height, depth + 1, samples);
+    }
+}
@@ -0,0 +1,490 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    constants::Constants,
+    equation::Equation,
+    error::EquationError,
+    item::{Constant, FunctionalOperator, LeftAssociativeOperator},
+    numeric::{unsupported, Evaluator, Numeric},
+    rational::Rational,
+};
+
+/// How a [`System`] was solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveMethod {
+    /// The system is linear, and was solved exactly with gaussian elimination.
+    Elimination,
+    /// The system was solved with newton's method, starting from the initial guess.
+    Newton,
+}
+
+/// The solution of a [`System`], along with how well it converged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    values: HashMap<String, f64>,
+    iterations: usize,
+    residual: f64,
+    converged: bool,
+    method: SolveMethod,
+}
+
+impl Solution {
+    /// Gives the value found for every unknown.
+    pub fn values(&self) -> &HashMap<String, f64> {
+        &self.values
+    }
+    /// Gives the value found for an unknown.
+    pub fn get(&self, unknown: &str) -> Option<f64> {
+        self.values.get(unknown).copied()
+    }
+    /// Gives the number of newton steps taken, which is zero for linear systems.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+    /// Gives the largest difference between the two sides of any equation at the solution.
+    pub fn residual(&self) -> f64 {
+        self.residual
+    }
+    /// Checks if the residual got within the tolerance of the system. When it didn't, the values are the best that were found.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+    /// Gives how the system was solved.
+    pub fn method(&self) -> SolveMethod {
+        self.method
+    }
+}
+
+/// A system of simultaneous equations (i.e. `x + y = 10` and `x*y = 21`), which is solved for every variable in it.
+#[derive(Debug)]
+pub struct System {
+    // lhs - rhs of every equation
+    residuals: Vec<Equation>,
+    unknowns: Vec<String>,
+    // the coefficients and constants of every equation, when the system is linear
+    linear: Option<Vec<Affine>>,
+    tolerance: f64,
+    max_iterations: usize,
+}
+
+impl System {
+    /// Creates a system from equations of the form `lhs = rhs` (see [`Equation::from_equality`]).
+    pub fn new<S: AsRef<str>>(
+        equations: impl IntoIterator<Item = S>,
+    ) -> Result<System, EquationError> {
+        System::with_constants(equations, &Constants::default())
+    }
+    /// Creates a system like [`new`](Self::new), where the names in `constants` are constants and every other name is an unknown.
+    pub fn with_constants<S: AsRef<str>>(
+        equations: impl IntoIterator<Item = S>,
+        constants: &Constants,
+    ) -> Result<System, EquationError> {
+        let residuals = equations
+            .into_iter()
+            .map(|equation| Equation::from_equality_with_constants(equation.as_ref(), constants))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut unknowns: Vec<String> = residuals
+            .iter()
+            .flat_map(Equation::list_vars)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        unknowns.sort();
+        let evaluator = AffineEvaluator {
+            unknowns: &unknowns,
+        };
+        let linear = residuals
+            .iter()
+            .map(|residual| residual.evaluate_with(&evaluator))
+            .collect::<Result<Vec<_>, _>>()
+            .ok();
+        Ok(System {
+            residuals,
+            unknowns,
+            linear,
+            tolerance: 1e-9,
+            max_iterations: 100,
+        })
+    }
+    /// Gives the unknowns of the system in alphabetical order, which are all the variables in its equations.
+    pub fn unknowns(&self) -> &[String] {
+        &self.unknowns
+    }
+    /// Checks if every equation is linear in the unknowns, in which case the system is solved exactly.
+    pub fn is_linear(&self) -> bool {
+        self.linear.is_some()
+    }
+    /// Sets the largest residual newton's method accepts as a solution, which is `1e-9` by default.
+    pub fn set_tolerance(&mut self, tolerance: f64) -> &mut Self {
+        self.tolerance = tolerance;
+        self
+    }
+    /// Sets the most steps newton's method takes, which is 100 by default.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) -> &mut Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+    /// Solves the system. Linear systems with as many equations as unknowns are solved exactly and ignore the initial guess.
+    /// Otherwise newton's method starts from the initial guess, where unknowns that aren't given start at 1.
+    pub fn solve(&self, initial_guess: &HashMap<&str, f64>) -> Result<Solution, EquationError> {
+        if let Some(linear) = &self.linear {
+            if linear.len() == self.unknowns.len() {
+                if let Some(x) = solve_rational(linear)
+                    .or_else(|| solve_f64(&to_f64_matrix(linear), self.unknowns.len()))
+                {
+                    let residual = max_abs(&self.residuals_at(&x)?);
+                    return Ok(self.solution(x, 0, residual, true, SolveMethod::Elimination));
+                }
+                return Err(unsupported("The system has no unique solution".to_string()));
+            }
+        }
+        self.newton(initial_guess)
+    }
+    fn newton(&self, initial_guess: &HashMap<&str, f64>) -> Result<Solution, EquationError> {
+        let mut x: Vec<f64> = self
+            .unknowns
+            .iter()
+            .map(|unknown| initial_guess.get(unknown.as_str()).copied().unwrap_or(1.0))
+            .collect();
+        let mut f = self.residuals_at(&x)?;
+        let mut iterations = 0;
+        while max_abs(&f) > self.tolerance && iterations < self.max_iterations {
+            iterations += 1;
+            let step = self.newton_step(&x, &f)?;
+            // halve the step until it gets closer to a root, so that a bad guess can't send it off
+            let norm = sum_squares(&f);
+            let mut scale = 1.0;
+            let mut next = None;
+            for _ in 0..32 {
+                let candidate: Vec<f64> = x.iter().zip(&step).map(|(x, d)| x + scale * d).collect();
+                if let Ok(candidate_f) = self.residuals_at(&candidate) {
+                    if sum_squares(&candidate_f) < norm {
+                        next = Some((candidate, candidate_f));
+                        break;
+                    }
+                }
+                scale /= 2.0;
+            }
+            match next {
+                Some((candidate, candidate_f)) => {
+                    x = candidate;
+                    f = candidate_f;
+                }
+                // no step makes it any better, so this is as close as it gets
+                None => break,
+            }
+        }
+        let residual = max_abs(&f);
+        Ok(self.solution(
+            x,
+            iterations,
+            residual,
+            residual <= self.tolerance,
+            SolveMethod::Newton,
+        ))
+    }
+    // solves J dx = -f, or the least squares version of it when the system isn't square
+    fn newton_step(&self, x: &[f64], f: &[f64]) -> Result<Vec<f64>, EquationError> {
+        let n = x.len();
+        let jacobian = self.jacobian(x)?;
+        let mut matrix: Vec<Vec<f64>> = if f.len() == n {
+            jacobian
+                .iter()
+                .zip(f)
+                .map(|(row, f)| row.iter().copied().chain([-f]).collect())
+                .collect()
+        } else {
+            normal_equations(&jacobian, f)
+        };
+        if let Some(step) = solve_f64(&matrix, n) {
+            return Ok(step);
+        }
+        // a singular jacobian gets a little damping, like levenberg-marquardt
+        if f.len() == n {
+            matrix = normal_equations(&jacobian, f);
+        }
+        let largest = (0..n).map(|i| matrix[i][i].abs()).fold(1.0, f64::max);
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] += largest * 1e-8;
+        }
+        solve_f64(&matrix, n)
+            .ok_or_else(|| unsupported("The jacobian of the system is singular".to_string()))
+    }
+    // central differences, with a step that balances truncation and rounding
+    fn jacobian(&self, x: &[f64]) -> Result<Vec<Vec<f64>>, EquationError> {
+        let mut jacobian = vec![vec![0.0; x.len()]; self.residuals.len()];
+        for j in 0..x.len() {
+            let h = f64::EPSILON.cbrt() * x[j].abs().max(1.0);
+            let mut above = x.to_vec();
+            above[j] += h;
+            let mut below = x.to_vec();
+            below[j] -= h;
+            let (above, below) = (self.residuals_at(&above)?, self.residuals_at(&below)?);
+            for (i, row) in jacobian.iter_mut().enumerate() {
+                row[j] = (above[i] - below[i]) / (2.0 * h);
+            }
+        }
+        Ok(jacobian)
+    }
+    fn residuals_at(&self, x: &[f64]) -> Result<Vec<f64>, EquationError> {
+        let bindings: HashMap<&str, f64> = self
+            .unknowns
+            .iter()
+            .map(String::as_str)
+            .zip(x.iter().copied())
+            .collect();
+        self.residuals
+            .iter()
+            .map(|residual| residual.evaluate_with_bindings(&bindings))
+            .collect()
+    }
+    fn solution(
+        &self,
+        x: Vec<f64>,
+        iterations: usize,
+        residual: f64,
+        converged: bool,
+        method: SolveMethod,
+    ) -> Solution {
+        Solution {
+            values: self.unknowns.iter().cloned().zip(x).collect(),
+            iterations,
+            residual,
+            converged,
+            method,
+        }
+    }
+}
+
+fn max_abs(values: &[f64]) -> f64 {
+    // NaN is never small enough to be a solution
+    values
+        .iter()
+        .map(|value| {
+            if value.is_nan() {
+                f64::INFINITY
+            } else {
+                value.abs()
+            }
+        })
+        .fold(0.0, f64::max)
+}
+
+fn sum_squares(values: &[f64]) -> f64 {
+    values.iter().map(|value| value * value).sum()
+}
+
+// the augmented matrix of J^T J dx = -J^T f
+fn normal_equations(jacobian: &[Vec<f64>], f: &[f64]) -> Vec<Vec<f64>> {
+    let n = jacobian.first().map_or(0, Vec::len);
+    (0..n)
+        .map(|i| {
+            let mut row: Vec<f64> = (0..n)
+                .map(|j| jacobian.iter().map(|row| row[i] * row[j]).sum())
+                .collect();
+            row.push(
+                -jacobian
+                    .iter()
+                    .zip(f)
+                    .map(|(row, f)| row[i] * f)
+                    .sum::<f64>(),
+            );
+            row
+        })
+        .collect()
+}
+
+// gaussian elimination with partial pivoting on an augmented n by n + 1 matrix
+pub(crate) fn solve_f64(matrix: &[Vec<f64>], n: usize) -> Option<Vec<f64>> {
+    let mut matrix = matrix.to_vec();
+    let largest = matrix
+        .iter()
+        .flat_map(|row| row[..n].iter())
+        .fold(0.0, |largest: f64, value| largest.max(value.abs()));
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        })?;
+        if matrix[pivot][column].abs() <= largest * n as f64 * f64::EPSILON {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        let (above, below) = matrix.split_at_mut(column + 1);
+        let pivot = &above[column];
+        for row in below.iter_mut() {
+            let factor = row[column] / pivot[column];
+            for (value, pivot) in row[column..].iter_mut().zip(&pivot[column..]) {
+                *value -= factor * pivot;
+            }
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| matrix[row][k] * x[k]).sum();
+        x[row] = (matrix[row][n] - sum) / matrix[row][row];
+    }
+    Some(x)
+}
+
+// exact gaussian elimination, which gives up if the fractions overflow
+fn solve_rational(linear: &[Affine]) -> Option<Vec<f64>> {
+    let n = linear.len();
+    let (add, sub, mul, div) = (
+        |a, b| Rational::operator(&LeftAssociativeOperator::Add, a, b).ok(),
+        |a, b| Rational::operator(&LeftAssociativeOperator::Subtract, a, b).ok(),
+        |a, b| Rational::operator(&LeftAssociativeOperator::Multiply, a, b).ok(),
+        |a, b| Rational::operator(&LeftAssociativeOperator::Divide, a, b).ok(),
+    );
+    let zero = Rational::default();
+    // a x + c = 0 is a x = -c
+    let mut matrix: Vec<Vec<Rational>> = linear
+        .iter()
+        .map(|row| {
+            let mut row_values = row.coefficients.clone();
+            row_values.push(row.constant.negate().ok()?);
+            Some(row_values)
+        })
+        .collect::<Option<_>>()?;
+    for column in 0..n {
+        let pivot = (column..n).find(|row| matrix[*row][column] != zero)?;
+        matrix.swap(column, pivot);
+        let (above, below) = matrix.split_at_mut(column + 1);
+        let pivot = &above[column];
+        for row in below.iter_mut() {
+            let factor = div(row[column], pivot[column])?;
+            for (value, pivot) in row[column..].iter_mut().zip(&pivot[column..]) {
+                *value = sub(*value, mul(factor, *pivot)?)?;
+            }
+        }
+    }
+    let mut x = vec![zero; n];
+    for row in (0..n).rev() {
+        let mut sum = zero;
+        for k in row + 1..n {
+            sum = add(sum, mul(matrix[row][k], x[k])?)?;
+        }
+        x[row] = div(sub(matrix[row][n], sum)?, matrix[row][row])?;
+    }
+    Some(x.iter().map(Rational::to_f64).collect())
+}
+
+fn to_f64_matrix(linear: &[Affine]) -> Vec<Vec<f64>> {
+    linear
+        .iter()
+        .map(|row| {
+            row.coefficients
+                .iter()
+                .map(Rational::to_f64)
+                .chain([-row.constant.to_f64()])
+                .collect()
+        })
+        .collect()
+}
+
+// a linear combination of the unknowns plus a constant
+#[derive(Debug, Clone)]
+struct Affine {
+    constant: Rational,
+    coefficients: Vec<Rational>,
+}
+
+impl Affine {
+    fn is_constant(&self) -> bool {
+        self.coefficients.iter().all(|c| c.numer() == 0)
+    }
+    fn map(
+        &self,
+        f: impl Fn(Rational) -> Result<Rational, EquationError>,
+    ) -> Result<Affine, EquationError> {
+        Ok(Affine {
+            constant: f(self.constant)?,
+            coefficients: self
+                .coefficients
+                .iter()
+                .map(|c| f(*c))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+// evaluates an equation as an affine function of the unknowns, failing if it isn't one
+#[derive(Debug)]
+struct AffineEvaluator<'a> {
+    unknowns: &'a [String],
+}
+
+impl AffineEvaluator<'_> {
+    fn constant_value(&self, value: Rational) -> Affine {
+        Affine {
+            constant: value,
+            coefficients: vec![Rational::default(); self.unknowns.len()],
+        }
+    }
+}
+
+fn nonlinear() -> EquationError {
+    unsupported("The equation is not linear".to_string())
+}
+
+impl Evaluator for AffineEvaluator<'_> {
+    type Value = Affine;
+    fn value(&self, value: f64) -> Result<Affine, EquationError> {
+        Ok(self.constant_value(Rational::from_f64(value)?))
+    }
+    fn constant(&self, constant: &Constant) -> Result<Affine, EquationError> {
+        self.value(constant.value())
+    }
+    fn variable(&self, name: &str) -> Option<Affine> {
+        let index = self.unknowns.iter().position(|unknown| unknown == name)?;
+        let mut value = self.constant_value(Rational::default());
+        value.coefficients[index] = Rational::new(1, 1).ok()?;
+        Some(value)
+    }
+    fn negate(&self, x: Affine) -> Result<Affine, EquationError> {
+        x.map(Rational::negate)
+    }
+    fn function(&self, func: &FunctionalOperator, x: Affine) -> Result<Affine, EquationError> {
+        if !x.is_constant() {
+            return Err(nonlinear());
+        }
+        // functions rational numbers can't represent exactly are rounded like any other literal
+        let value = Rational::function(func, x.constant)
+            .or_else(|_| Rational::from_f64(func.evaluate(x.constant.to_f64())))?;
+        Ok(self.constant_value(value))
+    }
+    fn operator(
+        &self,
+        op: &LeftAssociativeOperator,
+        lhs: Affine,
+        rhs: Affine,
+    ) -> Result<Affine, EquationError> {
+        match op {
+            LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => Ok(Affine {
+                constant: Rational::operator(op, lhs.constant, rhs.constant)?,
+                coefficients: lhs
+                    .coefficients
+                    .iter()
+                    .zip(&rhs.coefficients)
+                    .map(|(a, b)| Rational::operator(op, *a, *b))
+                    .collect::<Result<_, _>>()?,
+            }),
+            LeftAssociativeOperator::Multiply if lhs.is_constant() => {
+                rhs.map(|c| Rational::operator(op, lhs.constant, c))
+            }
+            LeftAssociativeOperator::Multiply | LeftAssociativeOperator::Divide
+                if rhs.is_constant() =>
+            {
+                lhs.map(|c| Rational::operator(op, c, rhs.constant))
+            }
+            _ if lhs.is_constant() && rhs.is_constant() => {
+                let value = Rational::operator(op, lhs.constant, rhs.constant).or_else(|_| {
+                    Rational::from_f64(op.eval(lhs.constant.to_f64(), rhs.constant.to_f64()))
+                })?;
+                Ok(self.constant_value(value))
+            }
+            _ => Err(nonlinear()),
+        }
+    }
+}
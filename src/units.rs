@@ -0,0 +1,457 @@
+use std::{
+    f64::consts::PI,
+    fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    error::{EquationError, EquationErrorType},
+    item::{float_bits, Constant, FunctionalOperator, LeftAssociativeOperator},
+    numeric::{overflow, Numeric},
+};
+
+// the base units, in the order of the exponents of a dimension
+const BASE: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+// the named units, with their size in base units, their dimension and whether they take an SI prefix
+const UNITS: [(&str, f64, [i8; 7], bool); 32] = [
+    ("m", 1.0, [1, 0, 0, 0, 0, 0, 0], true),
+    ("g", 1e-3, [0, 1, 0, 0, 0, 0, 0], true),
+    ("s", 1.0, [0, 0, 1, 0, 0, 0, 0], true),
+    ("A", 1.0, [0, 0, 0, 1, 0, 0, 0], true),
+    ("K", 1.0, [0, 0, 0, 0, 1, 0, 0], true),
+    ("mol", 1.0, [0, 0, 0, 0, 0, 1, 0], true),
+    ("cd", 1.0, [0, 0, 0, 0, 0, 0, 1], true),
+    ("N", 1.0, [1, 1, -2, 0, 0, 0, 0], true),
+    ("J", 1.0, [2, 1, -2, 0, 0, 0, 0], true),
+    ("W", 1.0, [2, 1, -3, 0, 0, 0, 0], true),
+    ("Pa", 1.0, [-1, 1, -2, 0, 0, 0, 0], true),
+    ("Hz", 1.0, [0, 0, -1, 0, 0, 0, 0], true),
+    ("C", 1.0, [0, 0, 1, 1, 0, 0, 0], true),
+    ("V", 1.0, [2, 1, -3, -1, 0, 0, 0], true),
+    ("Ohm", 1.0, [2, 1, -3, -2, 0, 0, 0], true),
+    ("Ω", 1.0, [2, 1, -3, -2, 0, 0, 0], true),
+    ("L", 1e-3, [3, 0, 0, 0, 0, 0, 0], true),
+    ("l", 1e-3, [3, 0, 0, 0, 0, 0, 0], true),
+    ("eV", 1.602_176_634e-19, [2, 1, -2, 0, 0, 0, 0], true),
+    ("min", 60.0, [0, 0, 1, 0, 0, 0, 0], false),
+    ("h", 3600.0, [0, 0, 1, 0, 0, 0, 0], false),
+    ("day", 86400.0, [0, 0, 1, 0, 0, 0, 0], false),
+    ("rad", 1.0, [0; 7], false),
+    ("deg", PI / 180.0, [0; 7], false),
+    ("in", 0.0254, [1, 0, 0, 0, 0, 0, 0], false),
+    ("ft", 0.3048, [1, 0, 0, 0, 0, 0, 0], false),
+    ("mi", 1609.344, [1, 0, 0, 0, 0, 0, 0], false),
+    ("lb", 0.453_592_37, [0, 1, 0, 0, 0, 0, 0], false),
+    ("t", 1000.0, [0, 1, 0, 0, 0, 0, 0], false),
+    ("bar", 1e5, [-1, 1, -2, 0, 0, 0, 0], false),
+    ("atm", 101_325.0, [-1, 1, -2, 0, 0, 0, 0], false),
+    ("mph", 0.44704, [1, 0, -1, 0, 0, 0, 0], false),
+];
+
+// `da` comes before `d` so that it is matched first
+const PREFIXES: [(&str, f64); 21] = [
+    ("Y", 1e24),
+    ("Z", 1e21),
+    ("E", 1e18),
+    ("P", 1e15),
+    ("T", 1e12),
+    ("G", 1e9),
+    ("M", 1e6),
+    ("k", 1e3),
+    ("h", 1e2),
+    ("da", 1e1),
+    ("d", 1e-1),
+    ("c", 1e-2),
+    ("m", 1e-3),
+    ("u", 1e-6),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+    ("f", 1e-15),
+    ("a", 1e-18),
+    ("z", 1e-21),
+];
+
+/// The dimension of a quantity, as the exponents of the seven SI base units (m, kg, s, A, K, mol and cd).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Dimension([i8; 7]);
+
+impl Dimension {
+    /// The dimension of pure numbers.
+    pub fn dimensionless() -> Dimension {
+        Dimension([0; 7])
+    }
+    /// Checks if this is the dimension of pure numbers.
+    pub fn is_dimensionless(&self) -> bool {
+        self.0 == [0; 7]
+    }
+    /// Gives the exponents of m, kg, s, A, K, mol and cd.
+    pub fn exponents(&self) -> [i8; 7] {
+        self.0
+    }
+    // the dimension of a product (sign 1) or a quotient (sign -1)
+    fn combine(self, other: Dimension, sign: i8) -> Option<Dimension> {
+        let mut exponents = self.0;
+        for (exponent, other) in exponents.iter_mut().zip(other.0) {
+            *exponent = exponent.checked_add(other.checked_mul(sign)?)?;
+        }
+        Some(Dimension(exponents))
+    }
+    // the dimension of a power, if every exponent stays an integer
+    fn power(self, n: f64) -> Option<Dimension> {
+        let mut exponents = self.0;
+        for exponent in exponents.iter_mut().filter(|exponent| **exponent != 0) {
+            let scaled = *exponent as f64 * n;
+            if scaled.fract() != 0.0 || scaled.abs() > i8::MAX as f64 {
+                return None;
+            }
+            *exponent = scaled as i8;
+        }
+        Some(Dimension(exponents))
+    }
+}
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_dimensionless() {
+            return write!(f, "1");
+        }
+        let mut first = true;
+        for (name, exponent) in BASE.iter().zip(self.0) {
+            if exponent == 0 {
+                continue;
+            }
+            if !first {
+                write!(f, " ")?;
+            }
+            first = false;
+            match exponent {
+                1 => write!(f, "{}", name)?,
+                _ => write!(f, "{}^{}", name, exponent)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A unit of measurement, as a multiple of the SI base units.
+/// Units are equal when they are the same size, whatever name they were written with.
+#[derive(Debug, Clone)]
+pub struct Unit {
+    factor: f64,
+    dimension: Dimension,
+    name: String,
+}
+
+impl PartialEq for Unit {
+    fn eq(&self, other: &Self) -> bool {
+        float_bits(self.factor) == float_bits(other.factor) && self.dimension == other.dimension
+    }
+}
+
+impl Eq for Unit {}
+
+impl Hash for Unit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        float_bits(self.factor).hash(state);
+        self.dimension.hash(state);
+    }
+}
+
+impl Unit {
+    /// Parses a unit such as `km/h`, `kg*m^2/s^2`, `1/s` or `1` (for dimensionless values). Units can have an SI prefix (i.e. `mm` or `GHz`).
+    pub fn parse(s: &str) -> Result<Unit, EquationError> {
+        let unknown = || {
+            EquationError::new(
+                format!("Unknown unit {}", s),
+                EquationErrorType::UnexpectedToken,
+            )
+        };
+        let trimmed = s.trim();
+        if trimmed == "1" {
+            return Ok(Unit::one());
+        }
+        let (reciprocal, rest) = match trimmed.strip_prefix("1/") {
+            Some(rest) => (true, rest),
+            None => (false, trimmed),
+        };
+        let chars: Vec<char> = rest.chars().collect();
+        let unit = match parse_unit(&chars, true) {
+            Some((unit, length)) if length == chars.len() && reciprocal => {
+                Unit::one().divide(unit).ok_or_else(overflow)?
+            }
+            Some((unit, length)) if length == chars.len() => unit,
+            _ => return Err(unknown()),
+        };
+        Ok(Unit {
+            name: trimmed.to_string(),
+            ..unit
+        })
+    }
+    /// Gives the size of the unit in SI base units.
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+    /// Gives the dimension of the unit.
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+    /// Gives the unit as it was written (i.e. `km/h`).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    fn one() -> Unit {
+        Unit {
+            factor: 1.0,
+            dimension: Dimension::dimensionless(),
+            name: "1".to_string(),
+        }
+    }
+    // the name of the result is the name of `self`, which `parse_unit` replaces with what it read
+    fn multiply(self, other: Unit) -> Option<Unit> {
+        Some(Unit {
+            factor: self.factor * other.factor,
+            dimension: self.dimension.combine(other.dimension, 1)?,
+            name: self.name,
+        })
+    }
+    fn divide(self, other: Unit) -> Option<Unit> {
+        Some(Unit {
+            factor: self.factor / other.factor,
+            dimension: self.dimension.combine(other.dimension, -1)?,
+            name: self.name,
+        })
+    }
+    fn powi(self, n: i8) -> Option<Unit> {
+        Some(Unit {
+            factor: self.factor.powi(n as i32),
+            dimension: self.dimension.power(n as f64)?,
+            name: self.name,
+        })
+    }
+}
+
+// looks up a single unit name, with or without a prefix
+fn named_unit(name: &str) -> Option<Unit> {
+    let unit = |(_, factor, dimension, _): &(&str, f64, [i8; 7], bool)| Unit {
+        factor: *factor,
+        dimension: Dimension(*dimension),
+        name: name.to_string(),
+    };
+    if let Some(named) = UNITS.iter().find(|named| named.0 == name) {
+        return Some(unit(named));
+    }
+    PREFIXES.iter().find_map(|(prefix, scale)| {
+        let rest = name.strip_prefix(prefix)?;
+        let named = UNITS.iter().find(|named| named.0 == rest && named.3)?;
+        let named = unit(named);
+        Some(Unit {
+            factor: scale * named.factor,
+            ..named
+        })
+    })
+}
+
+/// Checks if a name is a unit, with or without a prefix.
+pub(crate) fn is_unit(name: &str) -> bool {
+    named_unit(name).is_some()
+}
+
+/// Parses the unit at the start of `chars` (i.e. the `km/h` in `km/h * t`), giving the unit and the number of characters it took up.
+/// The parts of a compound unit can't have spaces between them, and the unit can't run into a name (i.e. the `m` of `m_e`).
+/// Without `products` the unit stops before a `*` (i.e. `kg*m` is just `kg`).
+pub(crate) fn parse_unit(chars: &[char], products: bool) -> Option<(Unit, usize)> {
+    let mut unit = Unit::one();
+    let mut i = 0;
+    let mut divide = false;
+    loop {
+        let start = i;
+        while chars.get(i).is_some_and(|c| c.is_alphabetic()) {
+            i += 1;
+        }
+        let name: String = chars[start..i].iter().collect();
+        let Some(mut named) = named_unit(&name) else {
+            if start == 0 {
+                return None;
+            }
+            // the operator before a name that isn't a unit isn't part of the unit
+            i = start - 1;
+            break;
+        };
+        // an integer power, i.e. `m^2` or `s^-1`
+        if chars.get(i) == Some(&'^') {
+            let negative = chars.get(i + 1) == Some(&'-');
+            let digits = i + 1 + negative as usize;
+            let mut end = digits;
+            while chars.get(end).is_some_and(|c| c.is_ascii_digit()) {
+                end += 1;
+            }
+            if end > digits {
+                let power: i8 = chars[digits..end].iter().collect::<String>().parse().ok()?;
+                named = named.powi(if negative { -power } else { power })?;
+                i = end;
+            }
+        }
+        unit = if divide {
+            unit.divide(named)?
+        } else {
+            unit.multiply(named)?
+        };
+        match chars.get(i) {
+            Some(c @ ('*' | '/'))
+                if (products || *c == '/')
+                    && chars.get(i + 1).is_some_and(|c| c.is_alphabetic()) =>
+            {
+                divide = *c == '/';
+                i += 1;
+            }
+            _ => break,
+        }
+    }
+    if chars
+        .get(i)
+        .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+    {
+        return None;
+    }
+    unit.name = chars[..i].iter().collect();
+    Some((unit, i))
+}
+
+/// A value along with its dimension, the result of [`Equation::evaluate_with_units`](crate::Equation::evaluate_with_units).
+/// The value is kept in SI base units, and adding or comparing values of different dimensions is a [`DimensionMismatch`](EquationErrorType::DimensionMismatch).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    value: f64,
+    dimension: Dimension,
+}
+
+impl Quantity {
+    /// Creates a quantity from a value in the given unit (i.e. `Quantity::new(10.0, "km/h")`).
+    pub fn new(value: f64, unit: &str) -> Result<Quantity, EquationError> {
+        let unit = Unit::parse(unit)?;
+        Ok(Quantity {
+            value: value * unit.factor,
+            dimension: unit.dimension,
+        })
+    }
+    /// Gives the value in SI base units.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+    /// Gives the dimension of the value.
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+    /// Gives the value in another unit of the same dimension.
+    pub fn convert_to(&self, unit: &str) -> Result<f64, EquationError> {
+        let unit = Unit::parse(unit)?;
+        if unit.dimension != self.dimension {
+            return Err(mismatch(format!(
+                "Can't convert {} to {}",
+                self.dimension, unit.dimension
+            )));
+        }
+        Ok(self.value / unit.factor)
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.dimension.is_dimensionless() {
+            write!(f, "{}", self.value)
+        } else {
+            write!(f, "{} {}", self.value, self.dimension)
+        }
+    }
+}
+
+fn mismatch(message: String) -> EquationError {
+    EquationError::new(message, EquationErrorType::DimensionMismatch)
+}
+
+fn dimensionless(value: f64) -> Quantity {
+    Quantity {
+        value,
+        dimension: Dimension::dimensionless(),
+    }
+}
+
+impl Numeric for Quantity {
+    fn from_f64(value: f64) -> Result<Self, EquationError> {
+        Ok(dimensionless(value))
+    }
+    fn constant(constant: &Constant) -> Result<Self, EquationError> {
+        Ok(match constant {
+            Constant::Unit(unit) => Quantity {
+                value: unit.factor,
+                dimension: unit.dimension,
+            },
+            constant => dimensionless(constant.value()),
+        })
+    }
+    fn negate(self) -> Result<Self, EquationError> {
+        Ok(Quantity {
+            value: -self.value,
+            ..self
+        })
+    }
+    fn function(func: &FunctionalOperator, x: Self) -> Result<Self, EquationError> {
+        let value = func.evaluate(x.value);
+        let dimension = match func {
+            FunctionalOperator::Abs => Some(x.dimension),
+            FunctionalOperator::Sign => Some(Dimension::dimensionless()),
+            FunctionalOperator::Sqrt => x.dimension.power(0.5),
+            FunctionalOperator::Cbrt => x.dimension.power(1.0 / 3.0),
+            _ if x.dimension.is_dimensionless() => Some(x.dimension),
+            _ => None,
+        };
+        let dimension = dimension
+            .ok_or_else(|| mismatch(format!("{:?} is not defined for {}", func, x.dimension)))?;
+        Ok(Quantity { value, dimension })
+    }
+    fn operator(op: &LeftAssociativeOperator, lhs: Self, rhs: Self) -> Result<Self, EquationError> {
+        let value = op.eval(lhs.value, rhs.value);
+        let dimension = match op {
+            LeftAssociativeOperator::Add | LeftAssociativeOperator::Subtract => {
+                if lhs.dimension != rhs.dimension {
+                    return Err(mismatch(format!(
+                        "Can't {:?} {} and {}",
+                        op, lhs.dimension, rhs.dimension
+                    )));
+                }
+                lhs.dimension
+            }
+            LeftAssociativeOperator::Multiply => lhs
+                .dimension
+                .combine(rhs.dimension, 1)
+                .ok_or_else(overflow)?,
+            LeftAssociativeOperator::Divide => lhs
+                .dimension
+                .combine(rhs.dimension, -1)
+                .ok_or_else(overflow)?,
+            LeftAssociativeOperator::Power if rhs.dimension.is_dimensionless() => {
+                lhs.dimension.power(rhs.value).ok_or_else(|| {
+                    mismatch(format!("Can't raise {} to {}", lhs.dimension, rhs.value))
+                })?
+            }
+            LeftAssociativeOperator::Root if lhs.dimension.is_dimensionless() => {
+                rhs.dimension.power(1.0 / lhs.value).ok_or_else(|| {
+                    mismatch(format!(
+                        "Can't take root {} of {}",
+                        lhs.value, rhs.dimension
+                    ))
+                })?
+            }
+            LeftAssociativeOperator::Power | LeftAssociativeOperator::Root => {
+                return Err(mismatch(format!(
+                    "The exponent of a {:?} must be dimensionless",
+                    op
+                )))
+            }
+        };
+        Ok(Quantity { value, dimension })
+    }
+}
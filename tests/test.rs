@@ -1,5 +1,10 @@
 use equation_solver::equation::Equation;
 
+// checks that `value` is within `tolerance` of `expected`, relative to it when it is larger than 1
+fn close(value: f64, expected: f64, tolerance: f64) -> bool {
+    (value - expected).abs() <= tolerance * expected.abs().max(1.0)
+}
+
 #[test]
 fn test() {
     let mut eq = Equation::new("sin (x + 132^y) / z").unwrap();
@@ -11,3 +16,1681 @@ fn test() {
 
     assert_eq!(value, Ok((3.0f64 + 132.0f64.powf(2.3)).sin() / 6.9));
 }
+
+#[test]
+fn unary_minus() {
+    assert_eq!(Equation::new("-2^2").unwrap().evaluate(), Ok(-4.0));
+    assert_eq!(Equation::new("2^-1").unwrap().evaluate(), Ok(0.5));
+    assert_eq!(Equation::new("3 * -(1 + 1)").unwrap().evaluate(), Ok(-6.0));
+    assert_eq!(
+        Equation::new("x-y")
+            .unwrap()
+            .set_value("x", 1.)
+            .set_value("y", 2.)
+            .evaluate(),
+        Ok(-1.0)
+    );
+}
+
+#[cfg(feature = "complex")]
+#[test]
+fn complex() {
+    use equation_solver::Complex64;
+
+    let check = |eq: &str, expected: Complex64| {
+        let value = Equation::new(eq).unwrap().evaluate_complex().unwrap();
+        assert!((value - expected).norm() < 1e-12, "{} = {}", eq, value);
+    };
+    check("(-1)^(1/2)", Complex64::i());
+    check("ln(-2)", Complex64::new(2f64.ln(), std::f64::consts::PI));
+    check("arcsin(2)", Complex64::new(2.0, 0.0).asin());
+    check("(-8)^(1/3)", Complex64::new(1.0, 3f64.sqrt()));
+    check("cbrt(-8)", Complex64::new(-2.0, 0.0));
+    check("cbrt(8 * i)", Complex64::new(3f64.sqrt(), 1.0));
+    check("3√-8", Complex64::new(-2.0, 0.0));
+    check("root(3, -8)", Complex64::new(-2.0, 0.0));
+    check("2√-4", Complex64::new(0.0, 2.0));
+    check("(3 + 4 * j) * i", Complex64::new(-4.0, 3.0));
+    assert!(Equation::new("ln(-2)")
+        .unwrap()
+        .evaluate()
+        .unwrap()
+        .is_nan());
+}
+
+#[test]
+fn numeric_backends() {
+    use equation_solver::{EquationErrorType, Rational};
+
+    let eq = Equation::new("0.1 + 0.2").unwrap();
+    assert_eq!(eq.evaluate_as::<Rational>(), Rational::new(3, 10));
+    assert_eq!(eq.evaluate_as::<f32>(), Ok(0.3));
+    let eq = Equation::new("(1/3)^-2 + 8^(2/3)").unwrap();
+    assert_eq!(eq.evaluate_as::<Rational>(), Rational::new(13, 1));
+    // literals are rounded to f64 first, so long ones lose their last digits
+    let eq = Equation::new("12345678901234567890123").unwrap();
+    assert_eq!(
+        eq.evaluate_as::<Rational>(),
+        Rational::new(12345678901234568000000, 1)
+    );
+
+    assert_eq!(
+        Equation::new("7 / 2 * 2^3").unwrap().evaluate_as::<i64>(),
+        Ok(24)
+    );
+    let overflow = Equation::new("2^62 * 2").unwrap().evaluate_as::<i64>();
+    assert_eq!(overflow.unwrap_err().type_, EquationErrorType::Overflow);
+    let unsupported = Equation::new("sin(1)").unwrap().evaluate_as::<Rational>();
+    assert_eq!(
+        unsupported.unwrap_err().type_,
+        EquationErrorType::Unsupported
+    );
+}
+
+#[cfg(feature = "precision")]
+#[test]
+fn precision() {
+    let digits = |eq: &str| {
+        Equation::new(eq)
+            .unwrap()
+            .evaluate_with_precision(340)
+            .unwrap()
+            .to_decimal(100)
+    };
+    assert_eq!(digits("pi"), "3.141592653589793238462643383279502884197169399375105820974944592307816406286208998628034825342117068");
+    assert_eq!(digits("ln(2)"), "0.6931471805599453094172321214581765680755001343602552541206800094933936219696947156058633269964186875");
+    assert_eq!(digits("sin(1)"), "0.8414709848078965066525023216302989996225630607983710656727517099919104043912396689486397435430526959");
+    assert_eq!(digits("arcsin(1) * 2"), digits("pi"));
+    assert_eq!(digits("log(1000) + 2^10"), "1027");
+    assert_eq!(digits("gamma(0.5)"), "1.772453850905516027298167483341145182797549456122387128213807789852911284591032181374950656738544665");
+    assert_eq!(digits("erf(1)"), "0.8427007929497148693412206350826092592960669979663029084599378978347172540960108412619833253481448885");
+    assert_eq!(digits("25! / 5!"), "129260083694424883200000");
+
+    let sum = Equation::new("0.1 + 0.2")
+        .unwrap()
+        .evaluate_with_precision(200);
+    assert_eq!(sum.unwrap().to_string(), "0.3");
+}
+
+#[test]
+fn interval() {
+    use equation_solver::Interval;
+    use std::collections::HashMap;
+
+    let eq = Equation::new("x^2 - 2*x + sin(y)").unwrap();
+    let bindings = HashMap::from([
+        ("x", Interval::new(1.0, 3.0)),
+        ("y", Interval::new(0.0, 3.0)),
+    ]);
+    let val = eq.evaluate_interval(&bindings).unwrap();
+    // the terms don't depend on each other, so only rounding widens [1 - 6 + 0, 9 - 2 + 1]
+    assert!(val.lo() <= -5.0 && val.lo() > -5.0 - 1e-9);
+    assert!(val.hi() >= 8.0 && val.hi() < 8.0 + 1e-9);
+    for (x, y) in [(1.0, 0.0), (2.0, 1.6), (3.0, 3.0), (1.5, 2.2)] {
+        let point = x * x - 2.0 * x + f64::sin(y);
+        assert!(val.contains(point));
+    }
+
+    let bindings = HashMap::from([("x", Interval::new(-1.0, 2.0))]);
+    let val = Equation::new("1 / x").unwrap().evaluate_interval(&bindings);
+    assert_eq!(val, Ok(Interval::entire()));
+    let bindings = HashMap::from([("x", Interval::new(0.0, 2.0))]);
+    let val = Equation::new("1 / x")
+        .unwrap()
+        .evaluate_interval(&bindings)
+        .unwrap();
+    assert!(val.lo() <= 0.5 && val.lo() > 0.4999 && val.hi() == f64::INFINITY);
+
+    let tenth = Equation::new("0.1 * 3")
+        .unwrap()
+        .evaluate_interval(&HashMap::new());
+    assert!(tenth.unwrap().contains(0.30000000000000004));
+}
+
+#[test]
+fn roots() {
+    use equation_solver::{Interval, Rational};
+
+    let eval = |eq: &str| Equation::new(eq).unwrap().evaluate();
+    assert_eq!(eval("cbrt(-8)"), Ok(-2.0));
+    assert_eq!(eval("sqrt(16) + √9"), Ok(7.0));
+    assert_eq!(eval("root(3, 27)"), Ok(3.0));
+    assert_eq!(eval("nthroot(3, -27)"), Ok(-3.0));
+    assert_eq!(eval("2 * 3√8"), Ok(4.0));
+    assert!(eval("sqrt(-4)").unwrap().is_nan());
+    assert!(Equation::new("root(3)").is_err());
+    assert!(Equation::new("root 3").is_err());
+    assert!(Equation::new("(1, 2)").is_err());
+
+    let eq = Equation::new("cbrt(-8/27)").unwrap();
+    assert_eq!(eq.evaluate_as::<Rational>(), Rational::new(-2, 3));
+    assert_eq!(
+        Equation::new("sqrt(49)").unwrap().evaluate_as::<i64>(),
+        Ok(7)
+    );
+    let bindings = std::collections::HashMap::from([("x", Interval::new(-8.0, 27.0))]);
+    let val = Equation::new("cbrt(x)")
+        .unwrap()
+        .evaluate_interval(&bindings)
+        .unwrap();
+    assert!(val.lo() <= -2.0 && val.lo() > -2.0001 && val.hi() >= 3.0 && val.hi() < 3.0001);
+}
+
+#[test]
+fn functions() {
+    use equation_solver::{Interval, Rational};
+    use std::collections::HashMap;
+
+    let eval = |eq: &str| Equation::new(eq).unwrap().evaluate().unwrap();
+    let check = |eq: &str, expected: f64| {
+        let value = eval(eq);
+        assert!(close(value, expected, 1e-14), "{} = {}", eq, value);
+    };
+    assert_eq!(eval("5!"), 120.0);
+    assert_eq!(eval("2^3!"), 64.0);
+    assert_eq!(eval("-3!"), -6.0);
+    // after a function call it takes the whole call
+    assert_eq!(eval("abs(-2)!"), 2.0);
+    assert_eq!(eval("sqrt(9)! + 1"), 7.0);
+    assert_eq!(eval("2 * abs(-3)!^2"), 72.0);
+    assert_eq!(eval("abs 3!"), 6.0);
+    assert_eq!(eval("abs(-2) + sign(-7) + sign(0)"), 1.0);
+    assert_eq!(
+        eval("floor(-2.5) + ceil(2.1) + round(-2.5) + trunc(-2.7)"),
+        -5.0
+    );
+    assert_eq!(eval("log2(8) + log20"), 3.0 + 20f64.log10());
+    check("frac(2.75)", 0.75);
+    check("gamma(0.5)^2", std::f64::consts::PI);
+    check("gamma(5) - 4!", 0.0);
+    check("lgamma(10) - ln(9!)", 0.0);
+    check("erf(0.5)", 0.520_499_877_813_046_5);
+    check("erfc(3)", 2.209_049_699_858_544e-5);
+    check("cosh(2)^2 - sinh(2)^2", 1.0);
+    check(
+        "arctanh(tanh(0.5)) + arcsinh(sinh(1)) + arccosh(cosh(2))",
+        3.5,
+    );
+    check("exp(1)", std::f64::consts::E);
+    assert!(Equation::new("!3").is_err());
+
+    assert_eq!(
+        Equation::new("20!").unwrap().evaluate_as::<i64>(),
+        Ok(2_432_902_008_176_640_000)
+    );
+    assert!(Equation::new("21!").unwrap().evaluate_as::<i64>().is_err());
+    let eq = Equation::new("floor(-7/2) + round(5/2) + frac(7/3)").unwrap();
+    assert_eq!(eq.evaluate_as::<Rational>(), Rational::new(-2, 3));
+
+    let bindings = HashMap::from([("x", Interval::new(1.0, 3.0))]);
+    let val = Equation::new("gamma(x)")
+        .unwrap()
+        .evaluate_interval(&bindings)
+        .unwrap();
+    assert!(
+        val.lo() <= 0.8856031944108887 && val.lo() > 0.885 && val.hi() >= 2.0 && val.hi() < 2.0001
+    );
+    let bindings = HashMap::from([("x", Interval::new(-1.0, 2.0))]);
+    let val = Equation::new("cosh(x)")
+        .unwrap()
+        .evaluate_interval(&bindings)
+        .unwrap();
+    assert!(val.lo() == 1.0 && val.contains(2f64.cosh()));
+}
+
+// every functional operator, checked against a known value in each of the backends
+const REFERENCE_VALUES: &[(&str, f64)] = &[
+    ("log(1000)", 3.0),
+    ("ln(e^2)", 2.0),
+    ("sin(pi/6)", 0.5),
+    ("cos(pi/3)", 0.5),
+    ("tan(pi/4)", 1.0),
+    ("cot(pi/4)", 1.0),
+    ("sec(pi/3)", 2.0),
+    ("csc(pi/6)", 2.0),
+    ("arcsin(0.5)", std::f64::consts::FRAC_PI_6),
+    ("arccos(0.5)", std::f64::consts::FRAC_PI_3),
+    ("arctan(1)", std::f64::consts::FRAC_PI_4),
+    ("arccot(1)", std::f64::consts::FRAC_PI_4),
+    ("arccot(-1)", -std::f64::consts::FRAC_PI_4),
+    ("arccot(0)", std::f64::consts::FRAC_PI_2),
+    ("arcsec(2)", std::f64::consts::FRAC_PI_3),
+    ("arcsec(-2)", 2.0 * std::f64::consts::FRAC_PI_3),
+    ("arccsc(2)", std::f64::consts::FRAC_PI_6),
+    ("arccsc(-1)", -std::f64::consts::FRAC_PI_2),
+    ("sqrt(2)^2", 2.0),
+    ("cbrt(27)", 3.0),
+    ("sinh(ln(2))", 0.75),
+    ("cosh(ln(2))", 1.25),
+    ("tanh(ln(3))", 0.8),
+    ("arcsinh(0.75)", std::f64::consts::LN_2),
+    ("arccosh(1.25)", std::f64::consts::LN_2),
+    ("arctanh(0.8)", 1.098_612_288_668_109_8),
+    ("abs(-2.5)", 2.5),
+    ("sign(-2.5)", -1.0),
+    ("floor(-2.5)", -3.0),
+    ("ceil(-2.5)", -2.0),
+    ("round(2.7)", 3.0),
+    ("trunc(-2.5)", -2.0),
+    ("frac(-2.25)", -0.25),
+    ("exp(ln(3))", 3.0),
+    ("log2(1/8)", -3.0),
+    ("gamma(6)", 120.0),
+    ("gamma(1.5)", 0.886_226_925_452_758),
+    ("lgamma(0.5)", 0.572_364_942_924_700_1),
+    ("erf(1)", 0.842_700_792_949_714_9),
+    ("erfc(1)", 0.157_299_207_050_285_13),
+    ("4.5!", 52.342_777_784_553_52),
+];
+
+#[test]
+fn reference_values() {
+    use std::collections::HashMap;
+
+    for (source, expected) in REFERENCE_VALUES {
+        let eq = Equation::new(*source).unwrap();
+        let exact = |value: f64| close(value, *expected, 16.0 * f64::EPSILON);
+        let value = eq.evaluate().unwrap();
+        assert!(exact(value), "{} = {}", source, value);
+        let interval = eq.evaluate_interval(&HashMap::new()).unwrap();
+        assert!(
+            interval.contains(*expected) && interval.width() < 1e-12 * expected.abs().max(1.0),
+            "{} in {}",
+            source,
+            interval
+        );
+        #[cfg(feature = "complex")]
+        {
+            let value = eq.evaluate_complex().unwrap();
+            assert!(
+                exact(value.re) && value.im.abs() < 1e-15,
+                "{:?} = {}",
+                eq,
+                value
+            );
+        }
+        #[cfg(feature = "precision")]
+        {
+            let value = eq.evaluate_with_precision(80).unwrap().to_f64();
+            assert!(exact(value), "{} = {}", source, value);
+        }
+    }
+}
+
+#[test]
+fn identities() {
+    for x in [0.3, 1.7, -2.2, 5.5] {
+        let eval = |eq: &str| {
+            Equation::new(eq)
+                .unwrap()
+                .set_value("x", x)
+                .evaluate()
+                .unwrap()
+        };
+        let check = |eq: &str, expected: f64| {
+            let value = eval(eq);
+            assert!(
+                close(value, expected, 1e-12),
+                "{} = {} at x = {}",
+                eq,
+                value,
+                x
+            );
+        };
+        check("sin(x)^2 + cos(x)^2", 1.0);
+        check("tan(x) * cot(x)", 1.0);
+        check("sec(x) * cos(x) + csc(x) * sin(x)", 2.0);
+        check("arccot(x) - arctan(1/x)", 0.0);
+        check("cosh(x)^2 - sinh(x)^2", 1.0);
+        check("arcsinh(sinh(x))", x);
+        check("arctanh(tanh(x / 4))", x / 4.0);
+        check("erf(x) + erfc(x)", 1.0);
+        check("gamma(x + 1) / gamma(x)", x);
+        check("exp(lgamma(x)) - abs(gamma(x))", 0.0);
+        check("floor(x) + ceil(x - floor(x))", x.ceil());
+        check("trunc(x) + frac(x)", x);
+        check("log2(exp(x)) * ln(2)", x);
+        if x.abs() >= 1.0 {
+            check("sec(arcsec(x))", x);
+            check("csc(arccsc(x))", x);
+            check("cosh(arccosh(abs(x)))", x.abs());
+        }
+        if x.abs() <= 1.0 {
+            check("sin(arcsin(x)) + cos(arccos(x))", 2.0 * x);
+        }
+    }
+}
+
+#[test]
+fn angle_mode() {
+    use equation_solver::{AngleMode, Interval};
+    use std::collections::HashMap;
+
+    let check = |value: f64, expected: f64| assert!(close(value, expected, 1e-14), "{}", value);
+    let mut eq = Equation::new("sin(x) + cos(60) + tan(45)").unwrap();
+    eq.set_angle_mode(AngleMode::Degrees).set_value("x", 30.0);
+    check(eq.evaluate().unwrap(), 2.0);
+    let mut eq = Equation::new("arcsin(1) + arctan(1) + arccot(0)").unwrap();
+    assert_eq!(eq.angle_mode(), AngleMode::Radians);
+    check(eq.evaluate().unwrap(), std::f64::consts::PI * 1.25);
+    check(
+        eq.set_angle_mode(AngleMode::Degrees).evaluate().unwrap(),
+        225.0,
+    );
+    check(
+        eq.set_angle_mode(AngleMode::Gradians).evaluate().unwrap(),
+        250.0,
+    );
+    // everything that isn't an angle is left alone
+    let mut eq = Equation::new("sinh(1) + ln(e)").unwrap();
+    check(
+        eq.set_angle_mode(AngleMode::Degrees).evaluate().unwrap(),
+        1f64.sinh() + 1.0,
+    );
+
+    let mut eq = Equation::new("sin(x)").unwrap();
+    eq.set_angle_mode(AngleMode::Degrees);
+    let bindings = HashMap::from([("x", Interval::new(30.0, 30.0))]);
+    assert!(eq.evaluate_interval(&bindings).unwrap().contains(0.5));
+}
+
+#[test]
+fn constants() {
+    use equation_solver::Constants;
+
+    let eq = Equation::new("e * x + pi").unwrap();
+    assert_eq!(
+        eq.list_vars(),
+        std::collections::HashSet::from(["x".to_string()])
+    );
+    // without `e` in the table it's a variable like any other
+    let mut constants = Constants::default();
+    constants.remove("e").insert("k", 2.0);
+    let mut eq = Equation::with_constants("e * k + pi", &constants).unwrap();
+    assert_eq!(eq.list_vars().len(), 1);
+    assert_eq!(
+        eq.set_value("e", 3.0).evaluate(),
+        Ok(6.0 + std::f64::consts::PI)
+    );
+    assert!(Equation::with_constants("pi", &Constants::new())
+        .unwrap()
+        .list_vars()
+        .contains("pi"));
+
+    let eq =
+        Equation::with_constants("m_e * c^2 / q_e + k_B - k_B", &Constants::physics()).unwrap();
+    assert!(eq.list_vars().is_empty());
+    let value = eq.evaluate().unwrap();
+    assert!((value - 510_998.95).abs() < 0.01, "{}", value);
+    assert_eq!(
+        Equation::with_constants("c", &Constants::physics())
+            .unwrap()
+            .evaluate_as::<i64>(),
+        Ok(299_792_458)
+    );
+    let mut eq = Equation::new("x_1 + x_2 * 2").unwrap();
+    assert_eq!(
+        eq.set_value("x_1", 1.0).set_value("x_2", 2.0).evaluate(),
+        Ok(5.0)
+    );
+}
+
+#[test]
+fn units() {
+    use equation_solver::{units::Unit, EquationErrorType};
+    use std::collections::HashSet;
+
+    let eq = Equation::new("3 [m] / 2 [s]").unwrap();
+    assert_eq!(eq, Equation::new("3m / 2s").unwrap());
+    assert_eq!(eq.evaluate(), Ok(1.5));
+    let speed = eq.evaluate_with_units().unwrap();
+    assert_eq!(speed.dimension(), Unit::parse("m/s").unwrap().dimension());
+    assert!((eq.evaluate_in("km/h").unwrap() - 5.4).abs() < 1e-12);
+
+    let mut eq = Equation::new("v * t + 500m").unwrap();
+    eq.set_value_with_unit("v", 36.0, "km/h")
+        .unwrap()
+        .set_value_with_unit("t", 2.0, "min")
+        .unwrap();
+    assert!((eq.evaluate_in("km").unwrap() - 1.7).abs() < 1e-12);
+    assert!((eq.evaluate().unwrap() - 1700.0).abs() < 1e-9);
+
+    let err = Equation::new("2m + 3s")
+        .unwrap()
+        .evaluate_with_units()
+        .unwrap_err();
+    assert_eq!(err.type_, EquationErrorType::DimensionMismatch);
+    let err = Equation::new("3m").unwrap().evaluate_in("s").unwrap_err();
+    assert_eq!(err.type_, EquationErrorType::DimensionMismatch);
+    assert!(Equation::new("sin(2m)")
+        .unwrap()
+        .evaluate_with_units()
+        .is_err());
+    assert!(Equation::new("x")
+        .unwrap()
+        .set_value_with_unit("x", 1.0, "furlong")
+        .is_err());
+
+    let check = |eq: &str, unit: &str, expected: f64| {
+        let value = Equation::new(eq).unwrap().evaluate_in(unit).unwrap();
+        assert!(close(value, expected, 1e-12), "{} = {}", eq, value);
+    };
+    check("sqrt(16m^2) + 1 ft", "in", 4.0 / 0.0254 + 12.0);
+    check("2 [kg*m^2/s^2] * 3", "J", 6.0);
+    check("1 kW * 2h", "MJ", 7.2);
+    check("sin(30 deg) * 2", "1", 1.0);
+    check("36 km/min", "m/s", 600.0);
+
+    check("2 [h] + 36 [km] / 1 [km/min]", "min", 156.0);
+
+    // without brackets a one letter unit after a space or a unit after a `*` could be a variable, and a constant's name is never a unit
+    for eq in [
+        "2 h",
+        "2 g",
+        "2 s*t",
+        "2s*t",
+        "10m/s*t",
+        "2kg*m^2/s^2",
+        "3 [x]",
+    ] {
+        let err = Equation::new(eq).unwrap_err();
+        assert_eq!(err.type_, EquationErrorType::UnexpectedToken, "{}", eq);
+    }
+    for (eq, expected) in [("2s * t", 6.0), ("10m/s * t", 30.0)] {
+        let mut eq = Equation::new(eq).unwrap();
+        assert_eq!(eq.list_vars(), HashSet::from(["t".to_string()]));
+        assert_eq!(eq.set_value("t", 3.0).evaluate(), Ok(expected));
+    }
+    let physics = equation_solver::Constants::physics();
+    for eq in ["2h", "2 h", "2kg/g"] {
+        let err = Equation::with_constants(eq, &physics).unwrap_err();
+        assert_eq!(err.type_, EquationErrorType::UnexpectedToken, "{}", eq);
+    }
+    let eq = Equation::with_constants("2 * h + 1 min", &physics).unwrap();
+    assert_eq!(eq.evaluate(), Ok(2.0 * 6.626_070_15e-34 + 60.0));
+    assert_eq!(
+        Equation::with_constants("2 [h]", &physics)
+            .unwrap()
+            .evaluate(),
+        Ok(7200.0)
+    );
+
+    // units are written by name, so the text parses back into the same equation
+    for source in ["3m/2s", "2 [kg*m^2/s^2] * 3", "1 kW * 2h", "5 [1/s] + x"] {
+        let eq = Equation::new(source).unwrap();
+        assert_eq!(Equation::new(eq.to_string()).unwrap(), eq, "{}", eq);
+    }
+    assert_eq!(Equation::new("3m/2s").unwrap().to_string(), "3[m]/2[s]");
+}
+
+#[test]
+fn programs() {
+    use equation_solver::Program;
+    use std::collections::{HashMap, HashSet};
+
+    let program = Program::new("a = x + 1; b = a^2; a * b").unwrap();
+    assert_eq!(program.list_vars(), HashSet::from(["x".to_string()]));
+    let bindings = HashMap::from([("x", 2.0)]);
+    assert_eq!(program.evaluate(&bindings), Ok(27.0));
+    let values = program.values(&bindings).unwrap();
+    assert_eq!(values.get("a"), Some(&3.0));
+    assert_eq!(values.get("b"), Some(&9.0));
+    assert!(program.evaluate(&HashMap::new()).is_err());
+
+    let program = Program::new("let r = 2\nlet area = pi * r^2\n\nr = r + 1").unwrap();
+    assert!(program.list_vars().is_empty());
+    let values = program.values(&HashMap::new()).unwrap();
+    assert_eq!(values.get("r"), Some(&3.0));
+    assert_eq!(values.get("area"), Some(&(std::f64::consts::PI * 4.0)));
+
+    assert!(Program::new("pi = 3").is_err());
+    assert!(Program::new("sin = 3").is_err());
+    assert!(Program::new(" ; ").is_err());
+}
+
+#[test]
+fn models() {
+    use equation_solver::{EquationErrorType, Model};
+
+    let mut model = Model::new();
+    model
+        .set_input("x", 2.0)
+        .set_input("y", 10.0)
+        .set_equation("a", Equation::new("x + 1").unwrap())
+        .set_equation("b", Equation::new("a^2").unwrap())
+        .set_equation("c", Equation::new("y / 2").unwrap())
+        .set_equation("total", Equation::new("a * b + c").unwrap());
+    assert_eq!(model.order().unwrap(), ["a", "b", "c", "total"]);
+    assert_eq!(model.evaluate("total"), Ok(32.0));
+    assert!(!model.is_dirty("b") && !model.is_dirty("c"));
+
+    // only what depends on x is recomputed
+    model.set_input("x", 3.0);
+    assert!(model.is_dirty("a") && model.is_dirty("b") && model.is_dirty("total"));
+    assert!(!model.is_dirty("c"));
+    assert_eq!(model.evaluate("total"), Ok(69.0));
+    let values = model.evaluate_all().unwrap();
+    assert_eq!(values.get("b"), Some(&16.0));
+
+    model.set_equation("x", Equation::new("total - 1").unwrap());
+    let err = model.evaluate("total").unwrap_err();
+    assert_eq!(err.type_, EquationErrorType::CircularDependency);
+    assert_eq!(
+        model.order().unwrap_err().type_,
+        EquationErrorType::CircularDependency
+    );
+    model
+        .set_input("x", 1.0)
+        .set_equation("d", Equation::new("z").unwrap());
+    assert_eq!(model.evaluate("total"), Ok(13.0));
+    assert_eq!(
+        model.evaluate("d").unwrap_err().type_,
+        EquationErrorType::UnsetVariable
+    );
+}
+
+#[test]
+fn systems() {
+    use equation_solver::system::SolveMethod;
+    use equation_solver::System;
+    use std::collections::HashMap;
+
+    let system = System::new(["x + y = 10", "x*y = 21"]).unwrap();
+    assert_eq!(system.unknowns(), ["x", "y"]);
+    assert!(!system.is_linear());
+    let solution = system
+        .solve(&HashMap::from([("x", 1.0), ("y", 5.0)]))
+        .unwrap();
+    assert!(solution.converged());
+    assert_eq!(solution.method(), SolveMethod::Newton);
+    let (x, y) = (solution.get("x").unwrap(), solution.get("y").unwrap());
+    assert!(
+        (x - 3.0).abs() < 1e-9 && (y - 7.0).abs() < 1e-9,
+        "{} {}",
+        x,
+        y
+    );
+
+    // linear systems are solved exactly, without a guess
+    let system = System::new(["x + 2*y = 5", "3*x - y = 1 / 3", "z = x / 3"]).unwrap();
+    assert!(system.is_linear());
+    let solution = system.solve(&HashMap::new()).unwrap();
+    assert_eq!(solution.method(), SolveMethod::Elimination);
+    assert_eq!(solution.get("x"), Some(17.0 / 21.0));
+    assert_eq!(solution.get("y"), Some(44.0 / 21.0));
+    assert_eq!(solution.get("z"), Some(17.0 / 63.0));
+    let singular = System::new(["x + y = 1", "2*x + 2*y = 3"]).unwrap();
+    assert!(singular.is_linear() && singular.solve(&HashMap::new()).is_err());
+
+    // a root that isn't there is reported rather than made up
+    let mut system = System::new(["x^2 = -1"]).unwrap();
+    let solution = system
+        .set_max_iterations(20)
+        .solve(&HashMap::new())
+        .unwrap();
+    assert!(!solution.converged() && solution.residual() >= 1.0);
+    assert_eq!(
+        Equation::from_equality("2 * x = x + 3")
+            .unwrap()
+            .set_value("x", 3.0)
+            .evaluate(),
+        Ok(0.0)
+    );
+
+    // names in a constants table aren't unknowns
+    let mut constants = equation_solver::Constants::new();
+    constants.insert("k", 2.0);
+    let eq = Equation::from_equality_with_constants("k * x = 6", &constants).unwrap();
+    assert!(eq.list_vars().contains("x") && !eq.list_vars().contains("k"));
+    let system = System::with_constants(["k * x + y = 7", "x - y = k"], &constants).unwrap();
+    assert_eq!(system.unknowns(), ["x", "y"]);
+    let solution = system.solve(&HashMap::new()).unwrap();
+    assert_eq!(solution.get("x"), Some(3.0));
+    assert_eq!(solution.get("y"), Some(1.0));
+}
+
+#[test]
+fn polynomials() {
+    use equation_solver::Polynomial;
+
+    let polynomial = Equation::new("(x - 1) * (2*x + 3) - x^2")
+        .unwrap()
+        .as_polynomial("x")
+        .unwrap();
+    assert_eq!(polynomial.coefficients(), [-3.0, 1.0, 1.0]);
+    assert!(Equation::new("sin(x) + 1")
+        .unwrap()
+        .as_polynomial("x")
+        .is_none());
+    assert!(Equation::new("x^0.5").unwrap().as_polynomial("x").is_none());
+    assert!(Equation::new("x * y").unwrap().as_polynomial("x").is_none());
+    assert_eq!(
+        Equation::from_equality("x^2 = 2")
+            .unwrap()
+            .as_polynomial("x")
+            .unwrap()
+            .real_roots(),
+        [-2f64.sqrt(), 2f64.sqrt()]
+    );
+
+    let check = |actual: &[f64], expected: &[f64]| {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(close(*a, *e, 1e-12), "{:?}", actual);
+        }
+    };
+    // double and triple roots are found exactly
+    check(
+        &Polynomial::new(vec![2.0, -3.0, 0.0, 1.0]).real_roots(),
+        &[-2.0, 1.0, 1.0],
+    );
+    check(
+        &Polynomial::new(vec![-1.0, 3.0, -3.0, 1.0]).real_roots(),
+        &[1.0, 1.0, 1.0],
+    );
+    check(
+        &Polynomial::new(vec![-6.0, 11.0, -6.0, 1.0]).real_roots(),
+        &[1.0, 2.0, 3.0],
+    );
+    // (x^2 - 1)(x^2 - 4) and (x - 1)(x - 2)(x - 3)(x - 4)
+    check(
+        &Polynomial::new(vec![4.0, 0.0, -5.0, 0.0, 1.0]).real_roots(),
+        &[-2.0, -1.0, 1.0, 2.0],
+    );
+    check(
+        &Polynomial::new(vec![24.0, -50.0, 35.0, -10.0, 1.0]).real_roots(),
+        &[1.0, 2.0, 3.0, 4.0],
+    );
+    // x^4 + 1 has only complex roots
+    let roots = Polynomial::new(vec![1.0, 0.0, 0.0, 0.0, 1.0]).roots();
+    assert_eq!(roots.len(), 4);
+    for root in roots {
+        assert!(!root.is_real() && (root.norm() - 1.0).abs() < 1e-12);
+    }
+    // repeated roots past degree 4 are merged back together
+    let polynomial = Equation::new("(x - 1)^3 * (x + 2)^2 * (x^2 + 1)")
+        .unwrap()
+        .as_polynomial("x")
+        .unwrap();
+    check(&polynomial.real_roots(), &[-2.0, -2.0, 1.0, 1.0, 1.0]);
+    // (x - 1)(x - 2)(x - 3)(x - 4)(x - 5)(x^2 + 1)x
+    let polynomial = Equation::new("(x-1)*(x-2)*(x-3)*(x-4)*(x-5)*(x^2+1)*x")
+        .unwrap()
+        .as_polynomial("x")
+        .unwrap();
+    assert_eq!(polynomial.degree(), 8);
+    check(&polynomial.real_roots(), &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    let complex: Vec<_> = polynomial
+        .roots()
+        .into_iter()
+        .filter(|r| !r.is_real())
+        .collect();
+    assert!(
+        complex.len() == 2
+            && complex
+                .iter()
+                .all(|r| r.re.abs() < 1e-12 && (r.im.abs() - 1.0).abs() < 1e-12)
+    );
+}
+
+#[test]
+fn integration() {
+    use equation_solver::integration::IntegrationOptions;
+    use std::f64::consts::PI;
+
+    let options = IntegrationOptions::default();
+    let integral = |eq: &str, a: f64, b: f64| {
+        Equation::new(eq)
+            .unwrap()
+            .integrate("x", a, b, &options)
+            .unwrap()
+    };
+    let check = |eq: &str, a: f64, b: f64, expected: f64| {
+        let result = integral(eq, a, b);
+        assert!(
+            result.converged() && close(result.value(), expected, 1e-9),
+            "{} = {:?}",
+            eq,
+            result
+        );
+        assert!(result.error() <= 1e-9 * expected.abs().max(1.0));
+        result
+    };
+    check("x^2", 0.0, 3.0, 9.0);
+    check("sin(x)", 0.0, PI, 2.0);
+    check("sin(x)", PI, 0.0, -2.0);
+    check("e^(-x^2)", f64::NEG_INFINITY, f64::INFINITY, PI.sqrt());
+    check("1 / (1 + x^2)", 0.0, f64::INFINITY, PI / 2.0);
+    check("e^x", f64::NEG_INFINITY, 0.0, 1.0);
+
+    // integrable singularities are found and still converge
+    let result = check("1 / sqrt(x)", 0.0, 1.0, 2.0);
+    assert_eq!(result.singularities(), [0.0]);
+    let result = check("ln(abs(x - 1))", 0.0, 3.0, 2f64.ln() * 2.0 - 3.0);
+    assert!(result
+        .singularities()
+        .iter()
+        .any(|x| (x - 1.0).abs() < 1e-6));
+    assert!(integral("x^2", 0.0, 1.0).singularities().is_empty());
+    // and ones that aren't integrable don't converge
+    assert!(!integral("1 / x", 0.0, 1.0).converged());
+    assert!(Equation::new("x * y")
+        .unwrap()
+        .integrate("x", 0.0, 1.0, &options)
+        .is_err());
+}
+
+#[test]
+fn antiderivatives() {
+    use equation_solver::integration::IntegrationOptions;
+    use std::collections::HashMap;
+
+    let antiderivative = |eq: &str| {
+        Equation::new(eq)
+            .unwrap()
+            .antiderivative("x")
+            .map(|eq| eq.to_string())
+    };
+    assert_eq!(
+        antiderivative("x^2 + cos(x)").as_deref(),
+        Some("x^3/3 + sin(x)")
+    );
+    assert_eq!(antiderivative("1/x").as_deref(), Some("ln(abs(x))"));
+    assert_eq!(
+        antiderivative("sin(2*x + 1)").as_deref(),
+        Some("-cos(2*x + 1)/2")
+    );
+    assert_eq!(
+        antiderivative("x * sin(x)").as_deref(),
+        Some("sin(x) - x*cos(x)")
+    );
+    assert_eq!(
+        antiderivative("x * ln(x)").as_deref(),
+        Some("x^2*ln(x)/2 - x^2/4")
+    );
+    assert_eq!(
+        antiderivative("1 / (x^2 + 1)").as_deref(),
+        Some("arctan(x)")
+    );
+    assert_eq!(antiderivative("a*x + b").as_deref(), Some("a*x^2/2 + b*x"));
+    // without a closed form the integral has to be found numerically
+    assert_eq!(antiderivative("sin(x)^2"), None);
+    assert_eq!(antiderivative("gamma(x)"), None);
+    assert_eq!(antiderivative("floor(x)"), None);
+    assert_eq!(antiderivative("1/cos(x)^2"), None);
+
+    // the difference of the antiderivative matches the numeric integral
+    let options = IntegrationOptions::default();
+    for eq in [
+        "3*x^2 - 2*x + 5",
+        "x^-2",
+        "sqrt(x)",
+        "3√x",
+        "e^(3*x)",
+        "2^x",
+        "exp(-x)",
+        "x^2 * e^x",
+        "(x + 1) * cos(x)",
+        "x^2 * log(2*x)",
+        "log2(x)",
+        "1 / (x^2 - 4)",
+        "1 / (3*x + 2)",
+        "1 / cos(x / 2)",
+        "tan(x / 2)",
+        "csc(x)",
+        "cot(x)",
+        "arcsin(x / 2)",
+        "arccos(x / 2)",
+        "arctan(x)",
+        "arccot(x)",
+        "arcsec(x)",
+        "arccsc(x)",
+        "sinh(x)",
+        "tanh(x)",
+        "arcsinh(x)",
+        "arccosh(x)",
+        "arctanh(x / 3)",
+        "abs(x - 1.5)",
+        "sign(x - 1.5)",
+        "erf(x)",
+        "erfc(x)",
+        "(2*x + 1)^5",
+        "1/e^x",
+        "1/(x + 1)^2",
+        "x*e^(-x)",
+        "e^(-x)*x^2",
+        "x^3*e^(-2*x)",
+        "x/2^x",
+    ] {
+        let equation = Equation::new(eq).unwrap();
+        let formula = equation.antiderivative("x").unwrap().to_string();
+        // the formula parses back into the antiderivative
+        let reparsed = Equation::new(formula.clone()).unwrap();
+        let at = |x: f64| {
+            reparsed
+                .evaluate_with_bindings(&HashMap::from([("x", x)]))
+                .unwrap()
+        };
+        let expected = equation.integrate("x", 1.2, 1.7, &options).unwrap().value();
+        assert!(
+            (at(1.7) - at(1.2) - expected).abs() < 1e-9,
+            "{} -> {}",
+            eq,
+            formula
+        );
+    }
+
+    // the angle mode is part of the antiderivative
+    let mut degrees = Equation::new("cos(x)").unwrap();
+    degrees.set_angle_mode(equation_solver::AngleMode::Degrees);
+    let sine = degrees.antiderivative("x").unwrap();
+    assert!(
+        (sine
+            .evaluate_with_bindings(&HashMap::from([("x", 90.0)]))
+            .unwrap()
+            - 180.0 / std::f64::consts::PI)
+            .abs()
+            < 1e-9
+    );
+}
+
+#[test]
+fn series() {
+    use equation_solver::EquationErrorType;
+    use std::collections::HashMap;
+
+    let series = |eq: &str, about: f64, order: u32| {
+        Equation::new(eq)
+            .unwrap()
+            .series("x", about, order)
+            .map(|eq| eq.to_string())
+    };
+    assert_eq!(series("sin(x)", 0.0, 5).unwrap(), "x - x^3/6 + x^5/120");
+    assert_eq!(series("e^x", 0.0, 3).unwrap(), "1 + x + x^2/2 + x^3/6");
+    assert_eq!(series("tan(x)", 0.0, 5).unwrap(), "x + x^3/3 + 2*x^5/15");
+    assert_eq!(
+        series("ln(x)", 1.0, 3).unwrap(),
+        "x - 1 - (x - 1)^2/2 + (x - 1)^3/3"
+    );
+    assert_eq!(
+        Equation::new("sin(x^2)")
+            .unwrap()
+            .derivative("x")
+            .unwrap()
+            .to_string(),
+        "2*x*cos(x^2)"
+    );
+    assert!(Equation::new("gamma(x)").unwrap().derivative("x").is_none());
+
+    // the remainder bounds the error of the approximation within the radius
+    for (eq, about, order, radius) in [
+        ("sin(x)", 0.0, 7, 1.0),
+        ("e^x", 1.0, 4, 0.5),
+        ("sqrt(x)", 4.0, 3, 1.0),
+        ("arctan(x)", 0.5, 5, 0.25),
+    ] {
+        let equation = Equation::new(eq).unwrap();
+        let (approximation, remainder) = equation
+            .series_with_remainder("x", about, order, radius)
+            .unwrap();
+        assert!(
+            remainder.is_finite() && remainder < 1e-2,
+            "{} {}",
+            eq,
+            remainder
+        );
+        for i in 0..=20 {
+            let x = about - radius + radius * i as f64 / 10.0;
+            let at = HashMap::from([("x", x)]);
+            let error = (equation.evaluate_with_bindings(&at).unwrap()
+                - approximation.evaluate_with_bindings(&at).unwrap())
+            .abs();
+            assert!(error <= remainder * (1.0 + 1e-9) + 1e-15, "{} at {}", eq, x);
+        }
+    }
+
+    // a function that isn't differentiable at the point has no series there
+    assert_eq!(
+        Equation::new("sqrt(x)")
+            .unwrap()
+            .series("x", 0.0, 2)
+            .unwrap_err()
+            .type_,
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        Equation::new("gamma(x)")
+            .unwrap()
+            .series("x", 1.0, 2)
+            .unwrap_err()
+            .type_,
+        EquationErrorType::Unsupported
+    );
+}
+
+#[test]
+fn limits() {
+    use equation_solver::limit::Direction;
+    use equation_solver::EquationErrorType;
+
+    let limit = |eq: &str, point: f64, direction: Direction| {
+        Equation::new(eq).unwrap().limit("x", point, direction)
+    };
+    let inf = f64::INFINITY;
+
+    // removable singularities are exact through l'hopital's rule
+    assert_eq!(limit("sin(x)/x", 0.0, Direction::Both), Ok(1.0));
+    assert_eq!(limit("(1 - cos(x))/x^2", 0.0, Direction::Both), Ok(0.5));
+    assert_eq!(limit("(x^2 - 4)/(x - 2)", 2.0, Direction::Both), Ok(4.0));
+    assert_eq!(limit("x*ln(x)", 0.0, Direction::Above), Ok(0.0));
+    assert_eq!(limit("sin(x)", 1.0, Direction::Both), Ok(1f64.sin()));
+    assert!((limit("(tan(x) - x)/x^3", 0.0, Direction::Both).unwrap() - 1.0 / 3.0).abs() < 1e-12);
+
+    // at infinity
+    assert_eq!(limit("x/(x + 1)", inf, Direction::Both), Ok(1.0));
+    assert_eq!(limit("x^2/e^x", inf, Direction::Both), Ok(0.0));
+    assert_eq!(limit("e^x", -inf, Direction::Both), Ok(0.0));
+    assert_eq!(limit("ln(x)", inf, Direction::Both), Ok(inf));
+    let e = limit("(1 + 1/x)^x", inf, Direction::Both).unwrap();
+    assert!((e - std::f64::consts::E).abs() < 1e-6, "{}", e);
+    let half = limit("x*(sqrt(x^2 + 1) - x)", inf, Direction::Both).unwrap();
+    assert!((half - 0.5).abs() < 1e-6, "{}", half);
+
+    // one-sided limits
+    assert_eq!(limit("1/x", 0.0, Direction::Below), Ok(-inf));
+    assert_eq!(limit("1/x", 0.0, Direction::Above), Ok(inf));
+    assert_eq!(limit("1/x^2", 0.0, Direction::Both), Ok(inf));
+    assert_eq!(limit("floor(x)", 1.0, Direction::Below), Ok(0.0));
+    assert_eq!(limit("floor(x)", 1.0, Direction::Above), Ok(1.0));
+    assert_eq!(limit("exp(1/x)", 0.0, Direction::Below), Ok(0.0));
+    assert_eq!(limit("sqrt(x)", 0.0, Direction::Above), Ok(0.0));
+
+    // the sides differ, or the values never settle
+    for (eq, point) in [
+        ("1/x", 0.0),
+        ("abs(x)/x", 0.0),
+        ("floor(x)", 1.0),
+        ("sin(1/x)", 0.0),
+        ("sin(x)", inf),
+    ] {
+        assert_eq!(
+            limit(eq, point, Direction::Both).unwrap_err().type_,
+            EquationErrorType::NoLimit,
+            "{} at {}",
+            eq,
+            point
+        );
+    }
+    assert_eq!(
+        limit("sqrt(x)", 0.0, Direction::Below).unwrap_err().type_,
+        EquationErrorType::NoLimit
+    );
+    assert_eq!(
+        limit("x", inf, Direction::Above).unwrap_err().type_,
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        limit("x + y", 0.0, Direction::Both).unwrap_err().type_,
+        EquationErrorType::UnsetVariable
+    );
+}
+
+#[test]
+fn optimization() {
+    use equation_solver::optimize::OptimizeMethod;
+    use equation_solver::EquationErrorType;
+
+    let inf = f64::INFINITY;
+
+    // one variable within bounds
+    for method in [
+        OptimizeMethod::Auto,
+        OptimizeMethod::Brent,
+        OptimizeMethod::GoldenSection,
+    ] {
+        let minimum = Equation::new("cos(x)")
+            .unwrap()
+            .minimize(&["x"], &[(0.0, 6.0)], method)
+            .unwrap();
+        assert!(minimum.converged());
+        assert!(close(minimum.get("x").unwrap(), std::f64::consts::PI, 1e-7));
+        assert!(close(minimum.value(), -1.0, 1e-12));
+    }
+    let minimum = Equation::new("x")
+        .unwrap()
+        .minimize(&["x"], &[(0.0, 1.0)], OptimizeMethod::Auto)
+        .unwrap();
+    assert_eq!(minimum.method(), OptimizeMethod::Brent);
+    assert_eq!(minimum.get("x"), Some(0.0));
+    let maximum = Equation::new("sin(x)")
+        .unwrap()
+        .maximize(&["x"], &[(0.0, 3.0)], OptimizeMethod::Auto)
+        .unwrap();
+    assert!(close(
+        maximum.get("x").unwrap(),
+        std::f64::consts::FRAC_PI_2,
+        1e-7
+    ));
+    assert!(close(maximum.value(), 1.0, 1e-12));
+
+    // rosenbrock's function, with the symbolic gradient and with only values
+    let rosenbrock = Equation::new("(1 - x)^2 + 100*(y - x^2)^2").unwrap();
+    for (method, bounds) in [
+        (OptimizeMethod::Auto, [(-inf, inf), (-inf, inf)]),
+        (OptimizeMethod::Bfgs, [(-2.0, 2.0), (-2.0, 2.0)]),
+        (OptimizeMethod::NelderMead, [(-2.0, 2.0), (-2.0, 2.0)]),
+    ] {
+        let minimum = rosenbrock.minimize(&["x", "y"], &bounds, method).unwrap();
+        assert!(minimum.converged(), "{:?}", method);
+        assert_ne!(minimum.method(), OptimizeMethod::Auto);
+        assert!(close(minimum.get("x").unwrap(), 1.0, 1e-6), "{:?}", method);
+        assert!(close(minimum.get("y").unwrap(), 1.0, 1e-6), "{:?}", method);
+        assert!(minimum.value() < 1e-12);
+    }
+
+    // a minimum on a bound, and the other variables set beforehand
+    let mut bowl = Equation::new("(x - a)^2 + (y + 1)^2").unwrap();
+    bowl.set_value("a", 3.0);
+    for method in [OptimizeMethod::Bfgs, OptimizeMethod::NelderMead] {
+        let minimum = bowl
+            .minimize(&["x", "y"], &[(0.0, 2.0), (-5.0, 5.0)], method)
+            .unwrap();
+        assert_eq!(minimum.get("x"), Some(2.0));
+        assert!(close(minimum.get("y").unwrap(), -1.0, 1e-6));
+        assert!(close(minimum.value(), 1.0, 1e-12));
+    }
+
+    // gamma has no derivative in closed form, so bfgs uses finite differences
+    let minimum = Equation::new("gamma(x) + gamma(y)")
+        .unwrap()
+        .minimize(&["x", "y"], &[(0.5, inf), (0.5, 5.0)], OptimizeMethod::Bfgs)
+        .unwrap();
+    assert!(close(
+        minimum.get("x").unwrap(),
+        1.461_632_144_968_362,
+        1e-6
+    ));
+    assert!(close(
+        minimum.get("y").unwrap(),
+        1.461_632_144_968_362,
+        1e-6
+    ));
+
+    // kinks go to nelder-mead, since bfgs stalls on them without a minimum
+    let taxicab = Equation::new("abs(x - 1) + abs(y - 2)").unwrap();
+    let minimum = taxicab
+        .minimize(&["x", "y"], &[(-inf, inf); 2], OptimizeMethod::Auto)
+        .unwrap();
+    assert_eq!(minimum.method(), OptimizeMethod::NelderMead);
+    assert!(minimum.converged());
+    assert!(close(minimum.get("x").unwrap(), 1.0, 1e-6));
+    assert!(close(minimum.get("y").unwrap(), 2.0, 1e-6));
+    let stalled = taxicab
+        .minimize(&["x", "y"], &[(-inf, inf); 2], OptimizeMethod::Bfgs)
+        .unwrap();
+    assert!(stalled.value() > 1e-6 && !stalled.converged());
+
+    // a half-infinite search starts inside the bound, where the equation can be undefined
+    let minimum = Equation::new("ln(x) + 1/x")
+        .unwrap()
+        .minimize(&["x"], &[(0.0, inf)], OptimizeMethod::Auto)
+        .unwrap();
+    assert!(minimum.converged());
+    assert!(close(minimum.get("x").unwrap(), 1.0, 1e-6));
+    assert!(close(minimum.value(), 1.0, 1e-12));
+
+    let unsupported = |eq: &str, variables: &[&str], bounds: &[(f64, f64)], method| {
+        Equation::new(eq)
+            .unwrap()
+            .minimize(variables, bounds, method)
+            .unwrap_err()
+            .type_
+    };
+    assert_eq!(
+        unsupported("x*y", &["x", "y"], &[(0.0, 1.0); 2], OptimizeMethod::Brent),
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        unsupported("x^2", &["x"], &[(0.0, inf)], OptimizeMethod::GoldenSection),
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        unsupported("x^2", &["x"], &[(1.0, 0.0)], OptimizeMethod::Auto),
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        unsupported("x^2", &["x"], &[], OptimizeMethod::Auto),
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        unsupported("x + y", &["x"], &[(0.0, 1.0)], OptimizeMethod::Auto),
+        EquationErrorType::UnsetVariable
+    );
+    assert_eq!(
+        unsupported("ln(x)", &["x"], &[(-inf, -1.0)], OptimizeMethod::Auto),
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        unsupported("x^2", &["x", "x"], &[(0.0, 1.0); 2], OptimizeMethod::Auto),
+        EquationErrorType::Unsupported
+    );
+}
+
+#[test]
+fn fitting() {
+    use equation_solver::fit::{fit, fit_from};
+    use equation_solver::EquationErrorType;
+    use std::collections::HashMap;
+
+    // an exponential decay, with the data exactly on it and with some noise
+    let decay = Equation::new("a * exp(-b * t) + c").unwrap();
+    let noise = [
+        0.01, -0.02, 0.015, 0.0, -0.01, 0.02, -0.015, 0.005, -0.005, 0.01, 0.0, -0.01,
+    ];
+    let exact: Vec<(f64, f64)> = (0..12)
+        .map(|i| {
+            let t = i as f64 / 2.0;
+            (t, 5.0 * (-0.7 * t).exp() + 1.5)
+        })
+        .collect();
+    let estimate = fit(&decay, &["a", "b", "c"], "t", &exact).unwrap();
+    assert!(estimate.converged());
+    for (parameter, value) in [("a", 5.0), ("b", 0.7), ("c", 1.5)] {
+        assert!(close(estimate.get(parameter).unwrap(), value, 1e-9));
+    }
+    assert!(estimate.sum_of_squares() < 1e-20);
+    let noisy: Vec<(f64, f64)> = exact
+        .iter()
+        .zip(noise)
+        .map(|((t, y), e)| (*t, y + e))
+        .collect();
+    let estimate = fit(&decay, &["a", "b", "c"], "t", &noisy).unwrap();
+    assert!(estimate.converged());
+    for (parameter, value) in [("a", 5.0), ("b", 0.7), ("c", 1.5)] {
+        let error = estimate.standard_error(parameter).unwrap();
+        assert!(error > 0.0 && error < 0.02, "{} {}", parameter, error);
+        assert!(close(estimate.get(parameter).unwrap(), value, 3.0 * error));
+    }
+    assert_eq!(estimate.parameters(), ["a", "b", "c"]);
+    assert_eq!(estimate.residuals().len(), noisy.len());
+    let covariance = estimate.covariance().unwrap();
+    assert!(close(covariance[0][2], covariance[2][0], 1e-15));
+
+    // a straight line has the closed form of linear regression
+    let line = Equation::new("m*t + q").unwrap();
+    let data = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0), (3.0, 7.5)];
+    let estimate = fit(&line, &["m", "q"], "t", &data).unwrap();
+    assert!(estimate.converged());
+    assert!(close(estimate.get("m").unwrap(), 2.15, 1e-9));
+    assert!(close(estimate.get("q").unwrap(), 0.9, 1e-9));
+    let residuals = [0.1, -0.05, -0.2, 0.15];
+    for (residual, expected) in estimate.residuals().iter().zip(residuals) {
+        assert!(close(*residual, expected, 1e-9));
+    }
+    // s^2 (XᵀX)⁻¹, with s^2 = 0.075 / 2
+    let covariance = estimate.covariance().unwrap();
+    assert!(close(covariance[0][0], 0.0075, 1e-9));
+    assert!(close(covariance[0][1], -0.01125, 1e-9));
+    assert!(close(covariance[1][1], 0.02625, 1e-9));
+
+    // a parameter without a derivative in closed form, from a guess near the answer
+    let wave = Equation::new("a*sin(w*t) + gamma(k)").unwrap();
+    let data: Vec<(f64, f64)> = (0..30)
+        .map(|i| {
+            let t = i as f64 / 5.0;
+            (t, 2.0 * (1.3 * t).sin() + 1.0)
+        })
+        .collect();
+    let estimate = fit_from(
+        &wave,
+        &["a", "w", "k"],
+        "t",
+        &data,
+        &HashMap::from([("w", 1.2), ("k", 2.0)]),
+    )
+    .unwrap();
+    assert!(close(estimate.get("a").unwrap(), 2.0, 1e-9));
+    assert!(close(estimate.get("w").unwrap(), 1.3, 1e-9));
+
+    // the best fit has a kink, where the gradient never flattens and steps stop lowering the sum
+    let kink = Equation::new("abs(m - 2)*t + q").unwrap();
+    let data: Vec<(f64, f64)> = (0..10).map(|i| (i as f64, 1.0 - i as f64 / 2.0)).collect();
+    let estimate = fit(&kink, &["m", "q"], "t", &data).unwrap();
+    assert!(!estimate.converged());
+    assert!(close(estimate.get("m").unwrap(), 2.0, 1e-3));
+
+    // no more points than parameters leaves no estimate of the covariance
+    let estimate = fit(&line, &["m", "q"], "t", &[(0.0, 1.0), (1.0, 3.0)]).unwrap();
+    assert!(estimate.covariance().is_none());
+    assert!(close(estimate.get("m").unwrap(), 2.0, 1e-9));
+
+    assert_eq!(
+        fit(&line, &[], "t", &data).unwrap_err().type_,
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        fit(&line, &["m"], "t", &data).unwrap_err().type_,
+        EquationErrorType::UnsetVariable
+    );
+    assert_eq!(
+        fit(&line, &["m", "t"], "t", &data).unwrap_err().type_,
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        fit(&line, &["m", "q", "m"], "t", &data).unwrap_err().type_,
+        EquationErrorType::Unsupported
+    );
+}
+
+#[test]
+fn sampling() {
+    let finite = |samples: &[(f64, Result<f64, equation_solver::EquationError>)]| {
+        samples
+            .iter()
+            .filter(|(_, y)| y.as_ref().is_ok_and(|y| y.is_finite()))
+            .count()
+    };
+
+    // a straight line needs nothing between the even points
+    let samples = Equation::new("2*x + 1").unwrap().sample("x", -1.0..=1.0, 5);
+    let expected = [
+        (-1.0, -1.0),
+        (-0.5, 0.0),
+        (0.0, 1.0),
+        (0.5, 2.0),
+        (1.0, 3.0),
+    ];
+    assert_eq!(samples.len(), expected.len());
+    for ((x, y), (ex, ey)) in samples.iter().zip(expected) {
+        assert_eq!((*x, y.clone()), (ex, Ok(ey)));
+    }
+    assert!(Equation::new("x")
+        .unwrap()
+        .sample("x", 0.0..=1.0, 0)
+        .is_empty());
+    assert_eq!(
+        Equation::new("x").unwrap().sample("x", 2.0..=3.0, 1),
+        vec![(2.0, Ok(2.0))]
+    );
+    for range in [
+        f64::NEG_INFINITY..=1.0,
+        0.0..=f64::NAN,
+        f64::NAN..=f64::INFINITY,
+    ] {
+        assert!(Equation::new("x").unwrap().sample("x", range, 5).is_empty());
+    }
+
+    // points are added around a jump, in order
+    let samples = Equation::new("floor(x)").unwrap().sample("x", 0.0..=3.0, 4);
+    assert!(samples.len() > 4);
+    assert!(samples.windows(2).all(|w| w[0].0 < w[1].0));
+    let before_jump = samples
+        .iter()
+        .filter(|(x, _)| *x < 1.0)
+        .map(|(x, _)| *x)
+        .fold(0.0, f64::max);
+    assert!(before_jump > 0.99);
+    // a reversed range is refined the same way, from its start to its end
+    let reversed = Equation::new("floor(x)").unwrap().sample("x", 3.0..=0.0, 4);
+    assert_eq!(reversed, samples.into_iter().rev().collect::<Vec<_>>());
+
+    // and where the equation stops being defined
+    let samples = Equation::new("sqrt(x)").unwrap().sample("x", -1.0..=1.0, 3);
+    let first_defined = samples
+        .iter()
+        .find(|(_, y)| y.as_ref().is_ok_and(|y| y.is_finite()))
+        .unwrap()
+        .0;
+    assert_eq!(first_defined, 0.0);
+    assert!(samples.iter().any(|(x, _)| *x < 0.0 && *x > -0.01));
+    assert_eq!(
+        finite(&samples),
+        samples.iter().filter(|(x, _)| *x >= 0.0).count()
+    );
+
+    // a pole gets many more points than the smooth parts
+    let samples = Equation::new("1/x").unwrap().sample("x", -1.0..=1.0, 5);
+    let near = samples.iter().filter(|(x, _)| x.abs() < 0.1).count();
+    assert!(near > 10, "{}", near);
+
+    // each point has its own result
+    let samples = Equation::new("x + y").unwrap().sample("x", 0.0..=1.0, 3);
+    assert_eq!(samples.len(), 3);
+    assert!(samples.iter().all(|(_, y)| y.is_err()));
+
+    #[cfg(feature = "plot")]
+    {
+        use equation_solver::plot;
+
+        let samples = Equation::new("x^2").unwrap().sample("x", -2.0..=2.0, 21);
+        let text = plot::ascii(&samples, 41, 11);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert!(lines.iter().all(|line| line.chars().count() == 41));
+        // the ends of the parabola are at the top corners, and the y axis runs down the middle
+        assert!(lines[1].starts_with('*') && lines[1].ends_with('*'));
+        assert!(lines
+            .iter()
+            .all(|line| matches!(line.chars().nth(20), Some('|' | '+' | '*'))));
+        assert!(text.contains('-'));
+        assert_eq!(plot::ascii(&samples, 0, 10), "");
+        let reversed = Equation::new("x^2").unwrap().sample("x", 2.0..=-2.0, 21);
+        assert_eq!(plot::ascii(&reversed, 41, 11), text);
+
+        let svg = plot::svg(&samples, 200, 100);
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<polyline").count(), 1);
+        assert_eq!(svg.matches("<line").count(), 2);
+
+        // the curve breaks at a pole, and at values that aren't defined
+        let samples = Equation::new("tan(x)").unwrap().sample("x", 0.0..=3.0, 31);
+        assert_eq!(
+            plot::svg(&samples, 200, 100).matches("<polyline").count(),
+            2
+        );
+        let samples = Equation::new("1/x").unwrap().sample("x", -1.0..=1.0, 5);
+        assert_eq!(
+            plot::svg(&samples, 200, 100).matches("<polyline").count(),
+            2
+        );
+        assert_eq!(plot::svg(&[], 20, 10).matches("<polyline").count(), 0);
+    }
+}
+
+#[test]
+fn equivalence() {
+    use equation_solver::equivalence::Equivalence;
+
+    let compare = |a: &str, b: &str| {
+        Equation::new(a)
+            .unwrap()
+            .is_equivalent(&Equation::new(b).unwrap())
+    };
+
+    // polynomials are multiplied out, in every variable
+    for (a, b) in [
+        ("(x + 1)^2", "x^2 + 2*x + 1"),
+        ("(x + y)^3", "x^3 + 3*x^2*y + 3*x*y^2 + y^3"),
+        ("(a - b)*(a + b)", "a^2 - b^2"),
+        ("x*2", "x + x"),
+        ("sin(x)*(y + 1)^2", "sin(x)*y^2 + 2*sin(x)*y + sin(x)"),
+    ] {
+        assert_eq!(compare(a, b), Equivalence::Proven, "{} and {}", a, b);
+    }
+
+    // identities it can't simplify are checked at random points
+    for (a, b) in [
+        ("sin(x)^2 + cos(x)^2", "1"),
+        ("(x^2 - 1)/(x - 1)", "x + 1"),
+        ("1/x + 1/y", "(x + y)/(x*y)"),
+    ] {
+        let equivalence = compare(a, b);
+        assert!(
+            matches!(equivalence, Equivalence::Likely(n) if n > 100),
+            "{} and {}: {:?}",
+            a,
+            b,
+            equivalence
+        );
+        assert!(equivalence.holds());
+    }
+
+    // and a difference gives values where it shows
+    for (a, b) in [
+        ("(x + 1)^2", "x^2 + 1"),
+        ("abs(x)", "x"),
+        ("x^2", "x^3"),
+        ("sqrt(x)^2", "x"),
+        ("x", "y"),
+    ] {
+        let Equivalence::Different(bindings) = compare(a, b) else {
+            panic!("{} and {} aren't the same", a, b);
+        };
+        let bindings: std::collections::HashMap<&str, f64> = bindings
+            .iter()
+            .map(|(variable, value)| (variable.as_str(), *value))
+            .collect();
+        let lhs = Equation::new(a).unwrap().evaluate_with_bindings(&bindings);
+        let rhs = Equation::new(b).unwrap().evaluate_with_bindings(&bindings);
+        assert_ne!(lhs, rhs, "{} and {} at {:?}", a, b, bindings);
+    }
+    assert_eq!(
+        compare("2", "3"),
+        Equivalence::Different(Default::default())
+    );
+    assert!(!compare("2", "3").holds());
+
+    // an infinity is only the same as itself
+    for (a, b) in [("1/0", "5"), ("x/0", "x"), ("1/0", "-1/0")] {
+        let equivalence = compare(a, b);
+        assert!(
+            matches!(equivalence, Equivalence::Different(_)),
+            "{} and {}: {:?}",
+            a,
+            b,
+            equivalence
+        );
+        assert!(!equivalence.holds());
+    }
+    assert!(compare("1/0", "2/0").holds());
+
+    // equations that are never defined can't be compared
+    for (a, b) in [
+        ("sqrt(-1 - x^2)", "5*ln(-1 - x^2) + 7"),
+        ("sqrt(-1)", "ln(-1)"),
+    ] {
+        assert_eq!(compare(a, b), Equivalence::Undefined, "{} and {}", a, b);
+        assert!(!compare(a, b).holds());
+    }
+}
+
+#[test]
+fn canonical_forms() {
+    use equation_solver::equation::AngleMode;
+    use std::collections::{HashMap, HashSet};
+
+    let eq = |s: &str| Equation::new(s).unwrap();
+    assert_eq!(eq("x*y + 1"), eq("x * y+1"));
+    assert_ne!(eq("x*y + 1"), eq("1 + y*x"));
+    assert_ne!(eq("x + 1"), eq("x + 2"));
+    let mut degrees = eq("sin(x)");
+    degrees.set_angle_mode(AngleMode::Degrees);
+    assert_ne!(degrees, eq("sin(x)"));
+
+    let same = [
+        ("x*y + 1", "1 + y*x"),
+        ("a - b + c", "c - b + a"),
+        ("a/b*c", "c*a/b"),
+        ("-a + b", "b - a"),
+        ("(-a)*b", "-(b*a)"),
+        ("sin(b + a)^2", "sin(a + b)^2"),
+        ("a*(1/b)", "a/b"),
+        ("1*x*1", "x"),
+        ("-0", "0"),
+    ];
+    for (a, b) in same {
+        assert_eq!(
+            eq(a).canonicalize(),
+            eq(b).canonicalize(),
+            "{} and {}",
+            a,
+            b
+        );
+    }
+    let set: HashSet<Equation> = same
+        .iter()
+        .flat_map(|(a, b)| [eq(a).canonicalize(), eq(b).canonicalize()])
+        .collect();
+    assert_eq!(set.len(), same.len());
+
+    for (a, b) in [("a - b", "b - a"), ("a/b", "b/a"), ("x + x", "2*x")] {
+        assert_ne!(
+            eq(a).canonicalize(),
+            eq(b).canonicalize(),
+            "{} and {}",
+            a,
+            b
+        );
+    }
+
+    let bindings = HashMap::from([("a", 1.5), ("b", -2.25), ("c", 4.0)]);
+    for formula in ["c - b/a*c + a", "-(a*b) - c/b", "a^b - c"] {
+        assert_eq!(
+            eq(formula).canonicalize().evaluate_with_bindings(&bindings),
+            eq(formula).evaluate_with_bindings(&bindings)
+        );
+    }
+    assert_eq!(degrees.canonicalize().angle_mode(), AngleMode::Degrees);
+
+    // division rounds in the integers, so rearranging it changes their values
+    assert_eq!(eq("3/2*2").evaluate_as::<i64>(), Ok(2));
+    assert_eq!(eq("3/2*2").canonicalize(), eq("2*3/2").canonicalize());
+    assert_eq!(eq("3/2*2").canonicalize().evaluate(), Ok(3.0));
+
+    let mut nan = eq("x");
+    nan.set_value("x", f64::NAN);
+    let mut other = eq("x");
+    other.set_value("x", f64::NAN);
+    assert_eq!(nan, other);
+    let mut zero = eq("x");
+    zero.set_value("x", 0.0);
+    let mut negative_zero = eq("x");
+    negative_zero.set_value("x", -0.0);
+    assert_eq!(zero, negative_zero);
+}
+
+#[test]
+fn compiled() {
+    use equation_solver::compile::Node;
+    use equation_solver::equation::AngleMode;
+    use equation_solver::error::EquationErrorType;
+    use std::collections::HashMap;
+
+    let mut eq = Equation::new("a*a + a/y - sin(a)").unwrap();
+    eq.set_equation("a", Equation::new("x^2 + cos(x)").unwrap());
+    let compiled = eq.compile().unwrap();
+    assert_eq!(compiled.variables(), ["x", "y"]);
+    // x, 2, x^2, cos(x), a, a*a, y, a/y, a*a + a/y, sin(a) and the difference
+    assert_eq!(compiled.nodes().len(), 11);
+    let uses = compiled.uses();
+    let a = compiled
+        .nodes()
+        .iter()
+        .position(|node| matches!(node, Node::Operator(_, _, rhs) if matches!(compiled.nodes()[*rhs], Node::Function(..))))
+        .unwrap();
+    assert_eq!(uses[a], 4);
+    assert_eq!(uses[compiled.root()], 1);
+    for (i, node) in compiled.nodes().iter().enumerate() {
+        assert!(node.operands().iter().all(|operand| *operand < i));
+    }
+    for (x, y) in [(0.5, 2.0), (-3.0, 0.25), (10.0, -7.0)] {
+        let bindings = HashMap::from([("x", x), ("y", y)]);
+        assert_eq!(
+            compiled.evaluate(&[x, y]),
+            eq.evaluate_with_bindings(&bindings)
+        );
+        assert_eq!(
+            compiled.evaluate_with_bindings(&bindings),
+            eq.evaluate_with_bindings(&bindings)
+        );
+    }
+    assert_eq!(
+        compiled.evaluate(&[1.0]).unwrap_err().type_,
+        EquationErrorType::Unsupported
+    );
+    assert_eq!(
+        compiled
+            .evaluate_with_bindings(&HashMap::from([("x", 1.0)]))
+            .unwrap_err()
+            .type_,
+        EquationErrorType::UnsetVariable
+    );
+
+    let mut degrees = Equation::new("sin(x) + arcsin(y)").unwrap();
+    degrees.set_angle_mode(AngleMode::Degrees);
+    let compiled = degrees.compile().unwrap();
+    assert_eq!(
+        compiled.evaluate(&[30.0, 1.0]),
+        degrees.evaluate_with_bindings(&HashMap::from([("x", 30.0), ("y", 1.0)]))
+    );
+
+    // -0 isn't shared with 0
+    let mut zeros = Equation::new("1/x - 1/0").unwrap();
+    zeros.set_value("x", -0.0);
+    assert_eq!(zeros.evaluate(), Ok(f64::NEG_INFINITY));
+    assert_eq!(zeros.compile().unwrap().evaluate(&[]), zeros.evaluate());
+    assert_eq!(
+        Equation::new("x")
+            .unwrap()
+            .compile()
+            .unwrap()
+            .evaluate(&[2.5]),
+        Ok(2.5)
+    );
+}
+
+#[cfg(feature = "jit")]
+#[test]
+fn jit() {
+    use equation_solver::equation::AngleMode;
+    use std::collections::HashMap;
+
+    let mut eq =
+        Equation::new("a*a + sqrt(abs(a))/y - sin(a)^2 + 3√y + floor(x) + gamma(y) + pi").unwrap();
+    eq.set_equation("a", Equation::new("x^2 + cos(x) - round(x)").unwrap());
+    let f = eq.jit().unwrap();
+    assert!(f.is_native());
+    assert_eq!(f.variables(), ["x", "y"]);
+    for (x, y) in [
+        (0.5, 2.0),
+        (-3.5, 0.25),
+        (10.0, -7.0),
+        (1e3, 0.0),
+        (-0.0, -8.0),
+    ] {
+        let expected = eq
+            .evaluate_with_bindings(&HashMap::from([("x", x), ("y", y)]))
+            .unwrap();
+        let value = f.call(&[x, y]);
+        assert!(
+            value.to_bits() == expected.to_bits() || (value.is_nan() && expected.is_nan()),
+            "{} at {}, {}: {} != {}",
+            eq,
+            x,
+            y,
+            value,
+            expected
+        );
+    }
+
+    let f = eq.jit_with_variables(&["y", "z", "x"]).unwrap();
+    assert_eq!(f.variables(), ["y", "z", "x"]);
+    assert_eq!(
+        f.call(&[2.0, 100.0, 0.5]),
+        eq.evaluate_with_bindings(&HashMap::from([("x", 0.5), ("y", 2.0)]))
+            .unwrap()
+    );
+    assert!(eq.jit_with_variables(&["x"]).is_err());
+
+    let mut degrees = Equation::new("sin(x) + arctan(x)").unwrap();
+    degrees.set_angle_mode(AngleMode::Degrees);
+    assert_eq!(
+        degrees.jit().unwrap().call(&[30.0]),
+        degrees
+            .evaluate_with_bindings(&HashMap::from([("x", 30.0)]))
+            .unwrap()
+    );
+    assert_eq!(
+        Equation::new("2^10").unwrap().jit().unwrap().call(&[]),
+        1024.0
+    );
+    assert_eq!(
+        Equation::new("-x")
+            .unwrap()
+            .jit()
+            .unwrap()
+            .call(&[0.0])
+            .to_bits(),
+        (-0.0f64).to_bits()
+    );
+}